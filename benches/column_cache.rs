@@ -0,0 +1,60 @@
+//! Compares hashing tuple leaves whose dynamic columns repeat with the column cache of `root_of`
+//! and without it, for 100k leaves.
+//!
+//! `root_of` also folds the leaf hashes into the tree, so the difference understates what the cache
+//! saves. Hashing dominates either way: the cache only saves re-encoding the columns.
+//!
+//! Run with `cargo bench --bench column_cache`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use alloy_dyn_abi::DynSolValue;
+use alloy_merkle_tree::standard_binary_tree::{standard_leaf_hash, StandardMerkleTree};
+use alloy_primitives::{Address, B256, U256};
+
+const LEAVES: u64 = 100_000;
+const ITERATIONS: u32 = 10;
+
+/// Returns the mean duration of `ITERATIONS` runs of `f`.
+fn time<T>(mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    // Ten tokens, each with a name and a list of tags shared by every leaf of that token.
+    let leaves: Vec<DynSolValue> = (0..LEAVES)
+        .map(|i| {
+            let token = i % 10;
+            DynSolValue::Tuple(vec![
+                DynSolValue::Address(Address::with_last_byte(token as u8)),
+                DynSolValue::String(format!("token number {token} of the airdrop")),
+                DynSolValue::Array(vec![DynSolValue::Uint(U256::from(token), 256); 16]),
+                DynSolValue::Uint(U256::from(i), 256),
+            ])
+        })
+        .collect();
+    let leaf_hashes: Vec<B256> = leaves
+        .iter()
+        .map(|leaf| standard_leaf_hash(leaf).unwrap())
+        .collect();
+    assert_eq!(
+        StandardMerkleTree::root_of(&leaves).unwrap(),
+        StandardMerkleTree::of_hashes(&leaf_hashes).root()
+    );
+
+    let cached = time(|| StandardMerkleTree::root_of(black_box(&leaves)).unwrap());
+    let uncached = time(|| {
+        black_box(&leaves)
+            .iter()
+            .map(|leaf| standard_leaf_hash(leaf).unwrap())
+            .collect::<Vec<B256>>()
+    });
+
+    println!("root_of, cached columns       {cached:?}");
+    println!("standard_leaf_hash, uncached  {uncached:?}");
+}
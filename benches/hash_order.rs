@@ -0,0 +1,51 @@
+//! Compares building the same 100k-leaf tree with sorted and with positional pair hashing.
+//!
+//! Run with `cargo bench --bench hash_order`.
+//!
+//! Both orders hash the same number of pairs, and keccak256 dominates the build, so expect the two
+//! to be within a few percent of each other. Sorted hashing adds one 32-byte comparison per pair,
+//! while positional hashing reads the direction from the parity of the node index, so neither needs
+//! extra memory; the choice should be made for on-chain compatibility, not speed.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use alloy_dyn_abi::DynSolValue;
+use alloy_merkle_tree::standard_binary_tree::{HashOrder, StandardMerkleTree};
+use alloy_primitives::U256;
+
+const LEAVES: u64 = 100_000;
+const ITERATIONS: u32 = 10;
+
+/// Returns the mean duration of `ITERATIONS` runs of `f`.
+fn time<T>(mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+/// Builds a tree of `leaves` with the given pair hashing order.
+fn build(leaves: &[DynSolValue], order: HashOrder) -> StandardMerkleTree {
+    StandardMerkleTree::builder()
+        .hash_order(order)
+        .build(black_box(leaves))
+        .unwrap()
+}
+
+fn main() {
+    let leaves: Vec<DynSolValue> = (0..LEAVES)
+        .map(|i| DynSolValue::Uint(U256::from(i), 256))
+        .collect();
+
+    let sorted = time(|| build(&leaves, HashOrder::Sorted).root());
+    let positional = time(|| build(&leaves, HashOrder::Positional).root());
+
+    println!("sorted      {sorted:?}");
+    println!("positional  {positional:?}");
+    println!(
+        "difference  {:+.1}%",
+        (positional.as_secs_f64() / sorted.as_secs_f64() - 1.0) * 100.0
+    );
+}
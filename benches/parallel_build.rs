@@ -0,0 +1,61 @@
+//! Compares building a 100k-leaf tree with a plain sequential build of the same tree, and checks
+//! that both have the same root.
+//!
+//! Run with `cargo bench --bench parallel_build --features rayon` to time the parallel build, or
+//! without the feature for the sequential one. Besides hashing, `of` indexes every value for proof
+//! lookups, so on a single core it is slower than the bare sequential build.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use alloy_dyn_abi::DynSolValue;
+use alloy_merkle_tree::standard_binary_tree::StandardMerkleTree;
+use alloy_primitives::{keccak256, B256, U256};
+
+const LEAVES: u64 = 100_000;
+const ITERATIONS: u32 = 10;
+
+/// Returns the mean duration of `ITERATIONS` runs of `f`, and the result of the last one.
+fn time<T>(mut f: impl FnMut() -> T) -> (Duration, T) {
+    let start = Instant::now();
+    for _ in 1..ITERATIONS {
+        black_box(f());
+    }
+    let result = f();
+    (start.elapsed() / ITERATIONS, result)
+}
+
+/// Computes the root of a tree of `uint256` leaves one node at a time, without the crate.
+fn sequential_root(leaves: &[U256]) -> B256 {
+    let tree_len = 2 * leaves.len() - 1;
+    let mut nodes = vec![B256::ZERO; tree_len];
+    for (i, leaf) in leaves.iter().enumerate() {
+        nodes[tree_len - 1 - i] = keccak256(keccak256(leaf.to_be_bytes::<32>()));
+    }
+    for i in (0..tree_len - leaves.len()).rev() {
+        let (a, b) = (nodes[2 * i + 1], nodes[2 * i + 2]);
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        nodes[i] = keccak256([first.as_slice(), second.as_slice()].concat());
+    }
+    nodes[0]
+}
+
+fn main() {
+    let values: Vec<U256> = (0..LEAVES).map(U256::from).collect();
+    let leaves: Vec<DynSolValue> = values
+        .iter()
+        .map(|value| DynSolValue::Uint(*value, 256))
+        .collect();
+
+    let (of, root) = time(|| {
+        StandardMerkleTree::of(black_box(&leaves))
+            .unwrap()
+            .root()
+            .unwrap()
+    });
+    let (sequential, sequential_root) = time(|| sequential_root(black_box(&values)));
+    assert_eq!(root, sequential_root);
+
+    println!("of          {of:?}");
+    println!("sequential  {sequential:?}");
+}
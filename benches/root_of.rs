@@ -0,0 +1,34 @@
+//! Compares computing only the root with building the whole tree, for 100k leaves.
+//!
+//! Run with `cargo bench --bench root_of`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use alloy_dyn_abi::DynSolValue;
+use alloy_merkle_tree::standard_binary_tree::StandardMerkleTree;
+use alloy_primitives::U256;
+
+const LEAVES: u64 = 100_000;
+const ITERATIONS: u32 = 10;
+
+/// Returns the mean duration of `ITERATIONS` runs of `f`.
+fn time<T>(mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let leaves: Vec<DynSolValue> = (0..LEAVES)
+        .map(|i| DynSolValue::Uint(U256::from(i), 256))
+        .collect();
+
+    let root_of = time(|| StandardMerkleTree::root_of(black_box(&leaves)).unwrap());
+    let of_root = time(|| StandardMerkleTree::of(black_box(&leaves)).unwrap().root());
+
+    println!("root_of      {root_of:?}");
+    println!("of().root()  {of_root:?}");
+}
@@ -0,0 +1,62 @@
+//! Compares hashing leaves one by one with hashing them in batches, and times building a tree of
+//! 100k leaves. With the `simd` feature, batches are hashed in parallel lanes.
+//!
+//! Run with `cargo bench --bench simd_keccak`, then with `--features simd` to compare the builds.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use alloy_dyn_abi::DynSolValue;
+use alloy_merkle_tree::standard_binary_tree::{
+    Hasher, Keccak256Hasher, StandardMerkleTree, HASH_BATCH,
+};
+use alloy_primitives::{B256, U256};
+
+const LEAVES: u64 = 100_000;
+const ITERATIONS: u32 = 10;
+
+/// Returns the mean duration of `ITERATIONS` runs of `f`.
+fn time<T>(mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let encodings: Vec<[u8; 32]> = (0..LEAVES)
+        .map(|i| U256::from(i).to_be_bytes::<32>())
+        .collect();
+    let one_by_one = || {
+        encodings
+            .iter()
+            .map(|encoding| Keccak256Hasher::hash_leaf(encoding))
+            .collect::<Vec<B256>>()
+    };
+    let batched = || {
+        encodings
+            .chunks_exact(HASH_BATCH)
+            .flat_map(|batch| {
+                Keccak256Hasher::hash_leaf_batch(core::array::from_fn(|lane| &batch[lane][..]))
+            })
+            .collect::<Vec<B256>>()
+    };
+    assert_eq!(one_by_one(), batched());
+
+    let leaves: Vec<DynSolValue> = (0..LEAVES)
+        .map(|i| DynSolValue::Uint(U256::from(i), 256))
+        .collect();
+
+    let one_by_one = time(one_by_one);
+    let batched = time(batched);
+    let root_of = time(|| StandardMerkleTree::root_of(black_box(&leaves)).unwrap());
+    let of = time(|| StandardMerkleTree::of(black_box(&leaves)).unwrap().root());
+
+    let simd = if cfg!(feature = "simd") { "on" } else { "off" };
+    println!("simd feature {simd}");
+    println!("hash_leaf        {one_by_one:?}");
+    println!("hash_leaf_batch  {batched:?}");
+    println!("root_of          {root_of:?}");
+    println!("of().root()      {of:?}");
+}
@@ -0,0 +1,33 @@
+//! A `no_std` library building a tree and checking its proofs with only `alloc`.
+//!
+//! The default build of the crate doesn't require `std`, which CI checks on a target without one:
+//! `cargo build --lib --target thumbv7em-none-eabi`. The example itself links the dev-dependencies,
+//! which need `std`, so it is built for the host: `cargo build --example no_std`. It is a library
+//! rather than a binary, since a `no_std` binary needs a platform-specific entry point and panic
+//! handler.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use alloy_dyn_abi::DynSolValue;
+use alloy_merkle_tree::standard_binary_tree::{verify, MerkleTreeError, StandardMerkleTree};
+use alloy_primitives::B256;
+
+/// Builds the tree of `count` string leaves and returns its root once every proof verifies.
+pub fn checked_root(count: usize) -> Result<B256, MerkleTreeError> {
+    let leaves = (0..count)
+        .map(|i| DynSolValue::String(i.to_string()))
+        .collect::<Vec<DynSolValue>>();
+    let tree = StandardMerkleTree::of(&leaves)?;
+    let root = tree.root().ok_or(MerkleTreeError::LeafNotFound)?;
+    for leaf in leaves.iter() {
+        if !verify(root, leaf, &tree.get_proof(leaf)?) {
+            return Err(MerkleTreeError::InvalidCheck);
+        }
+    }
+
+    Ok(root)
+}
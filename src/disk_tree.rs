@@ -0,0 +1,188 @@
+//! This module contains the [DiskTree], a standard Merkle tree stored in a memory-mapped file, for
+//! trees too large to be kept in memory.
+//!
+//! The file is the flat node array of the tree, 32 bytes per node, in the layout of
+//! [`StandardMerkleTree::nodes`](crate::standard_binary_tree::StandardMerkleTree::nodes). Proofs
+//! only read the siblings along the path of their leaf, so the operating system pages in a handful
+//! of nodes per proof however large the tree is.
+
+use alloc::vec::Vec;
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::B256;
+use memmap2::{Mmap, MmapMut};
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+
+use crate::standard_binary_tree::{
+    hash_pair, parent_index, sibling_index, standard_leaf_hash, MerkleTreeError,
+};
+
+/// The size of a node in the file.
+const NODE_SIZE: usize = 32;
+
+/// A standard Merkle tree whose nodes are read from a memory-mapped file.
+#[derive(Debug)]
+pub struct DiskTree {
+    /// The node array of the tree.
+    nodes: Mmap,
+    /// The number of nodes in the tree.
+    node_count: usize,
+}
+
+impl DiskTree {
+    /// Builds the tree of `values` into the file at `path`, replacing it, with the same nodes as
+    /// [`StandardMerkleTree::of`](crate::standard_binary_tree::StandardMerkleTree::of).
+    ///
+    /// The leaf hashes are written first, then every level from the deepest one up, reading the
+    /// level below from the file, so only the values are held in memory. Returns
+    /// [`MerkleTreeError::NotSupportedType`] if a value isn't of a supported leaf type, and
+    /// [`MerkleTreeError::DiskAccess`] if the file can't be written or `values` is empty.
+    pub fn build(path: impl AsRef<Path>, values: &[DynSolValue]) -> Result<Self, MerkleTreeError> {
+        if values.is_empty() {
+            return Err(MerkleTreeError::DiskAccess);
+        }
+        let node_count = 2 * values.len() - 1;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|_| MerkleTreeError::DiskAccess)?;
+        file.set_len((node_count * NODE_SIZE) as u64)
+            .map_err(|_| MerkleTreeError::DiskAccess)?;
+        // SAFETY: the file was just created for the tree and isn't expected to be modified by
+        // anything else while it is mapped.
+        let mut nodes =
+            unsafe { MmapMut::map_mut(&file) }.map_err(|_| MerkleTreeError::DiskAccess)?;
+
+        // Leaves are stored at the end of the array, in reverse order.
+        for (leaf_index, value) in values.iter().enumerate() {
+            write_node(
+                &mut nodes,
+                node_count - 1 - leaf_index,
+                standard_leaf_hash(value)?,
+            );
+        }
+        // A node's children come after it, so walking back from the last internal node computes
+        // every level before the one above it.
+        for index in (0..node_count - values.len()).rev() {
+            let hash = hash_pair(
+                read_node(&nodes, 2 * index + 1),
+                read_node(&nodes, 2 * index + 2),
+            );
+            write_node(&mut nodes, index, hash);
+        }
+        nodes.flush().map_err(|_| MerkleTreeError::DiskAccess)?;
+
+        Ok(Self {
+            nodes: nodes
+                .make_read_only()
+                .map_err(|_| MerkleTreeError::DiskAccess)?,
+            node_count,
+        })
+    }
+
+    /// Opens a tree previously built with [`Self::build`] at `path`.
+    ///
+    /// Returns [`MerkleTreeError::DiskAccess`] if the file can't be read or isn't a node array of
+    /// an odd number of nodes.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, MerkleTreeError> {
+        let file = File::open(path).map_err(|_| MerkleTreeError::DiskAccess)?;
+        // SAFETY: the file isn't expected to be modified while it is mapped, as with `build`.
+        let nodes = unsafe { Mmap::map(&file) }.map_err(|_| MerkleTreeError::DiskAccess)?;
+        let node_count = nodes.len() / NODE_SIZE;
+        if nodes.len() % NODE_SIZE != 0 || node_count % 2 == 0 {
+            return Err(MerkleTreeError::DiskAccess);
+        }
+
+        Ok(Self { nodes, node_count })
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> B256 {
+        read_node(&self.nodes, 0)
+    }
+
+    /// Returns the number of leaves in the tree.
+    pub fn leaf_count(&self) -> usize {
+        (self.node_count + 1) / 2
+    }
+
+    /// Generates a Merkle proof for the leaf at `leaf_index`, reading only the siblings along its
+    /// path from the file.
+    ///
+    /// Returns [`MerkleTreeError::LeafNotFound`] if `leaf_index` is out of bounds.
+    pub fn get_proof_by_index(&self, leaf_index: usize) -> Result<Vec<B256>, MerkleTreeError> {
+        if leaf_index >= self.leaf_count() {
+            return Err(MerkleTreeError::LeafNotFound);
+        }
+
+        let mut proof = Vec::new();
+        let mut current_index = self.node_count - 1 - leaf_index;
+        while current_index > 0 {
+            proof.push(read_node(&self.nodes, sibling_index(current_index)?));
+            current_index = parent_index(current_index);
+        }
+
+        Ok(proof)
+    }
+}
+
+/// Reads the node at `index` of a node array.
+fn read_node(nodes: &[u8], index: usize) -> B256 {
+    B256::from_slice(&nodes[index * NODE_SIZE..(index + 1) * NODE_SIZE])
+}
+
+/// Writes the node at `index` of a node array.
+fn write_node(nodes: &mut [u8], index: usize, node: B256) {
+    nodes[index * NODE_SIZE..(index + 1) * NODE_SIZE].copy_from_slice(node.as_slice());
+}
+
+#[cfg(test)]
+mod test {
+    use super::DiskTree;
+    use crate::alloc::string::ToString;
+    use crate::standard_binary_tree::{verify, MerkleTreeError, StandardMerkleTree};
+    use alloc::format;
+    use alloc::vec::Vec;
+    use alloy_dyn_abi::DynSolValue;
+
+    /// Tests building a tree on disk and verifying the proofs read back from the file.
+    #[test]
+    fn test_disk_tree() {
+        let path = std::env::temp_dir().join(format!("disk_tree_{}.bin", std::process::id()));
+        let leaves: Vec<DynSolValue> = (0..13)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        let built = DiskTree::build(&path, &leaves).unwrap();
+        assert_eq!(built.root(), tree.root().unwrap());
+        assert_eq!(std::fs::read(&path).unwrap(), tree.nodes().concat());
+        drop(built);
+
+        let disk = DiskTree::open(&path).unwrap();
+        assert_eq!(disk.leaf_count(), leaves.len());
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let proof = disk.get_proof_by_index(leaf_index).unwrap();
+            assert_eq!(proof, tree.get_proof_by_index(leaf_index).unwrap());
+            assert!(verify(disk.root(), leaf, &proof));
+        }
+        assert_eq!(
+            disk.get_proof_by_index(leaves.len()),
+            Err(MerkleTreeError::LeafNotFound)
+        );
+
+        std::fs::write(&path, [0u8; 64]).unwrap();
+        assert!(matches!(
+            DiskTree::open(&path),
+            Err(MerkleTreeError::DiskAccess)
+        ));
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            DiskTree::build(&path, &[]),
+            Err(MerkleTreeError::DiskAccess)
+        ));
+    }
+}
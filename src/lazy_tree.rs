@@ -0,0 +1,125 @@
+//! This module contains the [LazyTree], a verifier for a [StandardMerkleTree] that only holds the
+//! root and fetches the nodes it needs on demand.
+//!
+//! This minimizes the data transferred for one-off verifications against a huge tree, e.g. when its
+//! nodes are served from a CDN.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use alloy_merkle_tree::lazy_tree::LazyTree;
+//! use alloy_merkle_tree::standard_binary_tree::StandardMerkleTree;
+//! use alloy_dyn_abi::DynSolValue;
+//!
+//! let leaves: Vec<DynSolValue> = (0..100).map(|i| DynSolValue::String(i.to_string())).collect();
+//! let tree = StandardMerkleTree::of(&leaves).unwrap();
+//!
+//! let lazy = LazyTree::new(tree.root().unwrap(), leaves.len(), |index| tree.nodes().get(index).copied());
+//! assert!(lazy.verify(&leaves[42], 42).unwrap());
+//! ```
+//!
+//! [StandardMerkleTree]: crate::standard_binary_tree::StandardMerkleTree
+
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::B256;
+
+use crate::standard_binary_tree::{
+    hash_pair, parent_index, sibling_index, standard_leaf_hash, MerkleTreeError,
+};
+
+/// A verifier for a standard Merkle tree that fetches the sibling nodes of a leaf on demand.
+#[derive(Debug)]
+pub struct LazyTree<F> {
+    /// The trusted root of the tree.
+    root: B256,
+    /// The number of leaves in the tree.
+    leaf_count: usize,
+    /// Fetches the node at a given index of the flat tree array, in the layout of
+    /// [`StandardMerkleTree::nodes`](crate::standard_binary_tree::StandardMerkleTree::nodes).
+    fetcher: F,
+}
+
+impl<F: Fn(usize) -> Option<B256>> LazyTree<F> {
+    /// Creates a new [`LazyTree`] for a tree with the given root and number of leaves.
+    pub fn new(root: B256, leaf_count: usize, fetcher: F) -> Self {
+        Self {
+            root,
+            leaf_count,
+            fetcher,
+        }
+    }
+
+    /// Returns the trusted root of the tree.
+    pub fn root(&self) -> B256 {
+        self.root
+    }
+
+    /// Verifies that `leaf` is the leaf at `leaf_index`, fetching only the siblings along its path.
+    ///
+    /// # Returns
+    /// - `Ok(true)` - If folding the leaf with the fetched siblings reproduces the root.
+    /// - `Ok(false)` - If it doesn't.
+    /// - `Err(MerkleTreeError::LeafNotFound)` - If `leaf_index` is out of bounds.
+    /// - `Err(MerkleTreeError::NodeUnavailable)` - If a sibling could not be fetched.
+    /// - `Err(MerkleTreeError::NotSupportedType)` - If `leaf` isn't of a supported leaf type.
+    pub fn verify(&self, leaf: &DynSolValue, leaf_index: usize) -> Result<bool, MerkleTreeError> {
+        if leaf_index >= self.leaf_count {
+            return Err(MerkleTreeError::LeafNotFound);
+        }
+
+        let mut current_index = 2 * self.leaf_count - 2 - leaf_index;
+        let mut hash = standard_leaf_hash(leaf)?;
+        while current_index > 0 {
+            let sibling = sibling_index(current_index)?;
+            let sibling_hash =
+                (self.fetcher)(sibling).ok_or(MerkleTreeError::NodeUnavailable(sibling))?;
+            hash = hash_pair(hash, sibling_hash);
+            current_index = parent_index(current_index);
+        }
+
+        Ok(hash == self.root)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LazyTree;
+    use crate::alloc::string::ToString;
+    use crate::standard_binary_tree::{MerkleTreeError, StandardMerkleTree};
+    use alloc::vec::Vec;
+    use alloy_dyn_abi::DynSolValue;
+    use core::cell::Cell;
+
+    /// Tests [`LazyTree`] against a fetcher backed by a full in-memory tree.
+    #[test]
+    fn test_lazy_tree_verify() {
+        let leaves: Vec<DynSolValue> = (0..37)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        let fetched = Cell::new(0);
+        let lazy = LazyTree::new(tree.root().unwrap(), leaves.len(), |index| {
+            fetched.set(fetched.get() + 1);
+            tree.nodes().get(index).copied()
+        });
+
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            fetched.set(0);
+            assert!(lazy.verify(leaf, leaf_index).unwrap());
+            // Only the siblings along the path are fetched.
+            assert_eq!(fetched.get(), tree.get_proof(leaf).unwrap().len());
+        }
+        assert!(!lazy.verify(&leaves[0], 1).unwrap());
+        assert!(matches!(
+            lazy.verify(&leaves[0], leaves.len()),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+
+        let unavailable = LazyTree::new(tree.root().unwrap(), leaves.len(), |_| None);
+        assert!(matches!(
+            unavailable.verify(&leaves[0], 0),
+            Err(MerkleTreeError::NodeUnavailable(_))
+        ));
+    }
+}
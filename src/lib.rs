@@ -1,7 +1,22 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
+#[cfg(feature = "mmap")]
+pub mod disk_tree;
 pub mod incremental_tree;
+pub mod lazy_tree;
+#[cfg(feature = "poseidon")]
+pub mod poseidon;
+#[cfg(feature = "provider")]
+pub mod provider;
+#[cfg(feature = "simd")]
+mod simd;
+pub mod snapshot_log;
 pub mod standard_binary_tree;
+#[cfg(feature = "stats")]
+pub mod stats;
 pub mod tree;
+pub mod windowed_tree;
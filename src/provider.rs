@@ -0,0 +1,192 @@
+//! This module contains [verify_against_contract], which verifies a proof against the root
+//! published by a contract on a live chain.
+
+use alloy::network::{Network, TransactionBuilder};
+use alloy::providers::Provider;
+use alloy::transports::Transport;
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::{hex, keccak256, Address, Bytes, B256};
+
+use crate::standard_binary_tree::{verify, MerkleTreeError};
+
+/// Verifies that `leaf` is in the tree whose root is returned by the root getter of `contract`.
+///
+/// `root_selector` is either the getter's 4-byte selector in hex, such as `0x2eb4a7ab`, or its
+/// signature, such as `merkleRoot()`. The getter is called without arguments and must return a
+/// single `bytes32`.
+///
+/// Returns [`MerkleTreeError::InvalidSelector`] if `root_selector` is neither, or
+/// [`MerkleTreeError::RootCallFailed`] if the call fails or returns anything but 32 bytes.
+pub async fn verify_against_contract<P, T, N>(
+    provider: &P,
+    contract: Address,
+    root_selector: &str,
+    leaf: &DynSolValue,
+    proof: &[B256],
+) -> Result<bool, MerkleTreeError>
+where
+    P: Provider<T, N>,
+    T: Transport + Clone,
+    N: Network,
+{
+    let tx = N::TransactionRequest::default()
+        .with_to(contract)
+        .with_input(selector(root_selector)?);
+    let output = provider
+        .call(&tx)
+        .await
+        .map_err(|_| MerkleTreeError::RootCallFailed)?;
+    if output.len() != 32 {
+        return Err(MerkleTreeError::RootCallFailed);
+    }
+
+    Ok(verify(B256::from_slice(&output), leaf, proof))
+}
+
+/// Returns the calldata of a call to the getter with the given selector or signature.
+fn selector(root_selector: &str) -> Result<Bytes, MerkleTreeError> {
+    if root_selector.contains('(') {
+        return Ok(Bytes::copy_from_slice(&keccak256(root_selector)[..4]));
+    }
+    match hex::decode(root_selector) {
+        Ok(selector) if selector.len() == 4 => Ok(selector.into()),
+        _ => Err(MerkleTreeError::InvalidSelector),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::verify_against_contract;
+    use crate::alloc::string::ToString;
+    use crate::standard_binary_tree::{MerkleTreeError, StandardMerkleTree};
+    use alloc::boxed::Box;
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use alloy::providers::RootProvider;
+    use alloy::rpc::client::RpcClient;
+    use alloy::rpc::json_rpc::{
+        RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest,
+    };
+    use alloy::transports::{TransportError, TransportFut};
+    use alloy_dyn_abi::DynSolValue;
+    use alloy_primitives::{address, hex, Address};
+    use core::task::{Context, Poll};
+    use futures::executor::block_on;
+    use serde_json::value::RawValue;
+
+    const CONTRACT: Address = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+
+    /// The selector of `merkleRoot()`.
+    const MERKLE_ROOT: &str = "0x2eb4a7ab";
+
+    /// A transport answering every `eth_call` of [`MERKLE_ROOT`] on [`CONTRACT`] with `output`, and
+    /// failing any other.
+    #[derive(Clone)]
+    struct MockTransport {
+        output: String,
+    }
+
+    impl tower::Service<RequestPacket> for MockTransport {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: RequestPacket) -> Self::Future {
+            let RequestPacket::Single(request) = request else {
+                unreachable!("the provider sends single requests");
+            };
+            let response = self.respond(&request);
+            Box::pin(async move { response })
+        }
+    }
+
+    impl MockTransport {
+        fn respond(&self, request: &SerializedRequest) -> Result<ResponsePacket, TransportError> {
+            let params = request.params().map(RawValue::get).unwrap_or_default();
+            if request.method() != "eth_call"
+                || !params.contains(&CONTRACT.to_string().to_lowercase())
+                || !params.contains(MERKLE_ROOT)
+            {
+                return Err(TransportError::local_usage_str("unexpected request"));
+            }
+            let payload = RawValue::from_string(format!("\"{}\"", self.output)).unwrap();
+            Ok(ResponsePacket::Single(Response {
+                id: request.id().clone(),
+                payload: ResponsePayload::Success(payload),
+            }))
+        }
+    }
+
+    fn mock_provider(output: String) -> RootProvider<MockTransport> {
+        RootProvider::new(RpcClient::new(MockTransport { output }, true))
+    }
+
+    /// Tests verifying proofs against the root returned by a mock contract.
+    #[test]
+    fn test_verify_against_contract() {
+        let leaves = (0..5)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let provider = mock_provider(tree.root().unwrap().to_string());
+
+        for leaf in leaves.iter() {
+            let proof = tree.get_proof(leaf).unwrap();
+            for selector in ["merkleRoot()", MERKLE_ROOT] {
+                assert!(block_on(verify_against_contract(
+                    &provider, CONTRACT, selector, leaf, &proof
+                ))
+                .unwrap());
+            }
+        }
+
+        let proof = tree.get_proof(&leaves[0]).unwrap();
+        let outsider = DynSolValue::String("5".to_string());
+        assert!(!block_on(verify_against_contract(
+            &provider,
+            CONTRACT,
+            "merkleRoot()",
+            &outsider,
+            &proof
+        ))
+        .unwrap());
+
+        assert!(matches!(
+            block_on(verify_against_contract(
+                &provider,
+                CONTRACT,
+                "merkleRoot",
+                &leaves[0],
+                &proof
+            )),
+            Err(MerkleTreeError::InvalidSelector)
+        ));
+        assert!(matches!(
+            block_on(verify_against_contract(
+                &provider,
+                Address::ZERO,
+                "merkleRoot()",
+                &leaves[0],
+                &proof
+            )),
+            Err(MerkleTreeError::RootCallFailed)
+        ));
+
+        let short = mock_provider(hex::encode_prefixed([1u8; 31]));
+        assert!(matches!(
+            block_on(verify_against_contract(
+                &short,
+                CONTRACT,
+                "merkleRoot()",
+                &leaves[0],
+                &proof
+            )),
+            Err(MerkleTreeError::RootCallFailed)
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+//! Keccak-256 of several inputs at once, for the leaf-hashing phase of tree building.
+//!
+//! The Keccak-f\[1600\] state holds one lane per input for each of its 25 words, and every step of
+//! the permutation runs across all lanes, so the compiler lowers it to vector instructions on any
+//! target with SIMD registers (SSE2 on x86-64, NEON on AArch64) without nightly features or
+//! `unsafe`. The output is bit-identical to [`alloy_primitives::keccak256`].
+//!
+//! The gain depends on the vector width: a 128-bit register holds two lanes, so baseline x86-64
+//! gains little over scalar keccak, while a build with 256-bit vectors, e.g. with
+//! `-C target-feature=+avx2`, fits all four lanes in one register and hashes leaves about 1.4 times
+//! faster. `benches/simd_keccak.rs` measures it.
+use alloy_primitives::B256;
+
+/// The number of inputs hashed at once.
+pub(crate) const LANES: usize = crate::standard_binary_tree::HASH_BATCH;
+
+/// The rate of Keccak-256, in bytes: the part of the state each block of input is absorbed into.
+const RATE: usize = 136;
+
+/// One word of the state for each lane.
+type Word = [u64; LANES];
+
+/// The round constants of the iota step.
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Returns `a ^ b` in every lane.
+#[inline(always)]
+fn xor(a: Word, b: Word) -> Word {
+    let mut out = [0; LANES];
+    for l in 0..LANES {
+        out[l] = a[l] ^ b[l];
+    }
+    out
+}
+
+/// Returns `!a & b` in every lane.
+#[inline(always)]
+fn and_not(a: Word, b: Word) -> Word {
+    let mut out = [0; LANES];
+    for l in 0..LANES {
+        out[l] = !a[l] & b[l];
+    }
+    out
+}
+
+/// Returns `a` rotated left by `N` bits in every lane.
+#[inline(always)]
+fn rotate_left<const N: u32>(a: Word) -> Word {
+    let mut out = [0; LANES];
+    for l in 0..LANES {
+        out[l] = a[l].rotate_left(N);
+    }
+    out
+}
+
+/// Applies Keccak-f\[1600\] to the state of every lane, where word `x + 5 * y` is at
+/// `state[x + 5 * y]`.
+///
+/// The rho and pi steps are written out word by word so that every rotation is by a constant, which
+/// vector instructions need.
+#[inline(always)]
+fn keccak_f(state: &mut [Word; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        // Theta: each word is mixed with the parities of the two neighbouring columns.
+        let mut parities = [[0; LANES]; 5];
+        for (x, parity) in parities.iter_mut().enumerate() {
+            *parity = xor(
+                xor(
+                    xor(state[x], state[x + 5]),
+                    xor(state[x + 10], state[x + 15]),
+                ),
+                state[x + 20],
+            );
+        }
+        for x in 0..5 {
+            let mix = xor(
+                parities[(x + 4) % 5],
+                rotate_left::<1>(parities[(x + 1) % 5]),
+            );
+            for y in 0..5 {
+                state[x + 5 * y] = xor(state[x + 5 * y], mix);
+            }
+        }
+
+        // Rho and pi: word (x, y) is rotated by its rho offset and moved to (y, 2x + 3y).
+        let mut moved = [[0; LANES]; 25];
+        moved[0] = rotate_left::<0>(state[0]);
+        moved[10] = rotate_left::<1>(state[1]);
+        moved[20] = rotate_left::<62>(state[2]);
+        moved[5] = rotate_left::<28>(state[3]);
+        moved[15] = rotate_left::<27>(state[4]);
+        moved[16] = rotate_left::<36>(state[5]);
+        moved[1] = rotate_left::<44>(state[6]);
+        moved[11] = rotate_left::<6>(state[7]);
+        moved[21] = rotate_left::<55>(state[8]);
+        moved[6] = rotate_left::<20>(state[9]);
+        moved[7] = rotate_left::<3>(state[10]);
+        moved[17] = rotate_left::<10>(state[11]);
+        moved[2] = rotate_left::<43>(state[12]);
+        moved[12] = rotate_left::<25>(state[13]);
+        moved[22] = rotate_left::<39>(state[14]);
+        moved[23] = rotate_left::<41>(state[15]);
+        moved[8] = rotate_left::<45>(state[16]);
+        moved[18] = rotate_left::<15>(state[17]);
+        moved[3] = rotate_left::<21>(state[18]);
+        moved[13] = rotate_left::<8>(state[19]);
+        moved[14] = rotate_left::<18>(state[20]);
+        moved[24] = rotate_left::<2>(state[21]);
+        moved[9] = rotate_left::<61>(state[22]);
+        moved[19] = rotate_left::<56>(state[23]);
+        moved[4] = rotate_left::<14>(state[24]);
+
+        // Chi: each row is mixed non-linearly.
+        for y in 0..5 {
+            for x in 0..5 {
+                state[x + 5 * y] = xor(
+                    moved[x + 5 * y],
+                    and_not(moved[(x + 1) % 5 + 5 * y], moved[(x + 2) % 5 + 5 * y]),
+                );
+            }
+        }
+
+        // Iota.
+        state[0] = xor(state[0], [round_constant; LANES]);
+    }
+}
+
+/// Computes the Keccak-256 hash of each of `inputs`, which may differ in length.
+///
+/// The blocks of all inputs are absorbed in lockstep. An input that runs out of blocks first has
+/// its hash taken right after its last block, and its lane keeps being permuted unused.
+pub(crate) fn keccak256_lanes(inputs: [&[u8]; LANES]) -> [B256; LANES] {
+    // The padding always takes at least one byte, so an input of `n` bytes spans `n / RATE + 1`
+    // blocks.
+    let blocks = inputs.map(|input| input.len() / RATE + 1);
+    let mut state = [[0u64; LANES]; 25];
+    let mut hashes = [B256::ZERO; LANES];
+    for block in 0..blocks.iter().copied().max().unwrap_or(1) {
+        for (lane, input) in inputs.iter().enumerate() {
+            if block >= blocks[lane] {
+                continue;
+            }
+            let start = block * RATE;
+            let mut padded = [0u8; RATE];
+            let bytes = &input[start..input.len().min(start + RATE)];
+            padded[..bytes.len()].copy_from_slice(bytes);
+            if block == blocks[lane] - 1 {
+                padded[bytes.len()] ^= 0x01;
+                padded[RATE - 1] ^= 0x80;
+            }
+            for (word, chunk) in state.iter_mut().zip(padded.chunks_exact(8)) {
+                word[lane] ^= u64::from_le_bytes(chunk.try_into().unwrap());
+            }
+        }
+
+        keccak_f(&mut state);
+
+        for (lane, hash) in hashes.iter_mut().enumerate() {
+            if block == blocks[lane] - 1 {
+                for (word, chunk) in state.iter().zip(hash.0.chunks_exact_mut(8)) {
+                    chunk.copy_from_slice(&word[lane].to_le_bytes());
+                }
+            }
+        }
+    }
+    hashes
+}
+
+#[cfg(test)]
+mod test {
+    use crate::simd::{keccak256_lanes, LANES, RATE};
+    use alloc::vec::Vec;
+    use alloy_primitives::keccak256;
+
+    /// Tests that every lane hashes like `keccak256` for lengths around the block boundaries,
+    /// with lanes of different lengths in the same batch.
+    #[test]
+    fn test_keccak256_lanes() {
+        let data: Vec<u8> = (0..3 * RATE + 10).map(|i| (i * 31 + 7) as u8).collect();
+        for len in 0..=data.len() {
+            let lens = [len, (len + 1) % 300, len / 2, RATE];
+            let inputs = lens.map(|len| &data[..len]);
+            let hashes = keccak256_lanes(inputs);
+            for lane in 0..LANES {
+                assert_eq!(hashes[lane], keccak256(inputs[lane]));
+            }
+        }
+    }
+}
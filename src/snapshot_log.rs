@@ -0,0 +1,94 @@
+//! This module contains the [SnapshotLog], a record of the Merkle roots a tree was published under
+//! and when, used to reject proofs against roots that have expired.
+
+use alloc::vec::Vec;
+use alloy_primitives::B256;
+
+use crate::standard_binary_tree::{process_proof, MerkleTreeError};
+
+/// A log of published Merkle roots and the timestamps they were published at.
+#[derive(Debug, Default, Clone)]
+pub struct SnapshotLog {
+    /// The published roots with their publication timestamps, in the order they were recorded.
+    snapshots: Vec<(B256, u64)>,
+}
+
+impl SnapshotLog {
+    /// Creates a new, empty [`SnapshotLog`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `root` was published at `timestamp`.
+    pub fn record(&mut self, root: B256, timestamp: u64) {
+        self.snapshots.push((root, timestamp));
+    }
+
+    /// Returns the timestamp `root` was most recently published at, if it was published at all.
+    pub fn published_at(&self, root: B256) -> Option<u64> {
+        self.snapshots
+            .iter()
+            .rev()
+            .find(|(snapshot_root, _)| *snapshot_root == root)
+            .map(|(_, timestamp)| *timestamp)
+    }
+
+    /// Verifies a Merkle proof against a logged root that is no older than `max_age` at `now`.
+    ///
+    /// # Returns
+    /// - `Ok(true)` - If the proof folds to a logged root published within `max_age` of `now`.
+    /// - `Ok(false)` - If the proof doesn't fold to any logged root.
+    /// - `Err(MerkleTreeError::StaleRoot)` - If the proof folds to a logged root that has expired.
+    pub fn verify_fresh(
+        &self,
+        leaf_hash: B256,
+        proof: &[B256],
+        now: u64,
+        max_age: u64,
+    ) -> Result<bool, MerkleTreeError> {
+        let root = process_proof(leaf_hash, proof);
+        match self.published_at(root) {
+            None => Ok(false),
+            Some(timestamp) if now.saturating_sub(timestamp) > max_age => {
+                Err(MerkleTreeError::StaleRoot)
+            }
+            Some(_) => Ok(true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SnapshotLog;
+    use crate::alloc::string::ToString;
+    use crate::standard_binary_tree::{LeafScheme, MerkleTreeError, StandardMerkleTree};
+    use alloc::vec::Vec;
+    use alloy_dyn_abi::DynSolValue;
+    use alloy_primitives::B256;
+
+    /// Tests that [`SnapshotLog::verify_fresh`] accepts fresh roots and rejects expired ones.
+    #[test]
+    fn test_verify_fresh() {
+        let leaves: Vec<DynSolValue> = (0..4).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        let mut log = SnapshotLog::new();
+        log.record(tree.root().unwrap(), 100);
+
+        let leaf_hash = LeafScheme::Standard.leaf_hash(&leaves[1]).unwrap();
+        let proof = tree.get_proof(&leaves[1]).unwrap();
+
+        assert!(log.verify_fresh(leaf_hash, &proof, 150, 60).unwrap());
+        assert!(matches!(
+            log.verify_fresh(leaf_hash, &proof, 200, 60),
+            Err(MerkleTreeError::StaleRoot)
+        ));
+
+        // Republishing the root refreshes it.
+        log.record(tree.root().unwrap(), 190);
+        assert!(log.verify_fresh(leaf_hash, &proof, 200, 60).unwrap());
+
+        // A proof that doesn't fold to a logged root is simply invalid.
+        assert!(!log.verify_fresh(B256::ZERO, &proof, 200, 60).unwrap());
+    }
+}
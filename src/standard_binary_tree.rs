@@ -6,14 +6,14 @@
 //!
 //! ```rust
 //! use alloy_merkle_tree::standard_binary_tree::StandardMerkleTree;
-//! use alloy::dyn_abi::DynSolValue;
+//! use alloy_dyn_abi::DynSolValue;
 //!
 //! let num_leaves = 1000;
 //! let mut leaves = Vec::new();
 //! for i in 0..num_leaves {
 //!     leaves.push(DynSolValue::String(i.to_string()));
 //! }
-//! let tree = StandardMerkleTree::of(&leaves);
+//! let tree = StandardMerkleTree::of(&leaves).unwrap();
 //!
 //! for leaf in leaves.iter() {
 //!     let proof = tree.get_proof(leaf).unwrap();
@@ -22,19 +22,50 @@
 //! }
 //! ```
 //!
-use core::panic;
-
+//! `Address`, `Uint`, `Int`, and `Bool` leaves are hashed over their 32-byte ABI encoding, so a
+//! tree of addresses has the same leaves as OpenZeppelin's `["address"]` trees.
+//!
+//! Leaves made of several values, like OpenZeppelin's `["address", "uint256"]` trees, are
+//! [`DynSolValue::Tuple`]s ABI-encoded exactly as OpenZeppelin does; see
+//! [`StandardMerkleTree::of_multi`].
+//!
+//! # Differences from `rs_merkle`
+//!
+//! Roots computed by this module generally differ from those of the
+//! [`rs_merkle`](https://crates.io/crates/rs_merkle) crate for the same data, even with a keccak256
+//! hasher:
+//!
+//! - `rs_merkle` takes leaf hashes from the caller, who usually hashes each leaf once. This module
+//!   double-hashes leaves, `keccak256(keccak256(encoded))`, like OpenZeppelin does.
+//! - `rs_merkle` concatenates the children of a node in left-right order, while this module sorts them
+//!   first, so proofs don't need to carry the position of each sibling.
+//! - `rs_merkle` promotes the unpaired last node of a level unchanged, while this module lays the tree
+//!   out as a complete binary tree. The shapes are the same for up to four leaves and whenever the
+//!   number of leaves is a power of two, but differ otherwise.
+//!
+//! Given this module's leaf hashes and a sorted-pair hasher, `rs_merkle` reproduces its roots exactly
+//! when the shapes agree. The other way around, [`StandardMerkleTreeBuilder::rs_merkle_compatible`]
+//! builds trees whose root is `rs_merkle`'s when the number of leaves is a power of two.
+//!
 use crate::alloc::string::ToString;
+use alloc::collections::VecDeque;
 use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
-use alloy::dyn_abi::DynSolValue;
-use alloy::primitives::{keccak256, Keccak256, B256};
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy_primitives::{hex, keccak256, Keccak256, B256, U256};
+use core::marker::PhantomData;
 
+#[cfg(feature = "futures")]
+use futures::{Stream, StreamExt};
 use hashbrown::HashMap;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 /// The error type for the [StandardMerkleTree].
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MerkleTreeError {
     /// The specified leaf was not found in the tree.
     LeafNotFound,
@@ -44,269 +75,8104 @@ pub enum MerkleTreeError {
     RootHaveNoSiblings,
     /// The leaf type is not supported by the tree.
     NotSupportedType,
+    /// The ABI-encoded leaf at the given index could not be decoded.
+    InvalidAbiEncoding(usize),
+    /// The root was published longer ago than the maximum accepted age.
+    StaleRoot,
+    /// The node at the given tree index could not be fetched.
+    NodeUnavailable(usize),
+    /// The leaves were required to be sorted but aren't.
+    UnsortedLeaves,
+    /// The proof is empty but the tree has more than one leaf.
+    EmptyProof,
+    /// The proof has more elements than the tree has levels below the root.
+    ProofTooLong,
+    /// The leaf doesn't match the expected type.
+    SchemaMismatch,
+    /// The same leaf was requested more than once in a multiproof.
+    DuplicatedIndex,
+    /// The multiproof's leaves, proof and flags don't fit together.
+    InvalidMultiProof,
+    /// The proof ended with a partial element of fewer than 32 bytes.
+    TruncatedProof,
+    /// Reading the proof from its source failed.
+    ProofRead,
+    /// A Bloom filter needs at least one bit and one hash function.
+    InvalidBloomParameters,
+    /// The root selector is neither a 4-byte hex selector nor a function signature.
+    InvalidSelector,
+    /// Calling the contract's root getter failed or didn't return a `bytes32`.
+    RootCallFailed,
+    /// The stored value of the leaf at the given index doesn't hash to its leaf node.
+    InconsistentLeaf(usize),
+    /// Reading or writing the file of a disk tree failed, or it doesn't hold a node array.
+    DiskAccess,
+    /// Writing a dump to its destination failed.
+    DumpWrite,
+    /// The tree options can't be used together, e.g. a chain id with raw leaves.
+    IncompatibleOptions,
+}
+
+impl core::fmt::Display for MerkleTreeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::LeafNotFound => f.write_str("leaf not found in the tree"),
+            Self::InvalidCheck => f.write_str("invalid check during a tree operation"),
+            Self::RootHaveNoSiblings => f.write_str("the root has no siblings"),
+            Self::NotSupportedType => f.write_str("leaf type not supported"),
+            Self::InvalidAbiEncoding(index) => {
+                write!(f, "invalid ABI encoding of the leaf at index {index}")
+            }
+            Self::StaleRoot => f.write_str("root is older than the maximum accepted age"),
+            Self::NodeUnavailable(index) => {
+                write!(f, "node at tree index {index} is unavailable")
+            }
+            Self::UnsortedLeaves => f.write_str("leaves are not sorted"),
+            Self::EmptyProof => f.write_str("empty proof for a tree of more than one leaf"),
+            Self::ProofTooLong => f.write_str("proof is longer than the tree is deep"),
+            Self::SchemaMismatch => f.write_str("leaf doesn't match the expected type"),
+            Self::DuplicatedIndex => f.write_str("leaf requested more than once in a multiproof"),
+            Self::InvalidMultiProof => f.write_str("invalid multiproof"),
+            Self::TruncatedProof => f.write_str("proof ends with a partial element"),
+            Self::ProofRead => f.write_str("failed to read the proof"),
+            Self::InvalidBloomParameters => f.write_str("invalid Bloom filter parameters"),
+            Self::InvalidSelector => f.write_str("invalid root selector"),
+            Self::RootCallFailed => f.write_str("root getter call failed"),
+            Self::InconsistentLeaf(index) => {
+                write!(
+                    f,
+                    "stored value of the leaf at index {index} doesn't match its hash"
+                )
+            }
+            Self::DiskAccess => f.write_str("failed to access the disk tree file"),
+            Self::DumpWrite => f.write_str("failed to write the dump"),
+            Self::IncompatibleOptions => f.write_str("incompatible tree options"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MerkleTreeError {}
+
+/// The byte order a root is stored in.
+///
+/// Roots are normally stored as the 32 big-endian bytes of the hash. Some L2s store them
+/// byte-reversed instead, e.g. when the root is kept as a little-endian integer or field element;
+/// verify against such a stored root with [`ByteOrder::LittleEndian`] rather than flipping it by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ByteOrder {
+    /// The root is stored as is.
+    #[default]
+    BigEndian,
+    /// The root is stored with its 32 bytes reversed.
+    LittleEndian,
+}
+
+impl ByteOrder {
+    /// Converts a root stored in this byte order into the root as computed by the tree.
+    pub fn normalize(self, stored_root: B256) -> B256 {
+        match self {
+            Self::BigEndian => stored_root,
+            Self::LittleEndian => {
+                let mut root = stored_root;
+                root.0.reverse();
+                root
+            }
+        }
+    }
+}
+
+/// The leaf hashing scheme of the Merkle library a proof was produced with.
+///
+/// Every supported scheme hashes pairs of nodes the same way (sorted-pair `keccak256`), so proofs only
+/// differ in how the leaf itself is hashed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LeafScheme {
+    /// OpenZeppelin's `StandardMerkleTree`: the leaf is `keccak256(keccak256(encoded))`.
+    #[default]
+    Standard,
+    /// [Murky](https://github.com/dmfxyz/murky) and Solady-style helpers: the leaf is
+    /// `keccak256(abi.encodePacked(value))`, hashed once.
+    ///
+    /// Murky itself takes `bytes32` leaves and leaves hashing to the caller; this matches the
+    /// `keccak256(abi.encodePacked(...))` convention its users rely on, with `abi.encodePacked`
+    /// encoding e.g. a `uint256` as its 32-byte word and a `string` as its raw bytes.
+    Murky,
+}
+
+impl LeafScheme {
+    /// Computes the leaf hash of `value` under this scheme.
+    ///
+    /// Returns [`MerkleTreeError::NotSupportedType`] if the scheme can't hash such values.
+    pub fn leaf_hash(&self, value: &DynSolValue) -> Result<B256, MerkleTreeError> {
+        match self {
+            Self::Standard => standard_leaf_hash(value),
+            Self::Murky => Ok(keccak256(value.abi_encode_packed())),
+        }
+    }
+}
+
+/// The hash functions a [`StandardMerkleTree`] is built with.
+///
+/// Implement it to build trees for chains that don't hash with keccak256, and construct them with
+/// [`StandardMerkleTree::of_with_hasher`] or [`StandardMerkleTreeBuilder::build_with_hasher`].
+///
+/// Trees built with [`HashOrder::Sorted`] fold proofs with [`Self::hash_pair`] and no positions,
+/// so it must give the same parent for both orders of its nodes; by default it sorts them before
+/// [`Self::hash_pair_ordered`]. To hash pairs without sorting, build the tree with
+/// [`HashOrder::Positional`], which only uses [`Self::hash_pair_ordered`].
+pub trait Hasher {
+    /// Hashes `data` once, as leaves built with [`LeafHashMode::Single`] are.
+    fn hash(data: &[u8]) -> B256;
+
+    /// Hashes the encoding of a leaf value into its leaf node. Defaults to hashing it twice with
+    /// [`Self::hash`], as OpenZeppelin does.
+    fn hash_leaf(data: &[u8]) -> B256 {
+        Self::hash(Self::hash(data).as_slice())
+    }
+
+    /// Hashes a left and a right node, in that order, into their parent.
+    fn hash_pair_ordered(left: B256, right: B256) -> B256;
+
+    /// Hashes two sibling nodes, in either order, into their parent. Defaults to hashing the smaller
+    /// one first with [`Self::hash_pair_ordered`], as OpenZeppelin does.
+    ///
+    /// Overrides must stay commutative, i.e. `hash_pair(a, b) == hash_pair(b, a)`, or proofs of
+    /// sorted trees won't verify.
+    fn hash_pair(left: B256, right: B256) -> B256 {
+        if left <= right {
+            Self::hash_pair_ordered(left, right)
+        } else {
+            Self::hash_pair_ordered(right, left)
+        }
+    }
+
+    /// Hashes [`HASH_BATCH`] inputs with [`Self::hash`] at once, as leaves are while a tree is
+    /// built. Defaults to hashing them one after the other.
+    ///
+    /// Overrides must return what [`Self::hash`] does for each input.
+    fn hash_batch(data: [&[u8]; HASH_BATCH]) -> [B256; HASH_BATCH] {
+        data.map(Self::hash)
+    }
+
+    /// Hashes [`HASH_BATCH`] leaf encodings with [`Self::hash_leaf`] at once, as leaves are while a
+    /// tree is built. Defaults to hashing them one after the other.
+    ///
+    /// Overrides must return what [`Self::hash_leaf`] does for each input.
+    fn hash_leaf_batch(data: [&[u8]; HASH_BATCH]) -> [B256; HASH_BATCH] {
+        data.map(Self::hash_leaf)
+    }
+}
+
+/// The number of inputs [`Hasher::hash_batch`] and [`Hasher::hash_leaf_batch`] hash at once.
+pub const HASH_BATCH: usize = 4;
+
+/// OpenZeppelin's hash functions, used by default: leaves are `keccak256(keccak256(encoded))` and
+/// a parent is the `keccak256` of its children, the smaller one first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Keccak256Hasher;
+
+impl Hasher for Keccak256Hasher {
+    fn hash(data: &[u8]) -> B256 {
+        keccak256(data)
+    }
+
+    fn hash_pair_ordered(left: B256, right: B256) -> B256 {
+        let mut hasher = Keccak256::new();
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize()
+    }
+
+    /// With the `simd` feature the inputs are hashed in the parallel lanes of a vectorized
+    /// Keccak-f\[1600\], bit-identical to [`keccak256`].
+    #[cfg(feature = "simd")]
+    fn hash_batch(data: [&[u8]; HASH_BATCH]) -> [B256; HASH_BATCH] {
+        crate::simd::keccak256_lanes(data)
+    }
+
+    /// With the `simd` feature both hashes of every leaf are computed in parallel lanes, as in
+    /// [`Self::hash_batch`].
+    #[cfg(feature = "simd")]
+    fn hash_leaf_batch(data: [&[u8]; HASH_BATCH]) -> [B256; HASH_BATCH] {
+        let hashes = crate::simd::keccak256_lanes(data);
+        crate::simd::keccak256_lanes(hashes.each_ref().map(B256::as_slice))
+    }
 }
 
 /// Represents a standard Merkle tree with methods for proof generation and verification.
-#[derive(Debug)]
-pub struct StandardMerkleTree {
+///
+/// Nodes are hashed with `H`, OpenZeppelin's [`Keccak256Hasher`] by default.
+///
+/// With the `serde` feature the tree can be serialized as is, e.g. to cache a large tree instead of
+/// rebuilding it. Deserialized trees are trusted as they are; [`Self::load`] checks its input.
+///
+/// Trees compare equal when they have the same nodes, options and values (which are `PartialEq`
+/// only, so the tree isn't `Eq`).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct StandardMerkleTree<H: Hasher = Keccak256Hasher> {
     /// The internal representation of the tree as a flat vector.
     tree: Vec<B256>,
-    /// A mapping from serialized leaf values to their indices in the tree.
-    tree_values: HashMap<String, usize>,
+    /// A mapping from serialized leaf values to their indices in the tree. A value that occurs more
+    /// than once keeps every one of its indices, in insertion order. Keys are prefixed with the type
+    /// of the value (see [`Self::check_valid_value_type`]), so values of different types never share
+    /// a key.
+    tree_values: HashMap<String, Vec<usize>>,
+    /// The options the tree was built with, needed to hash leaves consistently during verification.
+    options: TreeOptions,
+    /// The number of leaves in the tree. Padded trees (see [`OddNodePolicy`]) have more leaf slots
+    /// than leaves, so this can't be derived from the length of `tree`.
+    leaf_count: usize,
+    /// The original leaf values, in leaf order.
+    #[cfg_attr(feature = "serde", serde(with = "serde_values"))]
+    values: Vec<DynSolValue>,
+    /// A mapping from lookup keys derived by [`StandardMerkleTreeBuilder::key_fn`] to the indices of
+    /// the leaves they were derived from, in insertion order.
+    keys: HashMap<String, Vec<usize>>,
+    /// The hash functions of the tree.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    hasher: PhantomData<H>,
 }
 
-impl Default for StandardMerkleTree {
-    /// Creates a new, empty `StandardMerkleTree`.
-    fn default() -> Self {
-        Self::new(Vec::new(), Vec::new())
+/// Options controlling how a [`StandardMerkleTree`] is built and how its leaves are hashed.
+///
+/// The options are recorded in the tree, so that proofs are verified the way the tree was built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TreeOptions {
+    /// The chain id bound into every leaf, if any. See [`StandardMerkleTreeBuilder::chain_id`].
+    pub chain_id: Option<u64>,
+    /// How a node without a sibling is handled. See [`StandardMerkleTreeBuilder::odd_node_policy`].
+    pub odd_node_policy: OddNodePolicy,
+    /// How two sibling nodes are combined into their parent. See
+    /// [`StandardMerkleTreeBuilder::hash_order`].
+    pub hash_order: HashOrder,
+    /// How a leaf value is turned into a leaf node.
+    pub leaf_hash_mode: LeafHashMode,
+    /// Whether the leaves were reordered with [`StandardMerkleTreeBuilder::sort_by`] before hashing.
+    pub custom_sort: bool,
+}
+
+impl TreeOptions {
+    /// Checks that the options can be used together, returning
+    /// [`MerkleTreeError::IncompatibleOptions`] for a chain id with [`LeafHashMode::Raw`], as a raw
+    /// leaf is the value itself and has nothing to bind the chain id into.
+    fn check(&self) -> Result<(), MerkleTreeError> {
+        if self.chain_id.is_some() && self.leaf_hash_mode == LeafHashMode::Raw {
+            return Err(MerkleTreeError::IncompatibleOptions);
+        }
+        Ok(())
     }
 }
 
-impl StandardMerkleTree {
-    /// Creates a new [`StandardMerkleTree`] with the given tree nodes and values.
-    pub fn new(tree: Vec<B256>, values: Vec<(&DynSolValue, usize)>) -> Self {
-        let mut tree_values = HashMap::new();
-        for (tree_key, tree_value) in values.into_iter() {
-            let tree_key_str = Self::check_valid_value_type(tree_key);
-            tree_values.insert(tree_key_str, tree_value);
+/// How two sibling nodes are ordered before being hashed into their parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HashOrder {
+    /// OpenZeppelin's convention: the smaller node comes first, so proofs need no directions.
+    #[default]
+    Sorted,
+    /// The left node comes first, so the parent commits to the order of the leaves.
+    Positional,
+}
+
+impl HashOrder {
+    /// Combines the node at `index` in the tree array with its sibling into their parent.
+    ///
+    /// Every level is stored in reverse, so the node at an even index is the left one.
+    fn hash_with_sibling<H: Hasher>(self, index: usize, node: B256, sibling: B256) -> B256 {
+        if index % 2 == 0 {
+            self.hash_nodes::<H>(node, sibling)
+        } else {
+            self.hash_nodes::<H>(sibling, node)
         }
-        Self { tree, tree_values }
     }
 
-    /// Constructs a [`StandardMerkleTree`] from a slice of dynamic Solidity values.
-    pub fn of(values: &[DynSolValue]) -> Self {
-        // Hash each value and associate it with its index and leaf hash.
-        let hashed_values: Vec<(&DynSolValue, usize, B256)> = values
-            .iter()
-            .enumerate()
-            .map(|(i, value)| (value, i, standard_leaf_hash(value)))
-            .collect();
+    /// Hashes a left and a right node into their parent with `H`, sorting them unless positional.
+    fn hash_nodes<H: Hasher>(self, left: B256, right: B256) -> B256 {
+        match self {
+            Self::Sorted => H::hash_pair(left, right),
+            Self::Positional => H::hash_pair_ordered(left, right),
+        }
+    }
+}
 
-        // Collect the leaf hashes into a vector.
-        let hashed_values_hash = hashed_values
-            .iter()
-            .map(|(_, _, hash)| *hash)
-            .collect::<Vec<B256>>();
+/// How a leaf value is turned into the leaf node of a [`StandardMerkleTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LeafHashMode {
+    /// OpenZeppelin's convention: the leaf is `keccak256(keccak256(encoded))`.
+    #[default]
+    Double,
+    /// The leaf is `keccak256(encoded)`, hashed once with [`Hasher::hash`], as in trees that don't
+    /// follow OpenZeppelin.
+    ///
+    /// A leaf whose encoding is 64 bytes then hashes like an internal node, which opens the tree to
+    /// second-preimage attacks, so prefer [`LeafHashMode::Double`] for new trees.
+    Single,
+    /// The leaf is the 32-byte value itself, for values that are already hashes (e.g. transaction
+    /// hashes). Only `bytes32` and `uint256` values can be raw leaves, and raw leaves can't be bound
+    /// to a chain id.
+    Raw,
+}
 
-        // Build the Merkle tree from the leaf hashes.
-        let tree = make_merkle_tree(hashed_values_hash);
+/// Every [`LeafHashMode`], in the order [`StandardMerkleTree::verify_any_mode`] tries them.
+const LEAF_HASH_MODES: [LeafHashMode; 3] = [
+    LeafHashMode::Double,
+    LeafHashMode::Single,
+    LeafHashMode::Raw,
+];
 
-        // Map each value to its corresponding index in the tree.
-        let mut indexed_values: Vec<(&DynSolValue, usize)> =
-            values.iter().map(|value| (value, 0)).collect();
+/// How a level with an odd number of nodes is reduced when building a [`StandardMerkleTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OddNodePolicy {
+    /// OpenZeppelin's layout: the tree is a complete binary tree of `2n - 1` nodes, so the unpaired
+    /// node is carried up and paired on a higher level.
+    #[default]
+    Promote,
+    /// Pairs the unpaired node with a copy of itself, as Bitcoin's transaction tree does.
+    HashWithSelf,
+    /// Pairs the unpaired node with [`B256::ZERO`].
+    HashWithZero,
+}
 
-        for (leaf_index, (_, value_index, _)) in hashed_values.iter().enumerate() {
-            indexed_values[*value_index].1 = tree.len() - leaf_index - 1;
-        }
+/// A builder for [`StandardMerkleTree`]s with non-default [`TreeOptions`].
+///
+/// # Examples
+///
+/// ```rust
+/// use alloy_merkle_tree::standard_binary_tree::StandardMerkleTree;
+/// use alloy_dyn_abi::DynSolValue;
+///
+/// let leaves = [DynSolValue::String("alice".into()), DynSolValue::String("bob".into())];
+/// let tree = StandardMerkleTree::builder().chain_id(1).build(&leaves).unwrap();
+///
+/// let proof = tree.get_proof(&leaves[0]).unwrap();
+/// assert!(tree.verify_proof(&leaves[0], proof));
+/// ```
+#[derive(Clone, Default)]
+pub struct StandardMerkleTreeBuilder {
+    /// The options the tree will be built with.
+    options: TreeOptions,
+    /// Whether to reject leaves that aren't sorted by hash.
+    require_sorted: bool,
+    /// The function deriving a lookup key from each leaf, if any.
+    key_fn: Option<Arc<KeyFn>>,
+    /// The comparator the leaves are sorted with before hashing, if any.
+    sort_fn: Option<Arc<SortFn>>,
+}
 
-        Self::new(tree, indexed_values)
+/// A function deriving a lookup key from a leaf value. See [`StandardMerkleTreeBuilder::key_fn`].
+type KeyFn = dyn Fn(&DynSolValue) -> String + Send + Sync;
+
+/// A comparator ordering leaf values. See [`StandardMerkleTreeBuilder::sort_by`].
+type SortFn = dyn Fn(&DynSolValue, &DynSolValue) -> core::cmp::Ordering + Send + Sync;
+
+/// The proof of every leaf, with its leaf index. See [`StandardMerkleTree::build_and_split`].
+type IndexedProofs = Vec<(usize, Vec<B256>)>;
+
+impl core::fmt::Debug for StandardMerkleTreeBuilder {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StandardMerkleTreeBuilder")
+            .field("options", &self.options)
+            .field("require_sorted", &self.require_sorted)
+            .field("key_fn", &self.key_fn.is_some())
+            .field("sort_fn", &self.sort_fn.is_some())
+            .finish()
     }
+}
 
-    /// Retrieves the root hash of the Merkle tree.
-    pub fn root(&self) -> B256 {
-        self.tree[0]
+impl StandardMerkleTreeBuilder {
+    /// Creates a new [`StandardMerkleTreeBuilder`] with the default options.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// Generates a Merkle proof for a given leaf value.
-    pub fn get_proof(&self, value: &DynSolValue) -> Result<Vec<B256>, MerkleTreeError> {
-        let tree_key = Self::check_valid_value_type(value);
+    /// Binds every leaf to `chain_id`, so that a proof can't be replayed on another chain.
+    ///
+    /// The chain id is prepended to the leaf encoding as a `uint256` word, making the leaf hash
+    /// `keccak256(keccak256(uint256(chain_id) ++ encoded))`. Verify such proofs without the tree with
+    /// [`verify_with_chain_id`]. It can't be combined with [`LeafHashMode::Raw`], and
+    /// [`Self::build`] then returns [`MerkleTreeError::IncompatibleOptions`].
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.options.chain_id = Some(chain_id);
+        self
+    }
 
-        let tree_index = self
-            .tree_values
-            .get(&tree_key)
-            .ok_or(MerkleTreeError::LeafNotFound)?;
+    /// Sets how a level with an odd number of nodes is reduced. Defaults to
+    /// [`OddNodePolicy::Promote`], which matches OpenZeppelin.
+    ///
+    /// The other policies build the tree level by level, padding every odd level with a filler
+    /// node, so a tree of `n` leaves is stored in `2 * n.next_power_of_two() - 1` nodes.
+    pub fn odd_node_policy(mut self, policy: OddNodePolicy) -> Self {
+        self.options.odd_node_policy = policy;
+        self
+    }
 
-        make_proof(&self.tree, *tree_index)
+    /// Sets how two sibling nodes are combined into their parent. Defaults to [`HashOrder::Sorted`],
+    /// which matches OpenZeppelin.
+    ///
+    /// With [`HashOrder::Positional`] the root also commits to the order of the leaves, and proofs
+    /// can only be checked knowing the position of the leaf: without the tree, pair
+    /// [`StandardMerkleTree::get_proof_with_positions`] with [`verify_with_positions`].
+    pub fn hash_order(mut self, order: HashOrder) -> Self {
+        self.options.hash_order = order;
+        self
     }
 
-    /// Computes the hash of a leaf node.
-    fn get_leaf_hash(&self, leaf: &DynSolValue) -> B256 {
-        standard_leaf_hash(leaf)
+    /// Sets how a leaf value is turned into a leaf node. Defaults to [`LeafHashMode::Double`],
+    /// which matches OpenZeppelin; use [`LeafHashMode::Single`] to interoperate with trees whose
+    /// leaves are hashed once.
+    pub fn leaf_hash_mode(mut self, mode: LeafHashMode) -> Self {
+        self.options.leaf_hash_mode = mode;
+        self
     }
 
-    /// Verifies a Merkle proof for a given leaf value.
-    pub fn verify_proof(&self, leaf: &DynSolValue, proof: Vec<B256>) -> bool {
-        let leaf_hash = self.get_leaf_hash(leaf);
-        let implied_root = process_proof(leaf_hash, proof);
-        self.tree[0] == implied_root
+    /// Sets the options that reproduce the roots of the `rs_merkle` crate with a keccak256 hasher:
+    /// [`LeafHashMode::Raw`] leaves, which are the leaf hashes `rs_merkle` is given, and
+    /// [`HashOrder::Positional`] pairs, which `rs_merkle` concatenates left first.
+    ///
+    /// Build the tree over the same hashes as `bytes32` values. For leaves `rs_merkle` was given as
+    /// the `keccak256` of their encoding, set [`LeafHashMode::Single`] afterwards and pass the values
+    /// themselves.
+    ///
+    /// The roots only match when the number of leaves is a power of two. Otherwise the shapes
+    /// diverge: `rs_merkle` promotes the unpaired last node of a level, while this crate lays the
+    /// leaves out as a complete binary tree, and no [`OddNodePolicy`] reproduces that.
+    pub fn rs_merkle_compatible(mut self) -> Self {
+        self.options.leaf_hash_mode = LeafHashMode::Raw;
+        self.options.hash_order = HashOrder::Positional;
+        self
     }
 
-    /// Validates and serializes a [`DynSolValue`] into a [`String`].
-    fn check_valid_value_type(value: &DynSolValue) -> String {
-        match value {
-            DynSolValue::String(inner_value) => inner_value.to_string(),
-            DynSolValue::FixedBytes(inner_value, _) => inner_value.to_string(),
-            _ => panic!("Not supported value type"),
+    /// Derives a lookup key from every leaf with `key_fn`, so that proofs can be fetched by key with
+    /// [`StandardMerkleTree::get_proof_by_key`], independently of the leaf encoding.
+    ///
+    /// The keys are derived once, when the tree is built. [`StandardMerkleTree::update_leaf`] drops
+    /// the keys of the leaf it replaces, as the tree doesn't keep `key_fn`.
+    pub fn key_fn(
+        mut self,
+        key_fn: impl Fn(&DynSolValue) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.key_fn = Some(Arc::new(key_fn));
+        self
+    }
+
+    /// Sorts the leaves with `compare` before hashing them, e.g. to lay them out in the numeric order
+    /// of the values rather than in insertion order.
+    ///
+    /// The sort is stable and recorded in [`TreeOptions::custom_sort`]. Proofs work the same.
+    pub fn sort_by(
+        mut self,
+        compare: impl Fn(&DynSolValue, &DynSolValue) -> core::cmp::Ordering + Send + Sync + 'static,
+    ) -> Self {
+        self.options.custom_sort = true;
+        self.sort_fn = Some(Arc::new(compare));
+        self
+    }
+
+    /// Requires the leaf hashes to be in non-decreasing order, as OpenZeppelin's `sortLeaves` leaves
+    /// them. See [`StandardMerkleTree::leaves_are_sorted`].
+    pub fn require_sorted(mut self) -> Self {
+        self.require_sorted = true;
+        self
+    }
+
+    /// Constructs a [`StandardMerkleTree`] from a slice of dynamic Solidity values.
+    ///
+    /// Returns [`MerkleTreeError::UnsortedLeaves`] if [`Self::require_sorted`] was set and the leaves
+    /// aren't sorted.
+    pub fn build(self, values: &[DynSolValue]) -> Result<StandardMerkleTree, MerkleTreeError> {
+        self.build_with_hasher(values)
+    }
+
+    /// Constructs a [`StandardMerkleTree`] like [`Self::build`], hashing leaves and pairs with `H`
+    /// instead of OpenZeppelin's keccak256.
+    pub fn build_with_hasher<H: Hasher>(
+        self,
+        values: &[DynSolValue],
+    ) -> Result<StandardMerkleTree<H>, MerkleTreeError> {
+        let mut sorted;
+        let values = match &self.sort_fn {
+            Some(compare) => {
+                sorted = values.to_vec();
+                sorted.sort_by(|a, b| compare(a, b));
+                &sorted[..]
+            }
+            None => values,
+        };
+
+        let mut tree = StandardMerkleTree::<H>::of_with_hasher_and_options(values, self.options)?;
+        if self.require_sorted && !tree.leaves_are_sorted() {
+            return Err(MerkleTreeError::UnsortedLeaves);
         }
+        if let Some(key_fn) = self.key_fn {
+            for (leaf_index, value) in values.iter().enumerate() {
+                let tree_index = tree.leaf_index_to_tree_index(leaf_index)?;
+                tree.keys.entry(key_fn(value)).or_default().push(tree_index);
+            }
+        }
+        Ok(tree)
     }
 }
 
-/// Computes the standard leaf hash for a given value..
-fn standard_leaf_hash(value: &DynSolValue) -> B256 {
-    let encoded = match value {
-        DynSolValue::String(inner_value) => inner_value.as_bytes(),
-        DynSolValue::FixedBytes(inner_value, _) => inner_value.as_ref(),
-        _ => panic!("Not supported value type for leaf"),
-    };
-    keccak256(keccak256(encoded))
+/// An iterator over the Merkle proof of every leaf of a [`StandardMerkleTree`], in leaf order.
+///
+/// Created by [`StandardMerkleTree::iter_proofs`]. Each proof is only computed when the iterator is
+/// advanced.
+#[derive(Debug)]
+pub struct ProofIter<'a, H: Hasher = Keccak256Hasher> {
+    /// The tree the proofs are generated from.
+    tree: &'a StandardMerkleTree<H>,
+    /// The index of the next leaf to generate a proof for.
+    leaf_index: usize,
 }
 
-/// Calculates the index of the left child for a given parent index..
-fn left_child_index(index: usize) -> usize {
-    2 * index + 1
-}
+impl<H: Hasher> Iterator for ProofIter<'_, H> {
+    type Item = (usize, Vec<B256>);
 
-/// Calculates the index of the right child for a given parent index.
-fn right_child_index(index: usize) -> usize {
-    2 * index + 2
-}
+    fn next(&mut self) -> Option<Self::Item> {
+        let leaf_index = self.leaf_index;
+        let tree_index = self.tree.leaf_index_to_tree_index(leaf_index).ok()?;
+        self.leaf_index += 1;
 
-/// Determines the sibling index for a given node index..
-fn sibling_index(index: usize) -> Result<usize, MerkleTreeError> {
-    if index == 0 {
-        return Err(MerkleTreeError::RootHaveNoSiblings);
+        let proof = make_proof(&self.tree.tree, tree_index).ok()?;
+        Some((leaf_index, proof))
     }
 
-    if index % 2 == 0 {
-        Ok(index - 1)
-    } else {
-        Ok(index + 1)
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.tree.leaf_count() - self.leaf_index;
+        (remaining, Some(remaining))
     }
 }
 
-/// Calculates the parent index for a given child index.
-fn parent_index(index: usize) -> usize {
-    (index - 1) / 2
+impl<H: Hasher> ExactSizeIterator for ProofIter<'_, H> {}
+
+/// The outcome of [`StandardMerkleTree::verify_or_nearest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    /// The proof verified against the root.
+    Verified,
+    /// The proof didn't verify.
+    NotVerified {
+        /// The leaf hash of the tree numerically closest to the leaf hash of the value.
+        nearest_leaf_hash: B256,
+    },
 }
 
-/// Checks if a given index corresponds to a node within the tree.
-fn is_tree_node(tree: &[B256], index: usize) -> bool {
-    index < tree.len()
+/// The outcome of [`StandardMerkleTree::verify_diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagResult {
+    /// Whether the proof verified against the root.
+    pub verified: bool,
+    /// The number of proof elements folded into the last computed node that is a node of the tree.
+    ///
+    /// `0` means only the leaf itself matched, so the first proof element is faulty. For a failing
+    /// proof, the element at this index is the first one that led the fold out of the tree.
+    pub last_matched_level: usize,
 }
 
-/// Checks if a given index corresponds to an internal node (non-leaf).
-fn is_internal_node(tree: &[B256], index: usize) -> bool {
-    is_tree_node(tree, left_child_index(index))
+/// A Merkle proof in the hex-encoded JSON shape of `eth_getProof`-style responses.
+///
+/// Created by [`StandardMerkleTree::proof_response`]. Every hash is a `0x`-prefixed hex string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProofResponse {
+    /// The leaf hash.
+    pub leaf: String,
+    /// The index of the leaf among the leaves of the tree.
+    pub leaf_index: u64,
+    /// The proof elements, from the leaf up to the root.
+    pub proof: Vec<String>,
+    /// The root of the tree.
+    pub root: String,
 }
 
-/// Checks if a given index corresponds to a leaf node.
-fn is_leaf_node(tree: &[B256], index: usize) -> bool {
-    !is_internal_node(tree, index) && is_tree_node(tree, index)
+/// A Merkle proof in the shape viem and wagmi frontends take: the proof and root as `0x`-prefixed
+/// hashes, and the original value of the leaf.
+///
+/// Created by [`StandardMerkleTree::viem_proof`]. The value has one string per column, in the
+/// syntax of [`StandardMerkleTreeData`] leaves, as in OpenZeppelin's `tree.entries()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ViemProof {
+    /// The proof elements, from the leaf up to the root.
+    pub proof: Vec<String>,
+    /// The root of the tree.
+    pub root: String,
+    /// The columns of the leaf value.
+    pub value: Vec<String>,
 }
 
-/// Validates that a given index corresponds to a leaf node.
-fn check_leaf_node(tree: &[B256], index: usize) -> Result<(), MerkleTreeError> {
-    if !is_leaf_node(tree, index) {
-        Err(MerkleTreeError::InvalidCheck)
-    } else {
-        Ok(())
-    }
+/// The format tag of OpenZeppelin's standard tree dumps.
+const STANDARD_V1_FORMAT: &str = "standard-v1";
+
+/// A [`StandardMerkleTree`] in the JSON shape of OpenZeppelin's `StandardMerkleTree.dump()`.
+///
+/// Created by [`StandardMerkleTree::dump`] and read back with [`StandardMerkleTree::load`]. Every
+/// hash is a `0x`-prefixed hex string and every leaf column a string, with numbers in decimal and
+/// bytes and addresses in hex, as OpenZeppelin dumps them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct StandardMerkleTreeData {
+    /// The dump format, always `standard-v1`.
+    pub format: String,
+    /// Every node of the tree, in array order.
+    pub tree: Vec<String>,
+    /// The leaves, each with the index of its node in `tree`.
+    pub values: Vec<StandardMerkleTreeValue>,
+    /// The Solidity type of every leaf column, e.g. `["address", "uint256"]`.
+    pub leaf_encoding: Vec<String>,
 }
 
-/// Constructs a Merkle tree from a vector of leaf hashes.
-fn make_merkle_tree(leaves: Vec<B256>) -> Vec<B256> {
-    let tree_len = 2 * leaves.len() - 1;
-    let mut tree = vec![B256::default(); tree_len];
-    let leaves_len = leaves.len();
+/// A leaf of a [`StandardMerkleTreeData`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct StandardMerkleTreeValue {
+    /// The columns of the leaf, one per type of [`StandardMerkleTreeData::leaf_encoding`].
+    pub value: Vec<String>,
+    /// The index of the leaf's node in [`StandardMerkleTreeData::tree`].
+    pub tree_index: usize,
+}
 
-    // Place leaves at the end of the tree array.
-    for (i, leaf) in leaves.into_iter().enumerate() {
-        tree[tree_len - 1 - i] = leaf;
+/// An immutable [`StandardMerkleTree`] that can be shared across threads.
+///
+/// Created by [`StandardMerkleTree::freeze`]. Every read-only method of the tree is available through
+/// [`Deref`](core::ops::Deref), and cloning only bumps a reference count.
+#[derive(Debug)]
+pub struct FrozenTree<H: Hasher = Keccak256Hasher>(Arc<StandardMerkleTree<H>>);
+
+impl<H: Hasher> Clone for FrozenTree<H> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
     }
+}
 
-    // Build the tree by hashing pairs of nodes from the leaves up to the root.
-    for i in (0..tree_len - leaves_len).rev() {
-        let left = tree[left_child_index(i)];
-        let right = tree[right_child_index(i)];
+impl<H: Hasher> core::ops::Deref for FrozenTree<H> {
+    type Target = StandardMerkleTree<H>;
 
-        tree[i] = hash_pair(left, right);
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
+}
 
-    tree
+/// A proof of several leaves at once, in the shape of OpenZeppelin's `getMultiProof`.
+///
+/// Created by [`StandardMerkleTree::get_multi_proof`] and consumed by Solidity's
+/// `MerkleProof.multiProofVerify(proof, proofFlags, root, leaves)`. Folding it takes
+/// `proof_flags.len()` hashes: each one combines the next pending node with either the next pending
+/// node too (`true`) or the next element of `proof` (`false`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiProof {
+    /// The hashes of the proven leaves, in the order they are folded.
+    pub leaves: Vec<B256>,
+    /// The sibling hashes that aren't computed from the leaves.
+    pub proof: Vec<B256>,
+    /// For every hash of the fold, whether its second input comes from the pending nodes.
+    pub proof_flags: Vec<bool>,
 }
 
-/// Generates a Merkle proof for a leaf at a given index.
-fn make_proof(tree: &[B256], index: usize) -> Result<Vec<B256>, MerkleTreeError> {
-    check_leaf_node(tree, index)?;
+/// Everything a Merkle-verification circuit needs to prove the inclusion of one leaf.
+///
+/// Created by [`StandardMerkleTree::witness`]. Level `i` of the path combines the current node with
+/// `siblings[i]` into `node_at_level[i]`, so the last node is the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleWitness {
+    /// The hash of the proven leaf.
+    pub leaf_hash: B256,
+    /// The sibling of the node on the path at every level, from the leaf up.
+    pub siblings: Vec<B256>,
+    /// The node computed at every level, from the parent of the leaf up to the root.
+    pub node_at_level: Vec<B256>,
+    /// For every level, whether the node on the path is the right child of its parent.
+    pub path_bits: Vec<bool>,
+    /// The root of the tree.
+    pub root: B256,
+}
 
-    let mut proof = Vec::new();
-    let mut current_index = index;
-    while current_index > 0 {
-        let sibling = sibling_index(current_index)?;
+/// A single commitment to the inclusion proofs of many leaves of a [`StandardMerkleTree`].
+///
+/// Created by [`StandardMerkleTree::aggregate_proofs`]. The commitment is
+/// `keccak256(entry_0 ++ entry_1 ++ ...)`, where each entry is the leaf hash, followed by the number
+/// of proof elements as a big-endian `uint256` word, followed by the proof elements:
+///
+/// ```text
+/// leaf_hash (32 bytes) ++ uint256(proof.len()) (32 bytes) ++ proof[0] ++ ... ++ proof[n - 1]
+/// ```
+///
+/// The entries are kept in the order the values were given, so a verifier holding only the
+/// commitment can be handed them later and [expand](Self::expand_and_verify) it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregateCommitment {
+    /// The hash over every entry, in order.
+    pub commitment: B256,
+    /// The leaf hash and proof of every aggregated value, in order.
+    pub entries: Vec<(B256, Vec<B256>)>,
+}
 
-        if sibling < tree.len() {
-            proof.push(tree[sibling]);
+impl AggregateCommitment {
+    /// Computes the commitment over `entries` using the layout described on [`AggregateCommitment`].
+    pub fn commit(entries: &[(B256, Vec<B256>)]) -> B256 {
+        let mut hasher = Keccak256::new();
+        for (leaf_hash, proof) in entries {
+            hasher.update(leaf_hash);
+            hasher.update(U256::from(proof.len()).to_be_bytes::<32>());
+            for sibling in proof {
+                hasher.update(sibling);
+            }
         }
-        current_index = parent_index(current_index);
+        hasher.finalize()
     }
 
-    Ok(proof)
+    /// Checks that the entries hash to the commitment and that every proof is valid against `root`.
+    pub fn expand_and_verify(&self, root: B256) -> bool {
+        Self::commit(&self.entries) == self.commitment
+            && self
+                .entries
+                .iter()
+                .all(|(leaf_hash, proof)| process_proof(*leaf_hash, proof) == root)
+    }
 }
 
-/// Processes a Merkle proof to compute the implied root hash.
+/// Verifies a [`MultiProof`] incrementally, hashing as soon as the leaves fed so far allow.
 ///
-/// Returns `B256` hash of the implied Merkle root.
-fn process_proof(leaf: B256, proof: Vec<B256>) -> B256 {
-    proof.into_iter().fold(leaf, hash_pair)
+/// Feed the leaf hashes in the order of [`MultiProof::leaves`] with [`Self::feed_leaf`], then call
+/// [`Self::finalize`]. Only the proof and flags are borrowed: at most two fed leaves are held at a
+/// time, plus the computed nodes still waiting for their sibling. OpenZeppelin's breadth-first order
+/// makes that a queue rather than a stack, but it never holds more nodes than there are leaves. The
+/// result is always the same as [`process_multi_proof`]'s.
+#[derive(Debug, Clone)]
+pub struct MultiProofVerifier<'a> {
+    /// The sibling hashes that aren't computed from the leaves.
+    proof: &'a [B256],
+    /// For every hash of the fold, whether its second input comes from the pending nodes.
+    proof_flags: &'a [bool],
+    /// The number of leaves the flags still expect to be fed.
+    leaves_left: usize,
+    /// The fed leaves that haven't been hashed yet.
+    leaves: VecDeque<B256>,
+    /// The computed nodes that haven't been hashed yet.
+    nodes: VecDeque<B256>,
+    /// The number of flags consumed.
+    flag_index: usize,
+    /// The number of proof elements consumed.
+    proof_index: usize,
+    /// Whether the proof is already known to be invalid.
+    failed: bool,
 }
 
-/// Hashes a pair of `B256` values to compute their parent hash.
-fn hash_pair(left: B256, right: B256) -> B256 {
-    let combined = if left <= right { left } else { right };
-    let second = if left <= right { right } else { left };
-
-    let mut hasher = Keccak256::new();
-    hasher.update(combined);
-    hasher.update(second);
-    hasher.finalize()
-}
+impl<'a> MultiProofVerifier<'a> {
+    /// Creates a verifier for the proof elements and flags of a [`MultiProof`].
+    ///
+    /// The number of leaves to feed follows from them: `proof_flags.len() + 1 - proof.len()`.
+    pub fn new(proof: &'a [B256], proof_flags: &'a [bool]) -> Self {
+        let expected_leaves = (proof_flags.len() + 1).checked_sub(proof.len());
+        Self {
+            proof,
+            proof_flags,
+            leaves_left: expected_leaves.unwrap_or(0),
+            leaves: VecDeque::new(),
+            nodes: VecDeque::new(),
+            flag_index: 0,
+            proof_index: 0,
+            failed: expected_leaves.is_none(),
+        }
+    }
 
-#[cfg(test)]
-mod test {
-    use crate::alloc::string::ToString;
-    use crate::standard_binary_tree::StandardMerkleTree;
-    use alloc::vec::Vec;
-    use alloy::dyn_abi::DynSolValue;
-    use alloy::primitives::{hex::FromHex, FixedBytes};
+    /// Feeds the next leaf hash, hashing every pair it completes.
+    ///
+    /// Feeding more leaves than the flags expect makes the proof invalid.
+    pub fn feed_leaf(&mut self, leaf_hash: B256) {
+        if self.leaves_left == 0 {
+            self.failed = true;
+            return;
+        }
+        self.leaves_left -= 1;
+        self.leaves.push_back(leaf_hash);
+        self.step();
+    }
 
-    /// Tests the [`StandardMerkleTree`] with string-type leaves.
-    #[test]
-    fn test_tree_string_type() {
-        let num_leaves = 1000;
-        let mut leaves = Vec::new();
-        for i in 0..num_leaves {
-            leaves.push(DynSolValue::String(i.to_string()));
+    /// Checks that every leaf was fed and the fold reached `root`.
+    pub fn finalize(mut self, root: B256) -> bool {
+        if self.leaves_left > 0 {
+            return false;
+        }
+        self.step();
+        if self.failed || self.flag_index < self.proof_flags.len() {
+            return false;
         }
-        let tree = StandardMerkleTree::of(&leaves);
 
-        for leaf in leaves.into_iter() {
-            let proof = tree.get_proof(&leaf).unwrap();
-            let is_valid = tree.verify_proof(&leaf, proof);
-            assert!(is_valid);
+        let computed = self.nodes.back().or(self.leaves.back()).copied();
+        computed.or(self.proof.get(self.proof_index).copied()) == Some(root)
+    }
+
+    /// Consumes flags for as long as their inputs are available.
+    fn step(&mut self) {
+        while !self.failed && self.flag_index < self.proof_flags.len() {
+            let from_queue = 1 + usize::from(self.proof_flags[self.flag_index]);
+            // Leaves precede every computed node in the queue, so nodes can't be used until the
+            // last leaf has been fed.
+            let available = if self.leaves_left > 0 {
+                self.leaves.len()
+            } else {
+                self.leaves.len() + self.nodes.len()
+            };
+            if available < from_queue {
+                self.failed = self.leaves_left == 0;
+                return;
+            }
+
+            let a = self.pop_queue();
+            let b = if self.proof_flags[self.flag_index] {
+                self.pop_queue()
+            } else {
+                self.proof_index += 1;
+                self.proof.get(self.proof_index - 1).copied()
+            };
+            match (a, b) {
+                (Some(a), Some(b)) => self.nodes.push_back(hash_pair(a, b)),
+                _ => self.failed = true,
+            }
+            self.flag_index += 1;
         }
     }
 
-    /// Tests the `StandardMerkleTree` with bytes32-type leaves.
-    #[test]
-    fn test_tree_bytes32_type() {
-        let mut leaves = Vec::new();
+    /// Pops the front of the queue of leaves followed by computed nodes.
+    fn pop_queue(&mut self) -> Option<B256> {
+        self.leaves.pop_front().or_else(|| self.nodes.pop_front())
+    }
+}
 
-        let leaf = DynSolValue::FixedBytes(
-            FixedBytes::<32>::from_hex(
-                "0x46296bc9cb11408bfa46c5c31a542f12242db2412ee2217b4e8add2bc1927d0b",
-            )
-            .unwrap(),
-            32,
-        );
+impl Default for StandardMerkleTree {
+    /// Creates a new, empty `StandardMerkleTree`.
+    fn default() -> Self {
+        Self::new(Vec::new(), Vec::new()).expect("an empty tree has no values to validate")
+    }
+}
 
-        leaves.push(leaf);
+impl StandardMerkleTree {
+    /// Creates a new [`StandardMerkleTree`] with the given tree nodes and values.
+    ///
+    /// Duplicate values are kept at every given position, as by [`Self::of`]. Returns
+    /// [`MerkleTreeError::NotSupportedType`] if a value isn't of a supported leaf type.
+    pub fn new(
+        tree: Vec<B256>,
+        values: Vec<(&DynSolValue, usize)>,
+    ) -> Result<Self, MerkleTreeError> {
+        Self::from_parts(tree, values)
+    }
 
-        let tree = StandardMerkleTree::of(&leaves);
+    /// Constructs a [`StandardMerkleTree`] from a slice of dynamic Solidity values.
+    ///
+    /// Duplicate values are accepted, as OpenZeppelin accepts them, and each occurrence is a leaf
+    /// of its own: every position of a duplicate stays provable, with [`Self::leaf_positions`] and
+    /// [`Self::get_proof_by_index`], while [`Self::get_proof`] proves the first one. Returns
+    /// [`MerkleTreeError::NotSupportedType`] if a value isn't of a supported leaf type.
+    pub fn of(values: &[DynSolValue]) -> Result<Self, MerkleTreeError> {
+        Self::of_with_options(values, TreeOptions::default())
+    }
 
-        for leaf in leaves.into_iter() {
-            let proof = tree.get_proof(&leaf).unwrap();
-            let is_valid = tree.verify_proof(&leaf, proof);
-            assert!(is_valid);
+    /// Constructs a [`StandardMerkleTree`] like [`Self::of`], for values known to be supported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value isn't of a supported leaf type.
+    pub fn of_unchecked(values: &[DynSolValue]) -> Self {
+        Self::of(values).expect("Not supported value type")
+    }
+
+    /// Constructs a [`StandardMerkleTree`] whose leaves are each bound to a nonce.
+    ///
+    /// Each leaf is the tuple `(value, uint256(nonce))`, hashed like the leaves of [`Self::of_multi`]
+    /// as `keccak256(keccak256(abi.encode(value, nonce)))`, so equal values with different nonces
+    /// are distinct leaves. The tuple is the stored value, so [`Self::values`], [`Self::dump`] and
+    /// [`Self::update_leaf`] all keep the nonce. Prove and verify leaves with
+    /// [`Self::get_proof_with_nonce`] and [`Self::verify_proof_with_nonce`], or with the methods
+    /// taking a value, given the tuple.
+    ///
+    /// Returns [`MerkleTreeError::NotSupportedType`] if a value isn't of a supported leaf type.
+    pub fn of_with_nonce(values: &[(DynSolValue, u64)]) -> Result<Self, MerkleTreeError> {
+        let leaves = values
+            .iter()
+            .map(|(value, nonce)| nonce_leaf(value, *nonce))
+            .collect::<Result<Vec<DynSolValue>, MerkleTreeError>>()?;
+
+        Self::of(&leaves)
+    }
+
+    /// Constructs a block-style [`StandardMerkleTree`] over transaction hashes.
+    ///
+    /// Unlike [`Self::of`], the tree follows the usual convention for transaction trees:
+    ///
+    /// - the leaves are the hashes themselves ([`LeafHashMode::Raw`]), in the given order;
+    /// - a parent is `keccak256(left ++ right)`, without sorting ([`HashOrder::Positional`]);
+    /// - the last node of an odd level is paired with itself ([`OddNodePolicy::HashWithSelf`]).
+    ///
+    /// A single hash is its own root. Proofs can be checked without the tree, using the position of
+    /// the leaf, with [`verify_positional`].
+    pub fn of_tx_hashes(hashes: &[B256]) -> Self {
+        let values = hashes
+            .iter()
+            .map(|hash| DynSolValue::FixedBytes(*hash, 32))
+            .collect::<Vec<DynSolValue>>();
+        let options = TreeOptions {
+            odd_node_policy: OddNodePolicy::HashWithSelf,
+            hash_order: HashOrder::Positional,
+            leaf_hash_mode: LeafHashMode::Raw,
+            ..TreeOptions::default()
+        };
+
+        Self::from_hashed_values(&values, hashes.to_vec(), options)
+            .expect("bytes32 leaves are always supported")
+    }
+
+    /// Constructs a [`StandardMerkleTree`] whose leaves are packed `bytes32` values, hashed with
+    /// [`leaf_hash_packed_bytes32`].
+    ///
+    /// Each leaf is stored as the tuple of its parts: `bytes32` words ABI-encode to their packed
+    /// concatenation, so the tuple hashes like any leaf of [`Self::of_multi`] to the packed leaf
+    /// hash, and [`Self::values`], [`Self::dump`] and [`Self::update_leaf`] need no special
+    /// handling. Prove and verify leaves with [`Self::get_proof_packed_bytes32`] and
+    /// [`Self::verify_proof_packed_bytes32`], or with the methods taking a value, given the tuple.
+    pub fn of_packed_bytes32(leaves: &[Vec<B256>]) -> Self {
+        let values = leaves
+            .iter()
+            .map(|parts| packed_bytes32_leaf(parts))
+            .collect::<Vec<DynSolValue>>();
+
+        Self::of(&values).expect("tuples of bytes32 leaves are always supported")
+    }
+
+    /// Constructs a [`StandardMerkleTree`] over precomputed commitments, e.g. hashes of encrypted
+    /// payloads, so the tree commits to data it never sees.
+    ///
+    /// With [`LeafHashMode::Double`] each commitment is hashed into its leaf like any `bytes32`,
+    /// `keccak256(keccak256(commitment))`; with [`LeafHashMode::Raw`] the commitment is the leaf.
+    /// The leaves are `bytes32` values, so prove them by index with [`Self::get_proof_by_index`], or
+    /// by value as `DynSolValue::FixedBytes(commitment, 32)`.
+    pub fn of_commitments(commitments: &[B256], leaf_hash_mode: LeafHashMode) -> Self {
+        let values = commitments
+            .iter()
+            .map(|commitment| DynSolValue::FixedBytes(*commitment, 32))
+            .collect::<Vec<DynSolValue>>();
+        let options = TreeOptions {
+            leaf_hash_mode,
+            ..TreeOptions::default()
+        };
+
+        Self::of_with_options(&values, options).expect("bytes32 leaves are always supported")
+    }
+
+    /// Constructs a [`StandardMerkleTree`] directly from leaf hashes computed elsewhere, e.g. by
+    /// another system or a prover, without hashing them again.
+    ///
+    /// Each hash is the leaf itself, so a tree of the [`standard_leaf_hash`]es of some values has
+    /// the root of [`Self::of`] over them. The leaves are looked up by hash: prove them with
+    /// [`Self::get_proof_by_hash`] and verify with [`Self::verify_proof_by_hash`]. This is
+    /// [`Self::of_commitments`] with [`LeafHashMode::Raw`].
+    pub fn of_hashes(leaves: &[B256]) -> Self {
+        Self::of_commitments(leaves, LeafHashMode::Raw)
+    }
+
+    /// Constructs a [`StandardMerkleTree`] of `size` leaves where each value of `entries` is the
+    /// leaf at its given index and every other leaf is `empty`, e.g. for a tree indexed by token id
+    /// where not every id is minted.
+    ///
+    /// Present leaves are hashed like in [`Self::of`]; `empty` is the leaf node itself. Prove an
+    /// absent index with [`Self::get_proof_by_index`], which verifies `empty` with
+    /// [`process_proof`]. Only the present values are kept, in leaf order, in [`Self::values`];
+    /// [`Self::entries`] and [`Self::dump`] pair each with its own leaf index.
+    /// Returns [`MerkleTreeError::LeafNotFound`] if an index isn't below `size`,
+    /// [`MerkleTreeError::DuplicatedIndex`] if two entries share an index, and
+    /// [`MerkleTreeError::NotSupportedType`] if a value isn't of a supported leaf type.
+    pub fn of_by_index(
+        entries: &[(usize, DynSolValue)],
+        size: usize,
+        empty: B256,
+    ) -> Result<Self, MerkleTreeError> {
+        let mut leaf_hashes = vec![empty; size];
+        let mut present = vec![false; size];
+        for (leaf_index, value) in entries {
+            if *leaf_index >= size {
+                return Err(MerkleTreeError::LeafNotFound);
+            }
+            if core::mem::replace(&mut present[*leaf_index], true) {
+                return Err(MerkleTreeError::DuplicatedIndex);
+            }
+            leaf_hashes[*leaf_index] = standard_leaf_hash(value)?;
+        }
+
+        let tree = make_merkle_tree::<Keccak256Hasher>(leaf_hashes, HashOrder::Sorted);
+        let values = entries
+            .iter()
+            .map(|(leaf_index, value)| (value, tree.len() - 1 - leaf_index))
+            .collect();
+        Self::from_parts(tree, values)
+    }
+
+    /// Computes the root [`Self::of`] would produce for `values`, without building the tree.
+    ///
+    /// No value lookup map is built and a few scratch buffers are reused to encode every leaf, so
+    /// this is the cheaper choice when only the root is needed. Leaves are encoded in place, without
+    /// allocating, except the dynamic columns of tuples, such as a `string`, whose ABI encoding is
+    /// built apart once per distinct value and copied in. Returns
+    /// [`MerkleTreeError::NotSupportedType`] if a value isn't of a supported leaf type, and
+    /// `Ok(None)` for no values, like [`Self::root`] of an empty tree.
+    pub fn root_of(values: &[DynSolValue]) -> Result<Option<B256>, MerkleTreeError> {
+        let leaf_hashes = hash_leaves::<Keccak256Hasher>(values, &TreeOptions::default())?;
+
+        Ok(
+            make_merkle_tree::<Keccak256Hasher>(leaf_hashes, HashOrder::Sorted)
+                .first()
+                .copied(),
+        )
+    }
+
+    /// Computes the root and every `(leaf_index, proof)` [`Self::of`] would produce for `values`,
+    /// without keeping the tree, for a builder that only ships proofs and a root to verifiers.
+    ///
+    /// No value lookup map is built and the nodes are dropped once the proofs are generated.
+    /// Returns [`MerkleTreeError::NotSupportedType`] if a value isn't of a supported leaf type, and
+    /// [`MerkleTreeError::LeafNotFound`] for no values, since an empty tree has no root.
+    pub fn build_and_split(
+        values: &[DynSolValue],
+    ) -> Result<(B256, IndexedProofs), MerkleTreeError> {
+        let leaf_hashes = hash_leaves::<Keccak256Hasher>(values, &TreeOptions::default())?;
+        let tree = make_merkle_tree::<Keccak256Hasher>(leaf_hashes, HashOrder::Sorted);
+        let root = *tree.first().ok_or(MerkleTreeError::LeafNotFound)?;
+        let proofs = (0..values.len())
+            .map(|leaf_index| Ok((leaf_index, make_proof(&tree, tree.len() - 1 - leaf_index)?)))
+            .collect::<Result<IndexedProofs, MerkleTreeError>>()?;
+
+        Ok((root, proofs))
+    }
+
+    /// Constructs a [`StandardMerkleTree`] like [`Self::of`], calling `on_level` as each level of the
+    /// tree is completed, e.g. to persist levels or report progress.
+    ///
+    /// Levels are those of the flat tree array: level `d` holds the nodes at indices
+    /// `2^d - 1 .. 2^(d + 1) - 1`, so the root is level 0 and, in OpenZeppelin's layout, the leaves
+    /// can span the two deepest levels. `on_level` is called once per level, from
+    /// [`Self::depth`] up to 0. Returns [`MerkleTreeError::NotSupportedType`] before building
+    /// anything if a value isn't of a supported leaf type.
+    pub fn build_with_level_callback(
+        values: &[DynSolValue],
+        mut on_level: impl FnMut(usize, &[B256]),
+    ) -> Result<Self, MerkleTreeError> {
+        let leaf_hashes = values
+            .iter()
+            .map(standard_leaf_hash)
+            .collect::<Result<Vec<B256>, MerkleTreeError>>()?;
+        let tree =
+            make_merkle_tree_with::<Keccak256Hasher>(leaf_hashes, HashOrder::Sorted, &mut on_level);
+
+        Self::from_tree(values, tree, TreeOptions::default())
+    }
+
+    /// Constructs a [`StandardMerkleTree`] of multi-value leaves, like OpenZeppelin's
+    /// `StandardMerkleTree.of([[address, uint256], ...], ["address", "uint256"])`.
+    ///
+    /// Each leaf is the [`DynSolValue::Tuple`] of its values, hashed as
+    /// `keccak256(keccak256(abi.encode(values)))`, so roots and proofs match OpenZeppelin's for the
+    /// same leaves in the same order. Look leaves up by their tuple, e.g. with
+    /// `tree.get_proof(&DynSolValue::Tuple(values))`, or by index with [`Self::iter_proofs`].
+    pub fn of_multi(values: &[Vec<DynSolValue>]) -> Result<Self, MerkleTreeError> {
+        let tuples = values
+            .iter()
+            .map(|columns| DynSolValue::Tuple(columns.clone()))
+            .collect::<Vec<DynSolValue>>();
+
+        Self::of(&tuples)
+    }
+
+    /// Constructs a [`StandardMerkleTree`] like [`Self::of`], together with a Bloom filter of its
+    /// leaf hashes to be committed alongside the root.
+    ///
+    /// The filter has `bits` bits, rounded up to a whole number of bytes, and sets `k` bits per leaf,
+    /// as described on [`bloom_maybe_contains`]. It never rejects a leaf of the tree, but accepts a
+    /// leaf that isn't in it with probability about `(1 - e^(-k * n / bits))^k` for `n` leaves, so a
+    /// larger filter saves more proof checks at the cost of more storage. For a given size, the best
+    /// `k` is `bits / n * ln 2`: e.g. 10 bits per leaf with `k = 7` gives about 1% false positives.
+    ///
+    /// Returns [`MerkleTreeError::InvalidBloomParameters`] if `bits` or `k` is zero.
+    pub fn build_with_bloom(
+        values: &[DynSolValue],
+        bits: usize,
+        k: usize,
+    ) -> Result<(Self, Vec<u8>), MerkleTreeError> {
+        if bits == 0 || k == 0 {
+            return Err(MerkleTreeError::InvalidBloomParameters);
+        }
+        let tree = Self::of(values)?;
+
+        let mut bloom = vec![0u8; bits.div_ceil(8)];
+        for leaf_hash in tree.leaf_hashes() {
+            for position in bloom_positions(bloom.len() * 8, k, leaf_hash) {
+                bloom[position / 8] |= 1 << (position % 8);
+            }
+        }
+        Ok((tree, bloom))
+    }
+
+    /// Returns a [`StandardMerkleTreeBuilder`] to construct a tree with non-default options.
+    pub fn builder() -> StandardMerkleTreeBuilder {
+        StandardMerkleTreeBuilder::new()
+    }
+
+    /// Constructs a [`StandardMerkleTree`] from a slice of dynamic Solidity values with the given options.
+    ///
+    /// This is [`Self::builder`] with the options given at once, e.g. to rebuild a tree with the
+    /// [`Self::options`] of another, or to build a positional tree for verifiers that hash children
+    /// in left-right order with `hash_order: HashOrder::Positional`. The values are hashed in the
+    /// given order; `custom_sort` is only recorded. Returns [`MerkleTreeError::NotSupportedType`] if
+    /// a value isn't of a supported leaf type, and [`MerkleTreeError::IncompatibleOptions`] for a
+    /// `chain_id` with [`LeafHashMode::Raw`].
+    pub fn of_with_options(
+        values: &[DynSolValue],
+        options: TreeOptions,
+    ) -> Result<Self, MerkleTreeError> {
+        Self::of_with_hasher_and_options(values, options)
+    }
+
+    /// Constructs a [`StandardMerkleTree`] from an asynchronous stream of leaves.
+    ///
+    /// Each leaf is hashed as it arrives, and the tree is built once the stream ends. An empty stream
+    /// yields an empty tree. Returns [`MerkleTreeError::NotSupportedType`] as soon as a leaf of an
+    /// unsupported type arrives.
+    #[cfg(feature = "futures")]
+    pub async fn of_stream<S: Stream<Item = DynSolValue>>(
+        stream: S,
+    ) -> Result<Self, MerkleTreeError> {
+        let mut stream = core::pin::pin!(stream);
+        let mut values = Vec::new();
+        let mut leaf_hashes = Vec::new();
+        while let Some(value) = stream.next().await {
+            leaf_hashes.push(standard_leaf_hash(&value)?);
+            values.push(value);
+        }
+
+        if values.is_empty() {
+            return Ok(Self::default());
+        }
+        Self::from_hashed_values(&values, leaf_hashes, TreeOptions::default())
+    }
+
+    /// Constructs a [`StandardMerkleTree`] from ABI-encoded leaves, decoding each of them as `ty`.
+    ///
+    /// Returns [`MerkleTreeError::InvalidAbiEncoding`] with the index of the first leaf that fails to
+    /// decode, or [`MerkleTreeError::NotSupportedType`] if `ty` decodes to an unsupported leaf type.
+    pub fn of_abi_encoded(
+        encoded_leaves: &[Vec<u8>],
+        ty: &DynSolType,
+    ) -> Result<Self, MerkleTreeError> {
+        let values = encoded_leaves
+            .iter()
+            .enumerate()
+            .map(|(i, encoded)| {
+                ty.abi_decode(encoded)
+                    .map_err(|_| MerkleTreeError::InvalidAbiEncoding(i))
+            })
+            .collect::<Result<Vec<DynSolValue>, MerkleTreeError>>()?;
+
+        Self::of(&values)
+    }
+
+    /// Loads a tree dumped in OpenZeppelin's `standard-v1` format, e.g. by its JS library's
+    /// `StandardMerkleTree.dump()`, checking that it is internally consistent.
+    ///
+    /// Every leaf is rehashed from its columns and every internal node, up to the root, from its
+    /// children. Leaves are [`DynSolValue::Tuple`]s of their columns, except for single `uintN` or
+    /// `bytesN` columns, which hash the same either way and are kept as plain values, and single
+    /// `string` columns of trees dumped by [`Self::dump`], whose leaves are the unencoded strings
+    /// rather than OpenZeppelin's one-column tuples. Leaves without a
+    /// value, as dumped from [`Self::of_by_index`], are checked through their parents only.
+    ///
+    /// # Returns
+    /// - `Err(MerkleTreeError::NotSupportedType)` - If a type of the leaf encoding can't be parsed.
+    /// - `Err(MerkleTreeError::InvalidCheck)` - If the format is unknown, a value doesn't parse, or
+    ///   the nodes don't match the values.
+    pub fn load(data: StandardMerkleTreeData) -> Result<Self, MerkleTreeError> {
+        if data.format != STANDARD_V1_FORMAT {
+            return Err(MerkleTreeError::InvalidCheck);
+        }
+        let types = data
+            .leaf_encoding
+            .iter()
+            .map(|name| DynSolType::parse(name).map_err(|_| MerkleTreeError::NotSupportedType))
+            .collect::<Result<Vec<DynSolType>, MerkleTreeError>>()?;
+        let tree = data
+            .tree
+            .iter()
+            .map(|node| {
+                node.parse::<B256>()
+                    .map_err(|_| MerkleTreeError::InvalidCheck)
+            })
+            .collect::<Result<Vec<B256>, MerkleTreeError>>()?;
+        let values = data
+            .values
+            .iter()
+            .map(|entry| {
+                if entry.value.len() != types.len() {
+                    return Err(MerkleTreeError::InvalidCheck);
+                }
+                let mut columns = types
+                    .iter()
+                    .zip(entry.value.iter())
+                    .map(|(ty, column)| {
+                        ty.coerce_str(column)
+                            .map_err(|_| MerkleTreeError::InvalidCheck)
+                    })
+                    .collect::<Result<Vec<DynSolValue>, MerkleTreeError>>()?;
+                let leaf = match columns.as_slice() {
+                    [DynSolValue::Address(_)
+                    | DynSolValue::Bool(_)
+                    | DynSolValue::Int(_, _)
+                    | DynSolValue::Uint(_, _)
+                    | DynSolValue::FixedBytes(_, _)] => columns.remove(0),
+                    _ => DynSolValue::Tuple(columns),
+                };
+                Ok((leaf, entry.tree_index))
+            })
+            .collect::<Result<Vec<(DynSolValue, usize)>, MerkleTreeError>>()?;
+        let values = match (types.as_slice(), values.first()) {
+            // A tree of plain strings, as dumped by `dump`, hashes its leaves unencoded.
+            ([DynSolType::String], Some((DynSolValue::Tuple(columns), tree_index)))
+                if tree.get(*tree_index) == Some(&standard_leaf_hash(&columns[0])?) =>
+            {
+                values
+                    .into_iter()
+                    .map(|(leaf, tree_index)| match leaf {
+                        DynSolValue::Tuple(mut columns) => (columns.remove(0), tree_index),
+                        leaf => (leaf, tree_index),
+                    })
+                    .collect()
+            }
+            _ => values,
+        };
+
+        // Trees built with `of_by_index` have no value for their absent leaves.
+        let leaf_count = (tree.len() + 1) / 2;
+        if tree.len() != (2 * leaf_count).saturating_sub(1) || values.len() > leaf_count {
+            return Err(MerkleTreeError::InvalidCheck);
+        }
+        let first_leaf = tree.len() - leaf_count;
+        let mut seen = vec![false; leaf_count];
+        for (leaf, tree_index) in values.iter() {
+            if *tree_index < first_leaf
+                || *tree_index >= tree.len()
+                || core::mem::replace(&mut seen[tree_index - first_leaf], true)
+                || tree[*tree_index] != standard_leaf_hash(leaf)?
+            {
+                return Err(MerkleTreeError::InvalidCheck);
+            }
+        }
+        for index in 0..first_leaf {
+            let children = (
+                tree[left_child_index(index)],
+                tree[right_child_index(index)],
+            );
+            if tree[index] != hash_pair(children.0, children.1) {
+                return Err(MerkleTreeError::InvalidCheck);
+            }
+        }
+
+        Self::new(
+            tree,
+            values
+                .iter()
+                .map(|(leaf, tree_index)| (leaf, *tree_index))
+                .collect(),
+        )
+    }
+
+    /// Aggregates the proofs of `values` into one [`AggregateCommitment`].
+    ///
+    /// Returns [`MerkleTreeError::LeafNotFound`] if any value isn't in the tree.
+    pub fn aggregate_proofs(
+        &self,
+        values: &[DynSolValue],
+    ) -> Result<AggregateCommitment, MerkleTreeError> {
+        let entries = values
+            .iter()
+            .map(|value| Ok((self.leaf_hash(value)?, self.get_proof(value)?)))
+            .collect::<Result<Vec<_>, MerkleTreeError>>()?;
+
+        Ok(AggregateCommitment {
+            commitment: AggregateCommitment::commit(&entries),
+            entries,
+        })
+    }
+}
+
+impl<H: Hasher> StandardMerkleTree<H> {
+    /// Constructs a [`StandardMerkleTree`] like [`Self::of`], hashing leaves and pairs with `H`
+    /// instead of OpenZeppelin's keccak256, e.g. `StandardMerkleTree::<MyHasher>::of_with_hasher`.
+    ///
+    /// Leaves are encoded as by [`Self::of`] and passed to [`Hasher::hash_leaf`]; every other option
+    /// is the default. Returns [`MerkleTreeError::NotSupportedType`] if a value isn't of a supported
+    /// leaf type.
+    pub fn of_with_hasher(values: &[DynSolValue]) -> Result<Self, MerkleTreeError> {
+        let leaf_hashes = values
+            .iter()
+            .map(|value| leaf_hash_with_options::<H>(value, &TreeOptions::default()))
+            .collect::<Result<Vec<B256>, MerkleTreeError>>()?;
+
+        Self::from_hashed_values(values, leaf_hashes, TreeOptions::default())
+    }
+
+    /// Constructs a [`StandardMerkleTree`] like [`Self::of_with_options`], hashing leaves and pairs
+    /// with `H`.
+    ///
+    /// With [`HashOrder::Positional`] pairs are hashed with [`Hasher::hash_pair_ordered`], so `H`
+    /// needn't sort them. Returns the errors of [`Self::of_with_options`].
+    pub fn of_with_hasher_and_options(
+        values: &[DynSolValue],
+        options: TreeOptions,
+    ) -> Result<Self, MerkleTreeError> {
+        options.check()?;
+        let leaf_hashes = hash_leaves::<H>(values, &options)?;
+
+        Self::from_hashed_values(values, leaf_hashes, options)
+    }
+
+    /// Creates a tree with the given nodes and values, hashed with `H`.
+    fn from_parts(
+        tree: Vec<B256>,
+        values: Vec<(&DynSolValue, usize)>,
+    ) -> Result<Self, MerkleTreeError> {
+        let leaf_count = (tree.len() + 1) / 2;
+        let mut tree_values: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut leaves = Vec::with_capacity(values.len());
+        for (tree_key, tree_value) in values.into_iter() {
+            let tree_key_str = Self::check_valid_value_type(tree_key)?;
+            tree_values
+                .entry(tree_key_str)
+                .or_default()
+                .push(tree_value);
+            leaves.push((tree_value, tree_key.clone()));
+        }
+        // Leaves are stored in reverse, so leaf order is descending tree index order.
+        leaves.sort_by(|(a, _), (b, _)| b.cmp(a));
+        Ok(Self {
+            tree,
+            tree_values,
+            options: TreeOptions::default(),
+            leaf_count,
+            values: leaves.into_iter().map(|(_, value)| value).collect(),
+            keys: HashMap::new(),
+            hasher: PhantomData,
+        })
+    }
+
+    /// Constructs a [`StandardMerkleTree`] from dynamic Solidity values and their precomputed leaf hashes.
+    fn from_hashed_values(
+        values: &[DynSolValue],
+        leaf_hashes: Vec<B256>,
+        options: TreeOptions,
+    ) -> Result<Self, MerkleTreeError> {
+        // Build the Merkle tree from the leaf hashes.
+        let tree = match options.odd_node_policy {
+            OddNodePolicy::Promote => make_merkle_tree::<H>(leaf_hashes, options.hash_order),
+            policy => make_padded_merkle_tree::<H>(leaf_hashes, policy, options.hash_order),
+        };
+
+        Self::from_tree(values, tree, options)
+    }
+
+    /// Constructs a [`StandardMerkleTree`] from dynamic Solidity values and the tree built over them.
+    fn from_tree(
+        values: &[DynSolValue],
+        tree: Vec<B256>,
+        options: TreeOptions,
+    ) -> Result<Self, MerkleTreeError> {
+        // Map each value to its corresponding index in the tree.
+        let indexed_values: Vec<(&DynSolValue, usize)> = values
+            .iter()
+            .enumerate()
+            .map(|(leaf_index, value)| (value, tree.len() - leaf_index - 1))
+            .collect();
+
+        Ok(Self {
+            options,
+            leaf_count: values.len(),
+            ..Self::from_parts(tree, indexed_values)?
+        })
+    }
+
+    /// Returns the options the tree was built with.
+    pub fn options(&self) -> &TreeOptions {
+        &self.options
+    }
+
+    /// Returns the nodes of the tree as a flat array.
+    ///
+    /// The root is at index `0` and the children of the node at index `i` are at `2 * i + 1` and
+    /// `2 * i + 2`, with the leaves stored at the tail of the array in reverse order. This is the layout
+    /// a [`LazyTree`](crate::lazy_tree::LazyTree) fetches nodes from.
+    pub fn nodes(&self) -> &[B256] {
+        &self.tree
+    }
+
+    /// Returns the nodes of the tree as `0x`-prefixed hex strings, in the order of the `tree` field of
+    /// OpenZeppelin's `StandardMerkleTree.dump()`.
+    ///
+    /// The flat array is laid out exactly like OpenZeppelin's, so this can be passed to
+    /// `StandardMerkleTree.load` as is.
+    pub fn tree_hex(&self) -> Vec<String> {
+        self.tree.iter().map(hex::encode_prefixed).collect()
+    }
+
+    /// Renders the tree as indented text, one node per line, like OpenZeppelin's `render()`, to
+    /// see why a root doesn't match.
+    ///
+    /// Each line holds the index of a node in the tree array and its shortened hash, and leaves are
+    /// marked with their leaf index:
+    ///
+    /// ```text
+    /// 0) 0x1a2b3c4d…5e6f7a8b
+    /// ├─ 1) 0x…
+    /// │  ├─ 3) 0x… leaf 3
+    /// │  └─ 4) 0x… leaf 2
+    /// └─ 2) 0x…
+    /// ```
+    pub fn render(&self) -> String {
+        let mut rendered = String::new();
+        if self.tree.is_empty() {
+            return rendered;
+        }
+        // Each entry is a node with the prefix of its line and the prefix of its children's lines.
+        let mut stack = vec![(0, String::new(), String::new())];
+        while let Some((index, line_prefix, child_prefix)) = stack.pop() {
+            rendered.push_str(&line_prefix);
+            rendered.push_str(&alloc::format!("{index}) {}", short_hex(self.tree[index])));
+            if let Ok(leaf_index) = self.tree_index_to_leaf_index(index) {
+                rendered.push_str(&alloc::format!(" leaf {leaf_index}"));
+            }
+            rendered.push('\n');
+
+            if is_internal_node(&self.tree, index) {
+                // The right child is pushed first so that the left one is rendered first.
+                stack.push((
+                    right_child_index(index),
+                    alloc::format!("{child_prefix}└─ "),
+                    alloc::format!("{child_prefix}   "),
+                ));
+                stack.push((
+                    left_child_index(index),
+                    alloc::format!("{child_prefix}├─ "),
+                    alloc::format!("{child_prefix}│  "),
+                ));
+            }
+        }
+        rendered
+    }
+
+    /// Dumps the tree in OpenZeppelin's `standard-v1` format, see [`StandardMerkleTreeData`].
+    ///
+    /// The leaf encoding is taken from the first leaf: the columns of a tuple leaf, or the type of a
+    /// single value. Only trees built with the default options can be loaded back. Tuple leaves,
+    /// such as those of [`Self::of_multi`], and single values other than strings are hashed as
+    /// OpenZeppelin does, and load with [`Self::load`] or OpenZeppelin's library. String leaves are
+    /// hashed unencoded, so their dumps only load with [`Self::load`].
+    pub fn dump(&self) -> StandardMerkleTreeData {
+        StandardMerkleTreeData {
+            format: STANDARD_V1_FORMAT.to_string(),
+            tree: self.tree_hex(),
+            values: self.dump_values().collect(),
+            leaf_encoding: self.dump_leaf_encoding(),
+        }
+    }
+
+    /// Writes [`Self::dump`] to `writer` as JSON, exactly as `serde_json::to_string` would
+    /// serialize it, without building the dump in memory.
+    ///
+    /// Nodes and values are written one at a time, so only one value is held at once however large
+    /// the tree is. Many small writes are made: pass a buffered writer, e.g. a
+    /// [`BufWriter`](std::io::BufWriter). Returns [`MerkleTreeError::DumpWrite`] if writing fails.
+    #[cfg(all(feature = "serde", feature = "std"))]
+    pub fn dump_to<W: std::io::Write>(&self, mut writer: W) -> Result<(), MerkleTreeError> {
+        self.write_dump(&mut writer)
+            .map_err(|_| MerkleTreeError::DumpWrite)
+    }
+
+    /// Writes the fields of [`Self::dump`] to `writer`, in the order they are declared.
+    #[cfg(all(feature = "serde", feature = "std"))]
+    fn write_dump(&self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        writer.write_all(b"{\"format\":")?;
+        write_json_string(writer, STANDARD_V1_FORMAT)?;
+        writer.write_all(b",\"tree\":[")?;
+        for (index, node) in self.tree.iter().enumerate() {
+            if index > 0 {
+                writer.write_all(b",")?;
+            }
+            write_json_string(writer, &hex::encode_prefixed(node))?;
+        }
+        writer.write_all(b"],\"values\":[")?;
+        for (index, entry) in self.dump_values().enumerate() {
+            if index > 0 {
+                writer.write_all(b",")?;
+            }
+            writer.write_all(b"{\"value\":")?;
+            write_json_strings(writer, &entry.value)?;
+            write!(writer, ",\"treeIndex\":{}}}", entry.tree_index)?;
+        }
+        writer.write_all(b"],\"leafEncoding\":")?;
+        write_json_strings(writer, &self.dump_leaf_encoding())?;
+        writer.write_all(b"}")
+    }
+
+    /// Returns the leaf encoding of [`Self::dump`], taken from the first leaf.
+    fn dump_leaf_encoding(&self) -> Vec<String> {
+        match self.values.first() {
+            Some(DynSolValue::Tuple(columns)) => columns.iter().map(sol_type_name).collect(),
+            Some(value) => vec![sol_type_name(value)],
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns an iterator over the values of [`Self::dump`], in leaf order.
+    fn dump_values(&self) -> impl Iterator<Item = StandardMerkleTreeValue> + '_ {
+        self.value_leaf_indices()
+            .into_iter()
+            .zip(self.values.iter())
+            .map(|(leaf_index, value)| StandardMerkleTreeValue {
+                value: match value {
+                    DynSolValue::Tuple(columns) => columns.iter().map(dump_value_string).collect(),
+                    value => vec![dump_value_string(value)],
+                },
+                tree_index: self.tree.len() - 1 - leaf_index,
+            })
+    }
+
+    /// Serializes [`Self::dump`] as JSON that is byte-for-byte the same for the same tree, on any
+    /// platform, so that the JSON itself can be hashed as a stable identifier of the tree.
+    ///
+    /// Object keys are sorted, nothing is indented, and all hex, addresses included, is lowercase.
+    /// Values are listed in leaf order.
+    pub fn to_canonical_json(&self) -> String {
+        let data = self.dump();
+        let mut json = String::from("{\"format\":");
+        push_json_string(&mut json, &data.format);
+        json.push_str(",\"leafEncoding\":");
+        push_json_strings(&mut json, &data.leaf_encoding);
+        json.push_str(",\"tree\":");
+        push_json_strings(&mut json, &data.tree);
+        json.push_str(",\"values\":[");
+        for (leaf_index, (entry, value)) in data.values.iter().zip(self.values.iter()).enumerate() {
+            if leaf_index > 0 {
+                json.push(',');
+            }
+            let columns = match value {
+                DynSolValue::Tuple(columns) => columns.iter().map(canonical_value_string).collect(),
+                value => vec![canonical_value_string(value)],
+            };
+            json.push_str("{\"treeIndex\":");
+            json.push_str(&entry.tree_index.to_string());
+            json.push_str(",\"value\":");
+            push_json_strings(&mut json, &columns);
+            json.push('}');
+        }
+        json.push_str("]}");
+        json
+    }
+
+    /// Retrieves the root hash of the Merkle tree, or `None` if the tree has no leaves.
+    ///
+    /// As in OpenZeppelin, the root of a single-leaf tree is the leaf hash itself.
+    pub fn root(&self) -> Option<B256> {
+        self.tree.first().copied()
+    }
+
+    /// Returns the root as `0x`-prefixed lowercase hex, to publish it to a contract or a frontend.
+    ///
+    /// A tree without leaves has the zero root, what an unset `bytes32` holds.
+    pub fn root_hex(&self) -> String {
+        hex::encode_prefixed(self.root().unwrap_or_default())
+    }
+
+    /// Checks whether `expected`, e.g. a root published elsewhere, is the root of the tree. A tree
+    /// without leaves has no root.
+    pub fn has_root(&self, expected: B256) -> bool {
+        self.root() == Some(expected)
+    }
+
+    /// Checks that `expected` is the root of the tree, like [`Self::has_root`].
+    ///
+    /// Returns [`MerkleTreeError::InvalidCheck`] if it isn't.
+    pub fn assert_root(&self, expected: B256) -> Result<(), MerkleTreeError> {
+        if !self.has_root(expected) {
+            return Err(MerkleTreeError::InvalidCheck);
+        }
+        Ok(())
+    }
+
+    /// Freezes the tree into a [`FrozenTree`], cheaply cloneable and shareable across threads.
+    ///
+    /// The storage is shrunk to fit first, since a frozen tree never grows.
+    pub fn freeze(mut self) -> FrozenTree<H> {
+        self.shrink_to_fit();
+        FrozenTree(Arc::new(self))
+    }
+
+    /// Shrinks the capacity of the tree's internal storage as much as possible.
+    ///
+    /// Worth calling on long-lived trees, whose vectors may have been over-reserved while building.
+    pub fn shrink_to_fit(&mut self) {
+        self.tree.shrink_to_fit();
+        self.values.shrink_to_fit();
+        self.tree_values.shrink_to_fit();
+        self.keys.shrink_to_fit();
+        self.tree_values
+            .values_mut()
+            .for_each(|tree_indices| tree_indices.shrink_to_fit());
+    }
+
+    /// Returns the depth of the tree: the number of levels below the root, i.e. the length of the
+    /// longest proof. A tree with a single leaf has depth 0.
+    pub fn depth(&self) -> usize {
+        self.tree.len().checked_sub(1).map_or(0, node_depth)
+    }
+
+    /// Counts the leaves with each proof length, as `(proof_length, leaf_count)` pairs sorted by
+    /// proof length, to see how many leaves pay for a longer proof on-chain.
+    ///
+    /// In OpenZeppelin's layout the proofs of a tree differ by at most one element, and all have
+    /// the same length when the number of leaves is a power of two.
+    pub fn proof_length_histogram(&self) -> Vec<(usize, usize)> {
+        let mut histogram: Vec<(usize, usize)> = Vec::new();
+        for leaf_index in 0..self.leaf_count {
+            let proof_length = self
+                .leaf_index_to_tree_index(leaf_index)
+                .map_or(0, node_depth);
+            match histogram
+                .iter_mut()
+                .find(|(length, _)| *length == proof_length)
+            {
+                Some((_, count)) => *count += 1,
+                None => histogram.push((proof_length, 1)),
+            }
+        }
+        histogram.sort_unstable();
+        histogram
+    }
+
+    /// Checks that every internal node of the tree is the hash of its two children.
+    ///
+    /// Use this on a tree from an untrusted source, e.g. loaded or assembled with [`Self::new`],
+    /// whose nodes could otherwise yield valid-looking proofs against a forged root. Nodes are hashed
+    /// with the [`HashOrder`] of the tree. In padded trees (see [`OddNodePolicy`]) a node over two
+    /// zeroed slots is a filler or unused, and is checked by its parent only. Returns
+    /// [`MerkleTreeError::InvalidCheck`] on the first mismatch.
+    pub fn verify_integrity(&self) -> Result<(), MerkleTreeError> {
+        let padded = self.options.odd_node_policy != OddNodePolicy::Promote;
+        for index in (0..self.tree.len()).take_while(|i| right_child_index(*i) < self.tree.len()) {
+            let node = self.tree[index];
+            let (left, right) = (
+                self.tree[right_child_index(index)],
+                self.tree[left_child_index(index)],
+            );
+            if padded && left.is_zero() && right.is_zero() {
+                continue;
+            }
+            if self.options.hash_order.hash_nodes::<H>(left, right) != node {
+                return Err(MerkleTreeError::InvalidCheck);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Computes a hash of every node of the tree, in array order, for use as e.g. a cache key.
+    ///
+    /// Unlike [`Self::root`], this commits to the whole layout: trees with the same nodes share a
+    /// content id, and any difference in any node changes it.
+    pub fn content_id(&self) -> B256 {
+        let mut hasher = Keccak256::new();
+        self.tree.iter().for_each(|node| hasher.update(node));
+        hasher.finalize()
+    }
+
+    /// Checks that every stored value hashes, with the options of the tree, to the leaf node at its
+    /// position, e.g. to catch values and nodes that drifted apart.
+    ///
+    /// Returns [`MerkleTreeError::InconsistentLeaf`] with the leaf index of the first mismatch, or
+    /// [`MerkleTreeError::InvalidCheck`] if the values and the lookup map don't have as many leaves.
+    pub fn verify_leaf_consistency(&self) -> Result<(), MerkleTreeError> {
+        let mut tree_indices = self
+            .tree_values
+            .values()
+            .flatten()
+            .copied()
+            .collect::<Vec<usize>>();
+        if tree_indices.len() != self.values.len() {
+            return Err(MerkleTreeError::InvalidCheck);
+        }
+        // The values are in leaf order, i.e. in descending tree index order.
+        tree_indices.sort_unstable_by(|a, b| b.cmp(a));
+
+        for (value, tree_index) in self.values.iter().zip(tree_indices) {
+            if self.leaf_hash(value).ok() != Some(self.tree[tree_index]) {
+                let leaf_index = self.tree.len() - 1 - tree_index;
+                return Err(MerkleTreeError::InconsistentLeaf(leaf_index));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Computes a commitment to the set of leaf values, independent of how the tree was built.
+    ///
+    /// This is the hash of the sorted standard leaf hashes of the values (see
+    /// [`standard_leaf_hash`]), so trees over the same leaves share a fingerprint whatever their
+    /// order or [`TreeOptions`], while their roots differ. Duplicate leaves count once per
+    /// occurrence.
+    pub fn leaf_set_fingerprint(&self) -> B256 {
+        let mut leaf_hashes = self
+            .values
+            .iter()
+            .map(|value| standard_leaf_hash(value).expect("the leaves of a tree are supported"))
+            .collect::<Vec<B256>>();
+        leaf_hashes.sort_unstable();
+
+        let mut hasher = Keccak256::new();
+        leaf_hashes
+            .iter()
+            .for_each(|leaf_hash| hasher.update(leaf_hash));
+        hasher.finalize()
+    }
+
+    /// Computes a commitment binding the root of the tree to arbitrary `metadata`.
+    ///
+    /// The bound root is `keccak256(root ++ metadata)`, so a root published for one context (e.g. an
+    /// airdrop round id and total amount) can't be reused for another. See [`verify_bound`]. A
+    /// tree without leaves has no root to bind.
+    pub fn bound_root(&self, metadata: &[u8]) -> Option<B256> {
+        self.root().map(|root| bind_root(root, metadata))
+    }
+
+    /// Generates a Merkle proof for a given leaf value.
+    ///
+    /// If the value occurs more than once, the proof is for its first occurrence. Use
+    /// [`Self::leaf_positions`] to find the others.
+    pub fn get_proof(&self, value: &DynSolValue) -> Result<Vec<B256>, MerkleTreeError> {
+        let tree_index = self.tree_indices(value)?[0];
+
+        make_proof(&self.tree, tree_index)
+    }
+
+    /// Generates a Merkle proof for a given leaf value as `0x`-prefixed lowercase hex, ready to be
+    /// passed as a `bytes32[]`. See [`Self::get_proof`].
+    pub fn get_proof_hex(&self, value: &DynSolValue) -> Result<Vec<String>, MerkleTreeError> {
+        Ok(self
+            .get_proof(value)?
+            .iter()
+            .map(hex::encode_prefixed)
+            .collect())
+    }
+
+    /// Generates a Merkle proof for a given leaf value, tagging every sibling with its level.
+    ///
+    /// The level of a node is its distance from the root, so the root is at level 0 and the siblings
+    /// are returned from the leaf's level (at most [`Self::depth`]) up to level 1. As with
+    /// [`Self::get_proof`], the proof is for the first occurrence of the value.
+    pub fn get_proof_tagged(
+        &self,
+        value: &DynSolValue,
+    ) -> Result<Vec<(usize, B256)>, MerkleTreeError> {
+        let mut tree_index = self.tree_indices(value)?[0];
+        let proof = make_proof(&self.tree, tree_index)?;
+
+        Ok(proof
+            .into_iter()
+            .map(|sibling| {
+                let level = node_depth(tree_index);
+                tree_index = parent_index(tree_index);
+                (level, sibling)
+            })
+            .collect())
+    }
+
+    /// Generates a Merkle proof for the leaf of a tree built with [`Self::of_with_nonce`] holding
+    /// `value` with `nonce`.
+    pub fn get_proof_with_nonce(
+        &self,
+        value: &DynSolValue,
+        nonce: u64,
+    ) -> Result<Vec<B256>, MerkleTreeError> {
+        self.get_proof(&nonce_leaf(value, nonce)?)
+    }
+
+    /// Generates a Merkle proof for a given leaf value and ABI-encodes it together with the root.
+    ///
+    /// The blob is `abi.encode(bytes32 root, bytes32[] proof)`, so a contract can unpack it with
+    /// `abi.decode(payload, (bytes32, bytes32[]))`.
+    pub fn get_proof_abi_blob(&self, value: &DynSolValue) -> Result<Vec<u8>, MerkleTreeError> {
+        let proof = self
+            .get_proof(value)?
+            .into_iter()
+            .map(|sibling| DynSolValue::FixedBytes(sibling, 32))
+            .collect();
+
+        // A tree with a proof has a leaf, so it has a root.
+        Ok(DynSolValue::Tuple(vec![
+            DynSolValue::FixedBytes(self.tree[0], 32),
+            DynSolValue::Array(proof),
+        ])
+        .abi_encode_params())
+    }
+
+    /// Generates a Merkle proof for the leaf of a tree built with [`Self::of_packed_bytes32`] packing
+    /// `parts`.
+    pub fn get_proof_packed_bytes32(&self, parts: &[B256]) -> Result<Vec<B256>, MerkleTreeError> {
+        self.get_proof(&packed_bytes32_leaf(parts))
+    }
+
+    /// Generates a Merkle proof for a given leaf value as a hex-encoded [`ProofResponse`].
+    ///
+    /// As with [`Self::get_proof`], the proof is for the first occurrence of the value.
+    pub fn proof_response(&self, value: &DynSolValue) -> Result<ProofResponse, MerkleTreeError> {
+        let tree_index = self.tree_indices(value)?[0];
+        let proof = make_proof(&self.tree, tree_index)?;
+
+        Ok(ProofResponse {
+            leaf: hex::encode_prefixed(self.tree[tree_index]),
+            leaf_index: self.tree_index_to_leaf_index(tree_index)? as u64,
+            proof: proof.iter().map(hex::encode_prefixed).collect(),
+            root: hex::encode_prefixed(self.tree[0]),
+        })
+    }
+
+    /// Generates a Merkle proof for a given leaf value as a [`ViemProof`], for frontends.
+    ///
+    /// As with [`Self::get_proof`], the proof is for the first occurrence of the value.
+    pub fn viem_proof(&self, value: &DynSolValue) -> Result<ViemProof, MerkleTreeError> {
+        let proof = self.get_proof(value)?;
+
+        Ok(ViemProof {
+            proof: proof.iter().map(hex::encode_prefixed).collect(),
+            root: hex::encode_prefixed(self.tree[0]),
+            value: match value {
+                DynSolValue::Tuple(columns) => columns.iter().map(dump_value_string).collect(),
+                value => vec![dump_value_string(value)],
+            },
+        })
+    }
+
+    /// Returns an iterator over every `(leaf_index, leaf_hash)` of the tree, in leaf order.
+    ///
+    /// Unlike [`Self::leaf_hashes`] nothing is allocated, the hashes are read from the tree as the
+    /// iterator advances.
+    pub fn leaves(&self) -> impl ExactSizeIterator<Item = (usize, B256)> + '_ {
+        (0..self.leaf_count)
+            .map(|leaf_index| (leaf_index, self.tree[self.tree.len() - 1 - leaf_index]))
+    }
+
+    /// Returns an iterator over every `(leaf_index, value, leaf_hash)` of the tree, in leaf order,
+    /// like OpenZeppelin's `tree.entries()` with the leaf hash alongside.
+    ///
+    /// Leaves without a value, the absent leaves of [`Self::of_by_index`], are skipped.
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = (usize, &DynSolValue, B256)> + '_ {
+        self.value_leaf_indices()
+            .into_iter()
+            .zip(self.values.iter())
+            .map(|(leaf_index, value)| {
+                (
+                    leaf_index,
+                    value,
+                    self.tree[self.tree.len() - 1 - leaf_index],
+                )
+            })
+    }
+
+    /// Returns the leaf index of every stored value, in leaf order.
+    ///
+    /// Every leaf has a value, except the absent leaves of [`Self::of_by_index`], whose positions are
+    /// then read from the lookup map.
+    fn value_leaf_indices(&self) -> Vec<usize> {
+        if self.values.len() == self.leaf_count {
+            return (0..self.leaf_count).collect();
+        }
+        let mut leaf_indices = self
+            .tree_values
+            .values()
+            .flatten()
+            .map(|tree_index| self.tree.len() - 1 - tree_index)
+            .collect::<Vec<usize>>();
+        leaf_indices.sort_unstable();
+        leaf_indices
+    }
+
+    /// Finds the position in [`Self::values`] of the value of the leaf at `leaf_index`, or where it
+    /// would be inserted if the leaf has no value.
+    fn value_position(&self, leaf_index: usize) -> Result<usize, usize> {
+        if self.values.len() == self.leaf_count {
+            return Ok(leaf_index);
+        }
+        self.value_leaf_indices().binary_search(&leaf_index)
+    }
+
+    /// Returns the leaf hashes, in leaf order.
+    pub fn leaf_hashes(&self) -> Vec<B256> {
+        self.leaves().map(|(_, leaf_hash)| leaf_hash).collect()
+    }
+
+    /// Returns the original leaf values, in leaf order.
+    pub fn values(&self) -> &[DynSolValue] {
+        &self.values
+    }
+
+    /// Returns the original leaf values as strings, in leaf order.
+    ///
+    /// Returns [`MerkleTreeError::NotSupportedType`] if any leaf isn't a string.
+    pub fn string_leaves(&self) -> Result<Vec<String>, MerkleTreeError> {
+        self.values
+            .iter()
+            .map(|value| match value {
+                DynSolValue::String(inner_value) => Ok(inner_value.clone()),
+                _ => Err(MerkleTreeError::NotSupportedType),
+            })
+            .collect()
+    }
+
+    /// Returns the original leaf values as `bytes32`, in leaf order.
+    ///
+    /// Returns [`MerkleTreeError::NotSupportedType`] if any leaf isn't a `bytes32`.
+    pub fn bytes32_leaves(&self) -> Result<Vec<B256>, MerkleTreeError> {
+        self.values
+            .iter()
+            .map(|value| match value {
+                DynSolValue::FixedBytes(inner_value, 32) => Ok(*inner_value),
+                _ => Err(MerkleTreeError::NotSupportedType),
+            })
+            .collect()
+    }
+
+    /// Generates a Merkle proof for a given leaf value, with the position of every sibling.
+    ///
+    /// `positions[i]` is `true` if `siblings[i]` is the left node of its pair, the `proofFlags`-like
+    /// input of positional Solidity verifiers. The positions follow the leaf order of the tree; they
+    /// only matter for trees built with [`HashOrder::Positional`], as sorted pairs ignore them. As
+    /// with [`Self::get_proof`], the proof is for the first occurrence of the value.
+    pub fn get_proof_with_positions(
+        &self,
+        value: &DynSolValue,
+    ) -> Result<(Vec<B256>, Vec<bool>), MerkleTreeError> {
+        let mut tree_index = self.tree_indices(value)?[0];
+        let siblings = make_proof(&self.tree, tree_index)?;
+
+        let mut positions = Vec::with_capacity(siblings.len());
+        while let Ok(sibling) = sibling_index(tree_index) {
+            // Every level is stored in reverse, so the node at an even index is the left one.
+            positions.push(sibling % 2 == 0);
+            tree_index = parent_index(tree_index);
+        }
+        Ok((siblings, positions))
+    }
+
+    /// Generates a Merkle proof for a given leaf value like [`Self::get_proof_with_positions`], with
+    /// each sibling paired with whether it is the left node of its pair.
+    ///
+    /// A verifier folds the proof as `hash(sibling, node)` for a left sibling and
+    /// `hash(node, sibling)` otherwise.
+    pub fn get_proof_with_sides(
+        &self,
+        value: &DynSolValue,
+    ) -> Result<Vec<(B256, bool)>, MerkleTreeError> {
+        let (siblings, positions) = self.get_proof_with_positions(value)?;
+
+        Ok(siblings.into_iter().zip(positions).collect())
+    }
+
+    /// Generates a [`MerkleWitness`] for `value`: its proof together with every node computed along
+    /// the path to the root.
+    ///
+    /// As with [`Self::get_proof`], a value that occurs more than once in the tree is proven at its
+    /// first occurrence.
+    pub fn witness(&self, value: &DynSolValue) -> Result<MerkleWitness, MerkleTreeError> {
+        let mut tree_index = self.tree_indices(value)?[0];
+        let leaf_hash = self.tree[tree_index];
+        let siblings = make_proof(&self.tree, tree_index)?;
+
+        let mut node_at_level = Vec::with_capacity(siblings.len());
+        let mut path_bits = Vec::with_capacity(siblings.len());
+        while let Ok(sibling) = sibling_index(tree_index) {
+            path_bits.push(sibling % 2 == 0);
+            tree_index = parent_index(tree_index);
+            node_at_level.push(self.tree[tree_index]);
+        }
+        Ok(MerkleWitness {
+            leaf_hash,
+            siblings,
+            node_at_level,
+            path_bits,
+            root: self.tree[0],
+        })
+    }
+
+    /// Generates a Merkle proof for the leaf at `leaf_index`, in the order the leaves were given,
+    /// without looking up its value.
+    ///
+    /// Returns [`MerkleTreeError::LeafNotFound`] if `leaf_index` is out of bounds.
+    pub fn get_proof_by_index(&self, leaf_index: usize) -> Result<Vec<B256>, MerkleTreeError> {
+        let tree_index = self.leaf_index_to_tree_index(leaf_index)?;
+
+        make_proof(&self.tree, tree_index)
+    }
+
+    /// Generates a Merkle proof for the leaf whose leaf node is `leaf_hash`, for callers that only
+    /// have the commitment and not the value.
+    ///
+    /// The leaves are scanned in leaf order, so this takes linear time, and the proof is for the
+    /// first matching leaf. Returns [`MerkleTreeError::LeafNotFound`] if no leaf matches.
+    pub fn get_proof_by_hash(&self, leaf_hash: B256) -> Result<Vec<B256>, MerkleTreeError> {
+        let (leaf_index, _) = self
+            .leaves()
+            .find(|(_, hash)| *hash == leaf_hash)
+            .ok_or(MerkleTreeError::LeafNotFound)?;
+
+        self.get_proof_by_index(leaf_index)
+    }
+
+    /// Generates a Merkle proof for the leaf looked up by a key derived with
+    /// [`StandardMerkleTreeBuilder::key_fn`].
+    ///
+    /// If several leaves share the key, the proof is for the first of them.
+    pub fn get_proof_by_key(&self, key: &str) -> Result<Vec<B256>, MerkleTreeError> {
+        let tree_index = self
+            .keys
+            .get(key)
+            .map(|tree_indices| tree_indices[0])
+            .ok_or(MerkleTreeError::LeafNotFound)?;
+
+        make_proof(&self.tree, tree_index)
+    }
+
+    /// Generates a [`MultiProof`] proving all of `values` at once, exactly as OpenZeppelin's
+    /// `getMultiProof` does.
+    ///
+    /// The leaves are ordered from the deepest tree index up, which is the order the fold consumes
+    /// them in. Returns [`MerkleTreeError::DuplicatedIndex`] if a value is given more than once. As
+    /// with [`Self::get_proof`], a value that occurs more than once in the tree is proven at its first
+    /// occurrence.
+    ///
+    /// The fold hashes sorted pairs, so trees built with [`HashOrder::Positional`] yield
+    /// [`MerkleTreeError::InvalidMultiProof`]. Padded trees (see [`OddNodePolicy`]) are supported.
+    pub fn get_multi_proof(&self, values: &[DynSolValue]) -> Result<MultiProof, MerkleTreeError> {
+        if self.options.hash_order == HashOrder::Positional {
+            return Err(MerkleTreeError::InvalidMultiProof);
+        }
+        let mut indices = values
+            .iter()
+            .map(|value| self.tree_indices(value).map(|tree_indices| tree_indices[0]))
+            .collect::<Result<Vec<usize>, MerkleTreeError>>()?;
+        indices.sort_unstable_by(|a, b| b.cmp(a));
+        if indices.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(MerkleTreeError::DuplicatedIndex);
+        }
+
+        let mut stack: VecDeque<usize> = indices.iter().copied().collect();
+        let mut proof = Vec::new();
+        let mut proof_flags = Vec::new();
+        while let Some(&index) = stack.front() {
+            if index == 0 {
+                break;
+            }
+            stack.pop_front();
+            let sibling = sibling_index(index)?;
+            if stack.front() == Some(&sibling) {
+                proof_flags.push(true);
+                stack.pop_front();
+            } else {
+                proof_flags.push(false);
+                proof.push(self.tree[sibling]);
+            }
+            stack.push_back(parent_index(index));
+        }
+        if indices.is_empty() {
+            proof.extend(self.root());
+        }
+
+        Ok(MultiProof {
+            leaves: indices.iter().map(|index| self.tree[*index]).collect(),
+            proof,
+            proof_flags,
+        })
+    }
+
+    /// Lists the nodes that appear in the individual proofs of more than one of `values`, as
+    /// `(tree index, hash)` pairs in tree index order.
+    ///
+    /// These are the nodes a [`MultiProof`] of the same values sends once instead of once per leaf.
+    /// Returns [`MerkleTreeError::DuplicatedIndex`] if a value is given more than once.
+    pub fn shared_proof_nodes(
+        &self,
+        values: &[DynSolValue],
+    ) -> Result<Vec<(usize, B256)>, MerkleTreeError> {
+        let mut leaf_indices = values
+            .iter()
+            .map(|value| self.tree_indices(value).map(|tree_indices| tree_indices[0]))
+            .collect::<Result<Vec<usize>, MerkleTreeError>>()?;
+        leaf_indices.sort_unstable();
+        if leaf_indices.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(MerkleTreeError::DuplicatedIndex);
+        }
+
+        let mut proof_indices = Vec::new();
+        for mut tree_index in leaf_indices {
+            while let Ok(sibling) = sibling_index(tree_index) {
+                proof_indices.push(sibling);
+                tree_index = parent_index(tree_index);
+            }
+        }
+        proof_indices.sort_unstable();
+
+        let mut shared: Vec<(usize, B256)> = Vec::new();
+        for pair in proof_indices.windows(2) {
+            if pair[0] == pair[1] && shared.last().map(|(index, _)| *index) != Some(pair[0]) {
+                shared.push((pair[0], self.tree[pair[0]]));
+            }
+        }
+        Ok(shared)
+    }
+
+    /// Verifies a [`MultiProof`] against the root of the tree.
+    ///
+    /// As with [`Self::get_multi_proof`], this is always `false` for trees built with
+    /// [`HashOrder::Positional`].
+    pub fn verify_multi_proof(&self, multi_proof: &MultiProof) -> bool {
+        self.options.hash_order == HashOrder::Sorted
+            && process_multi_proof_with::<H>(multi_proof).map_or(false, |root| self.has_root(root))
+    }
+
+    /// Checks that `multiproof` and the `individual` proofs all verify and prove the same leaves,
+    /// e.g. while migrating from individual proofs to multiproofs.
+    ///
+    /// The leaves are compared as multisets of leaf hashes, in any order. Returns `Ok(false)` if any
+    /// proof doesn't verify or the leaves differ, and [`MerkleTreeError::NotSupportedType`] if an
+    /// individual leaf isn't of a supported type.
+    pub fn multiproof_matches_individual(
+        &self,
+        multiproof: &MultiProof,
+        individual: &[(DynSolValue, Vec<B256>)],
+    ) -> Result<bool, MerkleTreeError> {
+        let individual_leaves = individual
+            .iter()
+            .map(|(value, proof)| {
+                let leaf_hash = self.leaf_hash(value)?;
+                Ok(self
+                    .verify_leaf_hash(value, leaf_hash, proof)
+                    .then_some(leaf_hash))
+            })
+            .collect::<Result<Option<Vec<B256>>, MerkleTreeError>>()?;
+        let Some(individual_leaves) = individual_leaves else {
+            return Ok(false);
+        };
+
+        Ok(self
+            .verify_multiproof_covers(multiproof, &individual_leaves)
+            .unwrap_or(false))
+    }
+
+    /// Verifies `multiproof` against the root of the tree and checks that it proves exactly the
+    /// leaves `expected_leaf_hashes`, so that a valid multiproof of other leaves isn't accepted.
+    ///
+    /// The leaves are compared as multisets of leaf hashes, in any order. Returns `Ok(false)` if the
+    /// multiproof doesn't verify or proves other leaves, and [`MerkleTreeError::InvalidMultiProof`]
+    /// if its leaves, proof and flags don't fit together, or the tree was built with
+    /// [`HashOrder::Positional`].
+    pub fn verify_multiproof_covers(
+        &self,
+        multiproof: &MultiProof,
+        expected_leaf_hashes: &[B256],
+    ) -> Result<bool, MerkleTreeError> {
+        if self.options.hash_order == HashOrder::Positional {
+            return Err(MerkleTreeError::InvalidMultiProof);
+        }
+        if !self.has_root(process_multi_proof_with::<H>(multiproof)?) {
+            return Ok(false);
+        }
+
+        let mut covered = multiproof_leaves(multiproof).to_vec();
+        let mut expected = expected_leaf_hashes.to_vec();
+        covered.sort_unstable();
+        expected.sort_unstable();
+        Ok(covered == expected)
+    }
+
+    /// Returns the leaf index of `value` in the tree, e.g. to pass to [`Self::get_proof_by_index`].
+    ///
+    /// As with [`Self::get_proof`], this is the first occurrence of the value; see
+    /// [`Self::leaf_positions`] for the others. Returns [`MerkleTreeError::LeafNotFound`] if the
+    /// value isn't a leaf of the tree.
+    pub fn index_of(&self, value: &DynSolValue) -> Result<usize, MerkleTreeError> {
+        self.tree_index_to_leaf_index(self.tree_indices(value)?[0])
+    }
+
+    /// Returns the leaf index of every occurrence of `value` in the tree, in ascending order.
+    pub fn leaf_positions(&self, value: &DynSolValue) -> Result<Vec<usize>, MerkleTreeError> {
+        self.tree_indices(value)?
+            .iter()
+            .map(|tree_index| self.tree_index_to_leaf_index(*tree_index))
+            .collect()
+    }
+
+    /// Returns every `(leaf_hash, leaf_index)` pair of the tree, sorted by leaf hash.
+    ///
+    /// Leaves with equal hashes are ordered by leaf index. The table supports binary-search membership
+    /// checks, e.g. with [`slice::binary_search_by_key`].
+    pub fn sorted_leaf_table(&self) -> Vec<(B256, usize)> {
+        let mut table = (0..self.leaf_count())
+            .map(|leaf_index| (self.tree[self.tree.len() - 1 - leaf_index], leaf_index))
+            .collect::<Vec<_>>();
+        table.sort_unstable();
+        table
+    }
+
+    /// Checks whether the leaf hashes are in non-decreasing order, in leaf order.
+    ///
+    /// Trees with zero or one leaf trivially satisfy this.
+    pub fn leaves_are_sorted(&self) -> bool {
+        let leaves = &self.tree[self.tree.len() - self.leaf_count()..];
+        // Leaves are stored in reverse, so sorted leaves are non-increasing in the array.
+        leaves.windows(2).all(|pair| pair[0] >= pair[1])
+    }
+
+    /// Checks whether every leaf of the tree has the same hash.
+    ///
+    /// Trees with zero or one leaf trivially satisfy this.
+    pub fn all_leaves_equal(&self) -> bool {
+        let leaves = &self.tree[self.tree.len() - self.leaf_count()..];
+        leaves.windows(2).all(|pair| pair[0] == pair[1])
+    }
+
+    /// Looks up the tree indices of every occurrence of a leaf value.
+    fn tree_indices(&self, value: &DynSolValue) -> Result<&[usize], MerkleTreeError> {
+        let tree_key = Self::check_valid_value_type(value)?;
+
+        self.tree_values
+            .get(&tree_key)
+            .map(Vec::as_slice)
+            .ok_or(MerkleTreeError::LeafNotFound)
+    }
+
+    /// Returns an iterator yielding `(leaf_index, proof)` for every leaf, computing each proof lazily.
+    ///
+    /// Only one proof is held in memory at a time, which suits streaming the proofs of a large tree.
+    pub fn iter_proofs(&self) -> ProofIter<'_, H> {
+        ProofIter {
+            tree: self,
+            leaf_index: 0,
+        }
+    }
+
+    /// Computes the leaf hash of a value as the tree hashes its leaves, with its options and `H`,
+    /// whether or not the value is a leaf of the tree.
+    ///
+    /// For trees with the default options this is [`standard_leaf_hash`]. Returns
+    /// [`MerkleTreeError::NotSupportedType`] if the value isn't of a supported leaf type.
+    pub fn leaf_hash(&self, leaf: &DynSolValue) -> Result<B256, MerkleTreeError> {
+        leaf_hash_with_options::<H>(leaf, &self.options)
+    }
+
+    /// Verifies a Merkle proof for a given leaf value.
+    ///
+    /// With [`HashOrder::Positional`] the proof is folded along the path of each occurrence of the
+    /// leaf in the tree, since the directions can't be recovered from the proof alone. A leaf of an
+    /// unsupported type is never verified.
+    ///
+    /// Proofs failing [`Self::precheck_proof`] are rejected before any hashing: a proof can't be
+    /// longer than [`Self::depth`], and only the leaf of a single-leaf tree, which is its root, has
+    /// an empty proof.
+    pub fn verify_proof(&self, leaf: &DynSolValue, proof: Vec<B256>) -> bool {
+        self.leaf_hash(leaf).map_or(false, |leaf_hash| {
+            self.verify_leaf_hash(leaf, leaf_hash, &proof)
+        })
+    }
+
+    /// Finds which [`LeafHashMode`] makes `proof` verify `leaf` against the root of the tree, for
+    /// working out the convention of a tree whose leaves may or may not have been hashed.
+    ///
+    /// The modes are tried in turn, with every other option of the tree unchanged, and the first
+    /// that verifies is returned, or `None` if none does. Returns
+    /// [`MerkleTreeError::NotSupportedType`] if the leaf isn't of a supported type.
+    pub fn verify_any_mode(
+        &self,
+        leaf: &DynSolValue,
+        proof: &[B256],
+    ) -> Result<Option<LeafHashMode>, MerkleTreeError> {
+        Self::check_valid_value_type(leaf)?;
+        Ok(LEAF_HASH_MODES.into_iter().find(|mode| {
+            let options = TreeOptions {
+                leaf_hash_mode: *mode,
+                ..self.options
+            };
+            // Not every value can be hashed in every mode, e.g. a string is never a raw leaf.
+            leaf_hash_with_options::<H>(leaf, &options).map_or(false, |leaf_hash| {
+                self.verify_leaf_hash(leaf, leaf_hash, proof)
+            })
+        }))
+    }
+
+    /// Verifies a Merkle proof for a given leaf value, reporting the nearest leaf of the tree if it
+    /// doesn't verify.
+    ///
+    /// The nearest leaf is the one whose hash is numerically closest to the leaf hash of `value`.
+    /// Hashed leaves are unrelated to how alike their values are, so this is most useful with
+    /// [`LeafHashMode::Raw`] leaves, which keep the order of their values. Returns
+    /// [`MerkleTreeError::LeafNotFound`] if the tree has no leaves, and
+    /// [`MerkleTreeError::NotSupportedType`] if the value isn't of a supported leaf type.
+    pub fn verify_or_nearest(
+        &self,
+        value: &DynSolValue,
+        proof: &[B256],
+    ) -> Result<VerifyOutcome, MerkleTreeError> {
+        let leaf_hash = self.leaf_hash(value)?;
+        if self.verify_leaf_hash(value, leaf_hash, proof) {
+            return Ok(VerifyOutcome::Verified);
+        }
+        let target = U256::from_be_bytes(leaf_hash.0);
+        let distance = |hash: &B256| {
+            let hash = U256::from_be_bytes(hash.0);
+            hash.max(target) - hash.min(target)
+        };
+
+        self.leaf_hashes()
+            .into_iter()
+            .min_by_key(distance)
+            .map(|nearest_leaf_hash| VerifyOutcome::NotVerified { nearest_leaf_hash })
+            .ok_or(MerkleTreeError::LeafNotFound)
+    }
+
+    /// Verifies two proofs against the root of the tree and checks that they are consistent with
+    /// each other, to catch corrupt or forged proof sets.
+    ///
+    /// Where the two paths overlap, from the root down, their nodes and siblings must be the same,
+    /// and where they split the node of each path must be the sibling of the other. Returns
+    /// `Ok(false)` if either proof doesn't verify or the paths contradict each other, and
+    /// [`MerkleTreeError::NotSupportedType`] if either leaf is of an unsupported type.
+    pub fn proofs_consistent(
+        &self,
+        leaf_a: &DynSolValue,
+        proof_a: &[B256],
+        leaf_b: &DynSolValue,
+        proof_b: &[B256],
+    ) -> Result<bool, MerkleTreeError> {
+        let path_a = self.implied_path(leaf_a, proof_a)?;
+        let path_b = self.implied_path(leaf_b, proof_b)?;
+
+        Ok(match (path_a, path_b) {
+            (Some(path_a), Some(path_b)) => paths_agree(&path_a, proof_a, &path_b, proof_b),
+            _ => false,
+        })
+    }
+
+    /// Computes the nodes a proof for `leaf` implies, from the leaf up to the root, or `None` if
+    /// the proof doesn't verify.
+    ///
+    /// With [`HashOrder::Positional`] the proof is folded along the path of each occurrence of the
+    /// leaf, as by [`Self::verify_proof`].
+    fn implied_path(
+        &self,
+        leaf: &DynSolValue,
+        proof: &[B256],
+    ) -> Result<Option<Vec<B256>>, MerkleTreeError> {
+        let leaf_hash = self.leaf_hash(leaf)?;
+        let fold = |tree_index: Option<usize>| {
+            let mut path = Vec::with_capacity(proof.len() + 1);
+            path.push(leaf_hash);
+            let mut current_index = tree_index;
+            for sibling in proof {
+                let node = path[path.len() - 1];
+                path.push(match current_index {
+                    Some(0) => return None,
+                    Some(index) => {
+                        current_index = Some(parent_index(index));
+                        HashOrder::Positional.hash_with_sibling::<H>(index, node, *sibling)
+                    }
+                    None => H::hash_pair(node, *sibling),
+                });
+            }
+            let at_root = current_index.map_or(true, |index| index == 0);
+            (at_root && self.has_root(path[path.len() - 1])).then_some(path)
+        };
+
+        Ok(match self.options.hash_order {
+            HashOrder::Sorted => fold(None),
+            HashOrder::Positional => self.tree_indices(leaf).ok().and_then(|tree_indices| {
+                tree_indices
+                    .iter()
+                    .find_map(|tree_index| fold(Some(*tree_index)))
+            }),
+        })
+    }
+
+    /// Verifies a Merkle proof for a given leaf value against a trusted `root`, e.g. one published
+    /// on-chain, rather than the root of the tree.
+    ///
+    /// The leaf is hashed and the proof folded as in [`Self::verify_proof`], with the options of the
+    /// tree, so this checks that the proof, as this tree would produce it, leads to `root`.
+    pub fn verify_proof_against(&self, root: B256, leaf: &DynSolValue, proof: &[B256]) -> bool {
+        self.leaf_hash(leaf).map_or(false, |leaf_hash| {
+            self.verify_leaf_hash_against(root, leaf, leaf_hash, proof)
+        })
+    }
+
+    /// Verifies a Merkle proof for `leaf`, whose leaf node is `leaf_hash`.
+    fn verify_leaf_hash(&self, leaf: &DynSolValue, leaf_hash: B256, proof: &[B256]) -> bool {
+        self.root().map_or(false, |root| {
+            self.verify_leaf_hash_against(root, leaf, leaf_hash, proof)
+        })
+    }
+
+    /// Verifies a Merkle proof for `leaf`, whose leaf node is `leaf_hash`, against `root`.
+    fn verify_leaf_hash_against(
+        &self,
+        root: B256,
+        leaf: &DynSolValue,
+        leaf_hash: B256,
+        proof: &[B256],
+    ) -> bool {
+        if self.precheck_proof(proof).is_err() {
+            return false;
+        }
+        match self.options.hash_order {
+            HashOrder::Sorted => process_proof_with::<H>(leaf_hash, proof) == root,
+            HashOrder::Positional => self.tree_indices(leaf).map_or(false, |tree_indices| {
+                self.verify_positional_at(root, tree_indices, leaf_hash, proof)
+            }),
+        }
+    }
+
+    /// Verifies a Merkle proof for the leaf whose leaf node is `leaf_hash`, for callers that only
+    /// have the commitment, e.g. against a tree built with [`Self::of_hashes`].
+    ///
+    /// With [`HashOrder::Positional`] the proof is folded along the path of every leaf matching
+    /// `leaf_hash`, found by scanning the leaves. Like [`Self::verify_proof`], proofs failing
+    /// [`Self::precheck_proof`] are rejected.
+    pub fn verify_proof_by_hash(&self, leaf_hash: B256, proof: &[B256]) -> bool {
+        if self.precheck_proof(proof).is_err() {
+            return false;
+        }
+        match self.options.hash_order {
+            HashOrder::Sorted => self.has_root(process_proof_with::<H>(leaf_hash, proof)),
+            HashOrder::Positional => {
+                let tree_indices = self
+                    .leaves()
+                    .filter(|(_, hash)| *hash == leaf_hash)
+                    .map(|(leaf_index, _)| self.tree.len() - 1 - leaf_index)
+                    .collect::<Vec<usize>>();
+                self.root().map_or(false, |root| {
+                    self.verify_positional_at(root, &tree_indices, leaf_hash, proof)
+                })
+            }
+        }
+    }
+
+    /// Folds a positional proof for `leaf_hash` along the path of each of `tree_indices`, checking
+    /// whether any of them reaches `root`.
+    fn verify_positional_at(
+        &self,
+        root: B256,
+        tree_indices: &[usize],
+        leaf_hash: B256,
+        proof: &[B256],
+    ) -> bool {
+        tree_indices.iter().any(|tree_index| {
+            let mut current_index = *tree_index;
+            let mut hash = leaf_hash;
+            for sibling in proof {
+                if current_index == 0 {
+                    return false;
+                }
+                hash = HashOrder::Positional.hash_with_sibling::<H>(current_index, hash, *sibling);
+                current_index = parent_index(current_index);
+            }
+            current_index == 0 && hash == root
+        })
+    }
+
+    /// Returns the paths a proof of `leaf` is folded along, as the tree index the fold starts from,
+    /// or `None` for trees built with [`HashOrder::Sorted`], whose proofs are folded without
+    /// positions.
+    ///
+    /// A positional tree folds the proof along every occurrence of the leaf, as
+    /// [`Self::verify_proof`] does, and along none if the leaf isn't in the tree.
+    fn proof_paths(&self, leaf: &DynSolValue) -> Vec<Option<usize>> {
+        match self.options.hash_order {
+            HashOrder::Sorted => vec![None],
+            HashOrder::Positional => self.tree_indices(leaf).map_or(Vec::new(), |tree_indices| {
+                tree_indices.iter().copied().map(Some).collect()
+            }),
+        }
+    }
+
+    /// Folds `sibling` into `node`, which is at `tree_index` on a path from [`Self::proof_paths`],
+    /// returning their parent and its tree index, or `None` if a positional path is already at the
+    /// root.
+    fn fold_proof_sibling(
+        &self,
+        node: B256,
+        tree_index: Option<usize>,
+        sibling: B256,
+    ) -> Option<(B256, Option<usize>)> {
+        match tree_index {
+            None => Some((H::hash_pair(node, sibling), None)),
+            Some(0) => None,
+            Some(index) => Some((
+                HashOrder::Positional.hash_with_sibling::<H>(index, node, sibling),
+                Some(parent_index(index)),
+            )),
+        }
+    }
+
+    /// Verifies a Merkle proof for `leaf` bound to `nonce`, as built by [`Self::of_with_nonce`].
+    pub fn verify_proof_with_nonce(&self, leaf: &DynSolValue, nonce: u64, proof: &[B256]) -> bool {
+        nonce_leaf(leaf, nonce).map_or(false, |leaf| self.verify_proof(&leaf, proof.to_vec()))
+    }
+
+    /// Verifies a Merkle proof for the leaf packing `parts`, as built by [`Self::of_packed_bytes32`].
+    pub fn verify_proof_packed_bytes32(&self, parts: &[B256], proof: &[B256]) -> bool {
+        self.verify_proof(&packed_bytes32_leaf(parts), proof.to_vec())
+    }
+
+    /// Cheaply rejects proofs that can't be valid for this tree, before any hashing.
+    ///
+    /// Returns [`MerkleTreeError::EmptyProof`] for an empty proof of a tree with more than one leaf,
+    /// and [`MerkleTreeError::ProofTooLong`] for a proof longer than [`Self::depth`]. Proofs carry no
+    /// direction flags, even with [`HashOrder::Positional`], where the directions follow from the
+    /// leaf's position, so there are none to check.
+    pub fn precheck_proof(&self, proof: &[B256]) -> Result<(), MerkleTreeError> {
+        if proof.is_empty() && self.leaf_count() > 1 {
+            return Err(MerkleTreeError::EmptyProof);
+        }
+        if proof.len() > self.depth() {
+            return Err(MerkleTreeError::ProofTooLong);
+        }
+        Ok(())
+    }
+
+    /// Folds a Merkle proof, reporting where it diverges from the tree.
+    ///
+    /// The proof is folded with the [`HashOrder`] of the tree, in positional trees along the first
+    /// occurrence of the leaf. After each proof element the computed node is looked up among all
+    /// nodes of the tree, which takes linear time per level: this is a debugging aid, not a
+    /// verifier. Returns [`MerkleTreeError::LeafNotFound`] if the leaf itself isn't in the tree.
+    pub fn verify_diagnostic(
+        &self,
+        leaf: &DynSolValue,
+        proof: &[B256],
+    ) -> Result<DiagResult, MerkleTreeError> {
+        let mut hash = self.leaf_hash(leaf)?;
+        if !self.tree.contains(&hash) {
+            return Err(MerkleTreeError::LeafNotFound);
+        }
+
+        let mut tree_index = self
+            .proof_paths(leaf)
+            .first()
+            .copied()
+            .ok_or(MerkleTreeError::LeafNotFound)?;
+        let mut last_matched_level = 0;
+        for (level, sibling) in proof.iter().enumerate() {
+            // A positional path past the root can't match anything any more.
+            let Some((parent, parent_index)) = self.fold_proof_sibling(hash, tree_index, *sibling)
+            else {
+                return Ok(DiagResult {
+                    verified: false,
+                    last_matched_level,
+                });
+            };
+            (hash, tree_index) = (parent, parent_index);
+            if self.tree.contains(&hash) {
+                last_matched_level = level + 1;
+            }
+        }
+
+        Ok(DiagResult {
+            verified: tree_index.map_or(true, |index| index == 0) && self.has_root(hash),
+            last_matched_level,
+        })
+    }
+
+    /// Verifies a Merkle proof for a given leaf value, after checking that it is an `expected_type`.
+    ///
+    /// Returns [`MerkleTreeError::SchemaMismatch`] if the leaf isn't an `expected_type`, and
+    /// [`MerkleTreeError::NotSupportedType`] if it is but the tree can't hash such leaves.
+    pub fn verify_with_schema(
+        &self,
+        leaf: &DynSolValue,
+        expected_type: &DynSolType,
+        proof: &[B256],
+    ) -> Result<bool, MerkleTreeError> {
+        if !expected_type.matches(leaf) {
+            return Err(MerkleTreeError::SchemaMismatch);
+        }
+        self.leaf_hash(leaf)?;
+        Ok(self.verify_proof(leaf, proof.to_vec()))
+    }
+
+    /// Verifies a Merkle proof read from `reader` as consecutive 32-byte elements, folding each
+    /// element as it is read instead of buffering the whole proof.
+    ///
+    /// The proof is folded like in [`Self::verify_proof`], with the options of the tree: along every
+    /// occurrence of the leaf in positional trees, and rejected if [`Self::precheck_proof`] would
+    /// reject it. Returns [`MerkleTreeError::TruncatedProof`] if the stream ends in the middle of an
+    /// element, and [`MerkleTreeError::ProofRead`] if reading fails.
+    #[cfg(feature = "std")]
+    pub fn verify_proof_reader<R: std::io::Read>(
+        &self,
+        leaf: &DynSolValue,
+        mut reader: R,
+    ) -> Result<bool, MerkleTreeError> {
+        let leaf_hash = self.leaf_hash(leaf)?;
+        // The node and tree index each path has reached, until it runs past the root.
+        let mut paths = self
+            .proof_paths(leaf)
+            .into_iter()
+            .map(|tree_index| Some((leaf_hash, tree_index)))
+            .collect::<Vec<Option<(B256, Option<usize>)>>>();
+        let mut proof_len = 0;
+        loop {
+            let mut sibling = B256::ZERO;
+            let mut filled = 0;
+            while filled < sibling.len() {
+                match reader.read(&mut sibling[filled..]) {
+                    Ok(0) => break,
+                    Ok(read) => filled += read,
+                    Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(_) => return Err(MerkleTreeError::ProofRead),
+                }
+            }
+
+            match filled {
+                0 => break,
+                32 => {
+                    proof_len += 1;
+                    for path in paths.iter_mut() {
+                        *path = path.and_then(|(node, tree_index)| {
+                            self.fold_proof_sibling(node, tree_index, sibling)
+                        });
+                    }
+                }
+                _ => return Err(MerkleTreeError::TruncatedProof),
+            }
+        }
+
+        if (proof_len == 0 && self.leaf_count() > 1) || proof_len > self.depth() {
+            return Ok(false);
+        }
+        Ok(paths.into_iter().flatten().any(|(node, tree_index)| {
+            tree_index.map_or(true, |index| index == 0) && self.has_root(node)
+        }))
+    }
+
+    /// Verifies that a Merkle proof proves the given leaf at exactly the position `leaf_index`.
+    ///
+    /// [`Self::verify_proof`] only shows that the leaf is included *somewhere* in the tree. This walks
+    /// the path from `leaf_index` up to the root and requires every proof element to be the sibling
+    /// stored at that position, so a valid proof presented for the wrong index is rejected.
+    ///
+    /// Returns [`MerkleTreeError::LeafNotFound`] if `leaf_index` is out of bounds.
+    pub fn verify_at_index(
+        &self,
+        leaf: &DynSolValue,
+        leaf_index: usize,
+        proof: &[B256],
+    ) -> Result<bool, MerkleTreeError> {
+        let mut current_index = self.leaf_index_to_tree_index(leaf_index)?;
+        let mut hash = self.leaf_hash(leaf)?;
+        if self.tree[current_index] != hash {
+            return Ok(false);
+        }
+
+        for sibling in proof {
+            // A proof longer than the path from the leaf to the root can't be for this index.
+            if current_index == 0 {
+                return Ok(false);
+            }
+            if self.tree[sibling_index(current_index)?] != *sibling {
+                return Ok(false);
+            }
+            hash = self
+                .options
+                .hash_order
+                .hash_with_sibling::<H>(current_index, hash, *sibling);
+            current_index = parent_index(current_index);
+        }
+
+        Ok(current_index == 0 && self.has_root(hash))
+    }
+
+    /// Replaces the value of the leaf at `leaf_index` with `new_value`, rehashing the path from the
+    /// leaf to the root.
+    ///
+    /// A leaf without a value, such as an absent leaf of [`Self::of_by_index`], gets `new_value` as
+    /// its value. Returns [`MerkleTreeError::LeafNotFound`] if `leaf_index` is out of bounds.
+    pub fn update_leaf(
+        &mut self,
+        leaf_index: usize,
+        new_value: &DynSolValue,
+    ) -> Result<(), MerkleTreeError> {
+        let tree_index = self.leaf_index_to_tree_index(leaf_index)?;
+        let new_key = Self::check_valid_value_type(new_value)?;
+        let value_position = self.value_position(leaf_index);
+        for (index, hash) in self.updated_path(tree_index, self.leaf_hash(new_value)?) {
+            self.tree[index] = hash;
+        }
+
+        for map in [&mut self.tree_values, &mut self.keys] {
+            map.retain(|_, tree_indices| {
+                tree_indices.retain(|index| *index != tree_index);
+                !tree_indices.is_empty()
+            });
+        }
+        match value_position {
+            Ok(position) => self.values[position] = new_value.clone(),
+            Err(position) => self.values.insert(position, new_value.clone()),
+        }
+        let tree_indices = self.tree_values.entry(new_key).or_default();
+        tree_indices.push(tree_index);
+        // Keep the occurrences in leaf order, i.e. in descending tree index order.
+        tree_indices.sort_unstable_by(|a, b| b.cmp(a));
+
+        Ok(())
+    }
+
+    /// Appends `value` as a new last leaf. See [`Self::extend`] for the cost.
+    ///
+    /// Returns [`MerkleTreeError::NotSupportedType`] if the value isn't of a supported leaf type,
+    /// leaving the tree unchanged.
+    pub fn push(&mut self, value: &DynSolValue) -> Result<(), MerkleTreeError> {
+        self.extend(core::slice::from_ref(value))
+    }
+
+    /// Appends `values` as new leaves, after the existing ones, with the same root as building the
+    /// tree over all the leaves at once.
+    ///
+    /// OpenZeppelin's layout stores the leaves at the tail of a `2n - 1` array, so every new leaf
+    /// shifts the others and changes which nodes are paired: the whole tree is laid out again and
+    /// every internal node rehashed. The existing leaf hashes are reused, so appending `k` leaves to
+    /// `n` costs `k` leaf hashes and `n + k - 1` pair hashes. That is the cost of a single
+    /// [`Self::push`] too, so append in batches where possible. Leaves aren't sorted or given keys
+    /// (see [`StandardMerkleTreeBuilder`]) when appended.
+    ///
+    /// Returns [`MerkleTreeError::NotSupportedType`] if a value isn't of a supported leaf type,
+    /// leaving the tree unchanged.
+    pub fn extend(&mut self, values: &[DynSolValue]) -> Result<(), MerkleTreeError> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        let new_keys = values
+            .iter()
+            .map(Self::check_valid_value_type)
+            .collect::<Result<Vec<String>, MerkleTreeError>>()?;
+        let mut leaf_hashes = self.leaf_hashes();
+        leaf_hashes.extend(hash_leaves::<H>(values, &self.options)?);
+
+        let tree = match self.options.odd_node_policy {
+            OddNodePolicy::Promote => make_merkle_tree::<H>(leaf_hashes, self.options.hash_order),
+            policy => make_padded_merkle_tree::<H>(leaf_hashes, policy, self.options.hash_order),
+        };
+        // Leaves are stored from the end of the array, so they all move by the growth of the array.
+        let shift = tree.len() - self.tree.len();
+        for map in [&mut self.tree_values, &mut self.keys] {
+            map.values_mut()
+                .flat_map(|tree_indices| tree_indices.iter_mut())
+                .for_each(|tree_index| *tree_index += shift);
+        }
+        for (offset, key) in new_keys.into_iter().enumerate() {
+            let tree_index = tree.len() - 1 - (self.leaf_count + offset);
+            self.tree_values.entry(key).or_default().push(tree_index);
+        }
+        self.tree = tree;
+        self.leaf_count += values.len();
+        self.values.extend_from_slice(values);
+
+        Ok(())
+    }
+
+    /// Reserves room for at least `additional` more leaves in the values and lookup map of the tree.
+    ///
+    /// The node array is laid out again on every append (see [`Self::extend`]), so it isn't
+    /// reserved.
+    pub fn reserve(&mut self, additional: usize) {
+        self.values.reserve(additional);
+        self.tree_values.reserve(additional);
+    }
+
+    /// Computes the root the tree would have if the leaf at `leaf_index` were `new_value`, without
+    /// modifying the tree.
+    ///
+    /// Only the path from the leaf to the root is rehashed. Returns [`MerkleTreeError::LeafNotFound`]
+    /// if `leaf_index` is out of bounds.
+    pub fn root_if_leaf_changed(
+        &self,
+        leaf_index: usize,
+        new_value: &DynSolValue,
+    ) -> Result<B256, MerkleTreeError> {
+        let tree_index = self.leaf_index_to_tree_index(leaf_index)?;
+        let path = self.updated_path(tree_index, self.leaf_hash(new_value)?);
+        Ok(path[path.len() - 1].1)
+    }
+
+    /// Computes the nodes that change when the leaf at `tree_index` is set to `leaf_hash`, from the
+    /// leaf up to the root, as `(tree index, new hash)` pairs.
+    ///
+    /// With [`OddNodePolicy::HashWithSelf`] a node paired with its own filler copy also updates the
+    /// filler.
+    fn updated_path(&self, tree_index: usize, leaf_hash: B256) -> Vec<(usize, B256)> {
+        let copies_filler = self.options.odd_node_policy == OddNodePolicy::HashWithSelf;
+        // The last slot of the current level and its number of nodes, fillers excluded.
+        let mut level_end = self.tree.len() - 1;
+        let mut level_len = self.leaf_count;
+
+        let mut index = tree_index;
+        let mut hash = leaf_hash;
+        let mut path = vec![(index, hash)];
+        while let Ok(sibling) = sibling_index(index) {
+            let sibling_hash = if copies_filler && level_end - sibling >= level_len {
+                path.push((sibling, hash));
+                hash
+            } else {
+                self.tree[sibling]
+            };
+            hash = self
+                .options
+                .hash_order
+                .hash_with_sibling::<H>(index, hash, sibling_hash);
+            index = parent_index(index);
+            level_end = (level_end - 1) / 2;
+            level_len = (level_len + 1) / 2;
+            path.push((index, hash));
+        }
+
+        path
+    }
+
+    /// Returns, for each leaf index, the index of the leaf it is paired with at the leaf level.
+    ///
+    /// A leaf whose sibling is an internal node (the last leaf of a tree with an odd number of
+    /// leaves) is reported as unpaired with `None`.
+    pub fn leaf_pairs(&self) -> Vec<(usize, Option<usize>)> {
+        (0..self.leaf_count())
+            .map(|leaf_index| {
+                let partner = self
+                    .leaf_index_to_tree_index(leaf_index)
+                    .and_then(sibling_index)
+                    .and_then(|sibling| self.tree_index_to_leaf_index(sibling))
+                    .ok();
+                (leaf_index, partner)
+            })
+            .collect()
+    }
+
+    /// Converts the index of a leaf (its position among the leaves, `0..leaf_count`) into its index in
+    /// the flat tree array.
+    ///
+    /// Leaves are stored at the tail of the array in reverse order, so leaf `i` of a tree with `n`
+    /// nodes lives at `n - 1 - i`. Returns [`MerkleTreeError::LeafNotFound`] if `leaf_index` is out of
+    /// bounds.
+    pub fn leaf_index_to_tree_index(&self, leaf_index: usize) -> Result<usize, MerkleTreeError> {
+        if leaf_index >= self.leaf_count() {
+            return Err(MerkleTreeError::LeafNotFound);
+        }
+        Ok(self.tree.len() - 1 - leaf_index)
+    }
+
+    /// Converts an index in the flat tree array into the index of the leaf stored there.
+    ///
+    /// This is the inverse of [`Self::leaf_index_to_tree_index`]. Returns
+    /// [`MerkleTreeError::InvalidCheck`] if `tree_index` isn't a leaf node, or is an unused leaf slot
+    /// of a padded tree.
+    pub fn tree_index_to_leaf_index(&self, tree_index: usize) -> Result<usize, MerkleTreeError> {
+        check_leaf_node(&self.tree, tree_index)?;
+        let leaf_index = self.tree.len() - 1 - tree_index;
+        if leaf_index >= self.leaf_count {
+            return Err(MerkleTreeError::InvalidCheck);
+        }
+        Ok(leaf_index)
+    }
+
+    /// Returns the number of leaves in the tree.
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Checks whether the tree has no leaves.
+    pub fn is_empty(&self) -> bool {
+        self.leaf_count == 0
+    }
+
+    /// Validates and serializes a [`DynSolValue`] into a [`String`].
+    fn check_valid_value_type(value: &DynSolValue) -> Result<String, MerkleTreeError> {
+        match value {
+            // Keys are prefixed with their type, so that e.g. an address and the string of its hex
+            // form don't collide.
+            DynSolValue::String(inner_value) => Ok(alloc::format!("string:{inner_value}")),
+            DynSolValue::FixedBytes(inner_value, _) => Ok(alloc::format!("bytes:{inner_value}")),
+            DynSolValue::Address(inner_value) => Ok(alloc::format!("address:{inner_value}")),
+            DynSolValue::Uint(inner_value, _) => Ok(alloc::format!("uint:{inner_value}")),
+            DynSolValue::Int(inner_value, _) => Ok(alloc::format!("int:{inner_value}")),
+            DynSolValue::Bool(inner_value) => Ok(alloc::format!("bool:{inner_value}")),
+            // Tuples have no natural string form, so they are keyed by their ABI encoding.
+            DynSolValue::Tuple(_) => Ok(alloc::format!(
+                "{}:{}",
+                sol_type_name(value),
+                hex::encode_prefixed(value.abi_encode_params())
+            )),
+            _ => Err(MerkleTreeError::NotSupportedType),
+        }
+    }
+}
+
+/// Verifies a Merkle proof against a root bound to `metadata` with [`StandardMerkleTree::bound_root`].
+///
+/// Returns `true` only if `bound_root` commits to `root` under exactly this `metadata` and the proof
+/// folds `leaf_hash` up to `root`.
+pub fn verify_bound(
+    bound_root: B256,
+    leaf_hash: B256,
+    proof: &[B256],
+    root: B256,
+    metadata: &[u8],
+) -> bool {
+    bind_root(root, metadata) == bound_root && process_proof(leaf_hash, proof) == root
+}
+
+/// Verifies a Merkle proof against a root stored in the given [`ByteOrder`].
+///
+/// The other verifiers take the root as computed by the tree; pass them
+/// [`ByteOrder::normalize`]d roots to verify against byte-reversed storage.
+pub fn verify_stored_root(
+    stored_root: B256,
+    byte_order: ByteOrder,
+    leaf_hash: B256,
+    proof: &[B256],
+) -> bool {
+    process_proof(leaf_hash, proof) == byte_order.normalize(stored_root)
+}
+
+/// Returns the number of hash operations building a tree of `leaf_count` leaves takes with the
+/// given [`OddNodePolicy`]: one leaf hash per leaf plus one pair hash per internal node.
+///
+/// With [`OddNodePolicy::Promote`], as in [`StandardMerkleTree::of`], that is `2 * leaf_count - 1`.
+/// The other policies pair the last node of every odd level with a filler, hashing one more pair
+/// on that level, so they cost more. The policy is a parameter because `2 * leaf_count - 1` alone
+/// would underestimate every padded tree of a count that isn't a power of two.
+///
+/// With the `stats` feature, the [`stats`](crate::stats) counters of a build add up to this cost.
+pub fn rebuild_hash_cost(leaf_count: usize, policy: OddNodePolicy) -> usize {
+    match policy {
+        OddNodePolicy::Promote => leaf_count + leaf_count.saturating_sub(1),
+        OddNodePolicy::HashWithSelf | OddNodePolicy::HashWithZero => {
+            let mut cost = leaf_count;
+            let mut level_len = leaf_count;
+            while level_len > 1 {
+                level_len = level_len.div_ceil(2);
+                cost += level_len;
+            }
+            cost
+        }
+    }
+}
+
+/// Returns the range of leaf counts consistent with a proof of `proof_len` elements in a tree built
+/// with the given [`OddNodePolicy`].
+///
+/// With [`OddNodePolicy::Promote`], the default, the leaves can span the two deepest levels, so a
+/// proof of `n` elements fits `2^(n - 1) + 1 ..= 2^(n + 1) - 1` leaves. The other policies keep
+/// every leaf at the same depth, so it fits `2^(n - 1) + 1 ..= 2^n` leaves. An empty proof is only
+/// that of a single leaf.
+///
+/// The policy is a parameter because the padded range alone would reject valid proofs of default
+/// trees: in a tree of 6 leaves built with [`StandardMerkleTree::of`], two leaves have proofs of 2
+/// elements, outside `3..=4`.
+pub fn plausible_leaf_count(
+    proof_len: usize,
+    policy: OddNodePolicy,
+) -> core::ops::RangeInclusive<usize> {
+    match (proof_len, policy) {
+        (0, _) => 1..=1,
+        (_, OddNodePolicy::Promote) => (1 << (proof_len - 1)) + 1..=(1 << (proof_len + 1)) - 1,
+        (_, OddNodePolicy::HashWithSelf | OddNodePolicy::HashWithZero) => {
+            (1 << (proof_len - 1)) + 1..=1 << proof_len
+        }
+    }
+}
+
+/// Returns the index of the first of `candidate_roots` that `proof` proves `leaf_hash` against, if any.
+///
+/// The proof is folded once, so checking many candidates (e.g. the roots published around a reorg)
+/// costs no more than checking one.
+pub fn which_root(leaf_hash: B256, proof: &[B256], candidate_roots: &[B256]) -> Option<usize> {
+    let implied_root = process_proof(leaf_hash, proof);
+    candidate_roots
+        .iter()
+        .position(|root| *root == implied_root)
+}
+
+/// Verifies a proof for the leaf at `leaf_index` of a tree built with
+/// [`StandardMerkleTree::of_tx_hashes`], without the tree.
+///
+/// At each level the node is the left one if its index on that level is even, and the parent is
+/// `keccak256(left ++ right)`.
+pub fn verify_positional(root: B256, leaf_hash: B256, leaf_index: usize, proof: &[B256]) -> bool {
+    let mut index = leaf_index;
+    let mut hash = leaf_hash;
+    for sibling in proof {
+        hash = if index % 2 == 0 {
+            positional_hash_pair(hash, *sibling)
+        } else {
+            positional_hash_pair(*sibling, hash)
+        };
+        index /= 2;
+    }
+    index == 0 && hash == root
+}
+
+/// Verifies a proof for a tree of the given `arity`, where each level contributes a group of
+/// `arity - 1` siblings.
+///
+/// A node is the `keccak256` of its `arity` children sorted in ascending order and concatenated, so
+/// an arity-2 proof folds exactly like [`StandardMerkleTree`]'s sorted pairs. Returns `false` if the
+/// arity is below 2 or any group doesn't have exactly `arity - 1` siblings, so a proof is never
+/// folded under the wrong arity.
+pub fn verify_arity(root: B256, leaf_hash: B256, proof_groups: &[Vec<B256>], arity: usize) -> bool {
+    if arity < 2 || proof_groups.iter().any(|group| group.len() != arity - 1) {
+        return false;
+    }
+
+    let implied_root = proof_groups.iter().fold(leaf_hash, |node, group| {
+        let mut children = group.clone();
+        children.push(node);
+        children.sort_unstable();
+
+        let mut hasher = Keccak256::new();
+        children.iter().for_each(|child| hasher.update(child));
+        hasher.finalize()
+    });
+    implied_root == root
+}
+
+/// Verifies a positional proof, where `positions[i]` is `true` if `siblings[i]` is the left node of
+/// its pair, as returned by [`StandardMerkleTree::get_proof_with_positions`].
+///
+/// A parent is `keccak256(left ++ right)`. Returns `false` if there isn't exactly one position per
+/// sibling.
+pub fn verify_with_positions(
+    root: B256,
+    leaf_hash: B256,
+    siblings: &[B256],
+    positions: &[bool],
+) -> bool {
+    if siblings.len() != positions.len() {
+        return false;
+    }
+
+    let implied_root =
+        siblings
+            .iter()
+            .zip(positions)
+            .fold(leaf_hash, |node, (sibling, sibling_is_left)| {
+                if *sibling_is_left {
+                    positional_hash_pair(*sibling, node)
+                } else {
+                    positional_hash_pair(node, *sibling)
+                }
+            });
+    implied_root == root
+}
+
+/// Verifies a Merkle proof produced by a library using the given [`LeafScheme`] against `root`.
+pub fn verify_with_scheme(
+    root: B256,
+    leaf: &DynSolValue,
+    proof: &[B256],
+    scheme: LeafScheme,
+) -> bool {
+    scheme
+        .leaf_hash(leaf)
+        .map_or(false, |leaf_hash| process_proof(leaf_hash, proof) == root)
+}
+
+/// Computes the root of a perfect subtree of the given `depth` whose leaves are all `empty_leaf`.
+///
+/// The result is `empty_leaf` folded with `hash_pair(x, x)` `depth` times, so a depth of `0` returns
+/// `empty_leaf` itself. This is the default node of a sparse tree at that height.
+pub fn empty_subtree_root(depth: usize, empty_leaf: B256) -> B256 {
+    (0..depth).fold(empty_leaf, |node, _| hash_pair(node, node))
+}
+
+/// Computes the root of a [`StandardMerkleTree`] of `count` leaves that all hash to `leaf_hash`, or
+/// `None` for no leaves.
+///
+/// A tree without leaves has no root, so a `count` of `0` returns `None` like
+/// [`StandardMerkleTree::root`] does, rather than a made-up hash that a caller could mistake for a
+/// real root.
+///
+/// A power-of-two `count` is a perfect tree, whose root is [`empty_subtree_root`] in `log2(count)`
+/// hashes; any other count builds the tree in full.
+pub fn uniform_root(leaf_hash: B256, count: usize) -> Option<B256> {
+    if count.is_power_of_two() {
+        return Some(empty_subtree_root(
+            count.trailing_zeros() as usize,
+            leaf_hash,
+        ));
+    }
+    make_merkle_tree::<Keccak256Hasher>(vec![leaf_hash; count], HashOrder::Sorted)
+        .first()
+        .copied()
+}
+
+/// Verifies a Merkle proof for a leaf of a tree built with [`StandardMerkleTree::of`] against
+/// `root` alone, without the tree.
+///
+/// Returns `false` for a leaf of an unsupported type.
+pub fn verify(root: B256, leaf: &DynSolValue, proof: &[B256]) -> bool {
+    standard_leaf_hash(leaf).map_or(false, |leaf_hash| verify_hash(root, leaf_hash, proof))
+}
+
+/// Checks whether two proofs, e.g. two submissions in a relayer's queue, both verify against
+/// `root` for the same leaf.
+///
+/// Returns `Ok(false)` if either proof is invalid, and [`MerkleTreeError::NotSupportedType`] if
+/// either leaf is of an unsupported type.
+pub fn proofs_target_same_leaf(
+    leaf_a: &DynSolValue,
+    proof_a: &[B256],
+    leaf_b: &DynSolValue,
+    proof_b: &[B256],
+    root: B256,
+) -> Result<bool, MerkleTreeError> {
+    let leaf_hash_a = standard_leaf_hash(leaf_a)?;
+    let leaf_hash_b = standard_leaf_hash(leaf_b)?;
+
+    Ok(leaf_hash_a == leaf_hash_b
+        && verify_hash(root, leaf_hash_a, proof_a)
+        && verify_hash(root, leaf_hash_b, proof_b))
+}
+
+/// Checks that two proof paths, each the nodes from a leaf up to a shared root with the proof that
+/// implies them, describe the same tree.
+///
+/// Walking down from the root, the paths share their nodes and siblings until they split, where the
+/// node of each path must be the sibling of the other; below that they never meet again. Paths that
+/// don't split must be the same length, since a leaf can't be an ancestor of another leaf.
+fn paths_agree(path_a: &[B256], proof_a: &[B256], path_b: &[B256], proof_b: &[B256]) -> bool {
+    let nodes_a = path_a.iter().rev().skip(1);
+    let nodes_b = path_b.iter().rev().skip(1);
+    let siblings_a = proof_a.iter().rev();
+    let siblings_b = proof_b.iter().rev();
+
+    for ((node_a, sibling_a), (node_b, sibling_b)) in
+        nodes_a.zip(siblings_a).zip(nodes_b.zip(siblings_b))
+    {
+        if node_a != node_b {
+            return node_a == sibling_b && node_b == sibling_a;
+        }
+        if sibling_a != sibling_b {
+            return false;
+        }
+    }
+    path_a.len() == path_b.len()
+}
+
+/// Verifies a Merkle proof for a precomputed leaf hash against `root` alone, like OpenZeppelin's
+/// `MerkleProof.verify`.
+pub fn verify_hash(root: B256, leaf_hash: B256, proof: &[B256]) -> bool {
+    process_proof(leaf_hash, proof) == root
+}
+
+/// Verifies a Merkle proof for a leaf of a tree built with
+/// [`StandardMerkleTreeBuilder::chain_id`], requiring the leaf to be bound to `chain_id`.
+pub fn verify_with_chain_id(root: B256, chain_id: u64, leaf: &DynSolValue, proof: &[B256]) -> bool {
+    let options = TreeOptions {
+        chain_id: Some(chain_id),
+        ..TreeOptions::default()
+    };
+    leaf_hash_with_options::<Keccak256Hasher>(leaf, &options)
+        .map_or(false, |leaf_hash| process_proof(leaf_hash, proof) == root)
+}
+
+/// Checks whether a Bloom filter built by [`StandardMerkleTree::build_with_bloom`] with `k` hash
+/// functions may contain `leaf_hash`.
+///
+/// With `m = 8 * bloom.len()`, the leaf sets the bits `uint256(keccak256(leaf_hash ++ uint256(i))) % m`
+/// for `i` in `0..k`, bit `p` being bit `p % 8` of byte `p / 8`. `false` means the leaf is certainly
+/// not in the tree; `true` only means a proof is worth checking.
+pub fn bloom_maybe_contains(bloom: &[u8], k: usize, leaf_hash: B256) -> bool {
+    !bloom.is_empty()
+        && bloom_positions(bloom.len() * 8, k, leaf_hash)
+            .all(|position| bloom[position / 8] & (1 << (position % 8)) != 0)
+}
+
+/// Computes the `k` Bloom filter bit positions of `leaf_hash` in a filter of `bits` bits.
+fn bloom_positions(bits: usize, k: usize, leaf_hash: B256) -> impl Iterator<Item = usize> {
+    (0..k).map(move |i| {
+        let mut hasher = Keccak256::new();
+        hasher.update(leaf_hash);
+        hasher.update(U256::from(i).to_be_bytes::<32>());
+        let position = U256::from_be_bytes(hasher.finalize().0) % U256::from(bits);
+        position.to::<usize>()
+    })
+}
+
+/// Computes `keccak256(root ++ metadata)`.
+fn bind_root(root: B256, metadata: &[u8]) -> B256 {
+    let mut hasher = Keccak256::new();
+    hasher.update(root);
+    hasher.update(metadata);
+    hasher.finalize()
+}
+
+/// Returns the Solidity type name of a value, e.g. `uint256`.
+fn sol_type_name(value: &DynSolValue) -> String {
+    value
+        .sol_type_name()
+        .map(|name| name.into_owned())
+        .unwrap_or_default()
+}
+
+/// Renders a value as a column of a [`StandardMerkleTreeData`] leaf, in the syntax
+/// [`DynSolType::coerce_str`] parses back.
+fn dump_value_string(value: &DynSolValue) -> String {
+    match value {
+        DynSolValue::Bool(inner_value) => inner_value.to_string(),
+        DynSolValue::Int(inner_value, _) => inner_value.to_string(),
+        DynSolValue::Uint(inner_value, _) => inner_value.to_string(),
+        DynSolValue::FixedBytes(inner_value, size) => hex::encode_prefixed(&inner_value[..*size]),
+        DynSolValue::Address(inner_value) => inner_value.to_checksum(None),
+        DynSolValue::Bytes(inner_value) => hex::encode_prefixed(inner_value),
+        DynSolValue::String(inner_value) => inner_value.clone(),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) => {
+            let items = items.iter().map(dump_value_string).collect::<Vec<String>>();
+            alloc::format!("[{}]", items.join(","))
+        }
+        DynSolValue::Tuple(items) => {
+            let items = items.iter().map(dump_value_string).collect::<Vec<String>>();
+            alloc::format!("({})", items.join(","))
+        }
+        value => hex::encode_prefixed(value.abi_encode_packed()),
+    }
+}
+
+/// Shortens a hash to its first and last four bytes, e.g. `0x1a2b3c4d…5e6f7a8b`.
+fn short_hex(hash: B256) -> String {
+    alloc::format!(
+        "{}…{}",
+        hex::encode_prefixed(&hash[..4]),
+        hex::encode(&hash[28..])
+    )
+}
+
+/// Renders a value like [`dump_value_string`], with addresses in lowercase rather than checksummed.
+fn canonical_value_string(value: &DynSolValue) -> String {
+    match value {
+        DynSolValue::Address(inner_value) => hex::encode_prefixed(inner_value),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) => {
+            let items = items
+                .iter()
+                .map(canonical_value_string)
+                .collect::<Vec<String>>();
+            alloc::format!("[{}]", items.join(","))
+        }
+        DynSolValue::Tuple(items) => {
+            let items = items
+                .iter()
+                .map(canonical_value_string)
+                .collect::<Vec<String>>();
+            alloc::format!("({})", items.join(","))
+        }
+        value => dump_value_string(value),
+    }
+}
+
+/// Appends `items` to `json` as an array of JSON strings.
+fn push_json_strings(json: &mut String, items: &[String]) {
+    json.push('[');
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        push_json_string(json, item);
+    }
+    json.push(']');
+}
+
+/// Appends `value` to `json` as a JSON string, escaping quotes, backslashes, and control
+/// characters.
+fn push_json_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&alloc::format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// Writes `items` to `writer` as an array of JSON strings, see [`write_json_string`].
+#[cfg(all(feature = "serde", feature = "std"))]
+fn write_json_strings(writer: &mut impl std::io::Write, items: &[String]) -> std::io::Result<()> {
+    writer.write_all(b"[")?;
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            writer.write_all(b",")?;
+        }
+        write_json_string(writer, item)?;
+    }
+    writer.write_all(b"]")
+}
+
+/// Writes `value` to `writer` as a JSON string, escaped as `serde_json` escapes it: quotes,
+/// backslashes and the ASCII control characters, with the short escapes where JSON has one.
+#[cfg(all(feature = "serde", feature = "std"))]
+fn write_json_string(writer: &mut impl std::io::Write, value: &str) -> std::io::Result<()> {
+    writer.write_all(b"\"")?;
+    let mut start = 0;
+    for (index, byte) in value.bytes().enumerate() {
+        let escape = match byte {
+            b'"' => Some("\\\""),
+            b'\\' => Some("\\\\"),
+            b'\x08' => Some("\\b"),
+            b'\x0c' => Some("\\f"),
+            b'\n' => Some("\\n"),
+            b'\r' => Some("\\r"),
+            b'\t' => Some("\\t"),
+            0x00..=0x1f => None,
+            _ => continue,
+        };
+        writer.write_all(&value.as_bytes()[start..index])?;
+        match escape {
+            Some(escape) => writer.write_all(escape.as_bytes())?,
+            None => write!(writer, "\\u{byte:04x}")?,
+        }
+        start = index + 1;
+    }
+    writer.write_all(&value.as_bytes()[start..])?;
+    writer.write_all(b"\"")
+}
+
+/// Computes the leaf hash of every value under the given tree options, hashing with `H`. With the
+/// `rayon` feature the values are hashed in parallel.
+fn hash_leaves<H: Hasher>(
+    values: &[DynSolValue],
+    options: &TreeOptions,
+) -> Result<Vec<B256>, MerkleTreeError> {
+    hash_leaves_with_cache::<H>(values, options, true)
+}
+
+/// Computes the leaf hash of every value like [`hash_leaves`], sharing a [`ColumnCache`] between
+/// the tuple leaves if `cache_columns` is set. The hashes are the same either way.
+///
+/// With the `rayon` feature, blocks of [`PARALLEL_BLOCK`] values are hashed in parallel, each with
+/// a cache of its own.
+fn hash_leaves_with_cache<H: Hasher>(
+    values: &[DynSolValue],
+    options: &TreeOptions,
+    cache_columns: bool,
+) -> Result<Vec<B256>, MerkleTreeError> {
+    #[cfg(feature = "rayon")]
+    let leaf_hashes = values
+        .par_chunks(PARALLEL_BLOCK)
+        .map(|block| hash_leaf_block::<H>(block, options, cache_columns))
+        .collect::<Result<Vec<Vec<B256>>, MerkleTreeError>>()?
+        .concat();
+    #[cfg(not(feature = "rayon"))]
+    let leaf_hashes = hash_leaf_block::<H>(values, options, cache_columns)?;
+    #[cfg(feature = "stats")]
+    crate::stats::record_leaf_hashes(leaf_hashes.len());
+    Ok(leaf_hashes)
+}
+
+/// The number of values each rayon job of [`hash_leaves_with_cache`] hashes.
+#[cfg(feature = "rayon")]
+const PARALLEL_BLOCK: usize = 1024;
+
+/// Computes the leaf hash of every value of `values`, [`HASH_BATCH`] at a time with
+/// [`Hasher::hash_batch`] or [`Hasher::hash_leaf_batch`], sharing a [`ColumnCache`] between the
+/// tuple leaves if `cache_columns` is set.
+fn hash_leaf_block<H: Hasher>(
+    values: &[DynSolValue],
+    options: &TreeOptions,
+    cache_columns: bool,
+) -> Result<Vec<B256>, MerkleTreeError> {
+    let mut cache = ColumnCache::default();
+    let mut buffers: [Vec<u8>; HASH_BATCH] = Default::default();
+    let mut leaf_hashes = Vec::with_capacity(values.len());
+    for batch in values.chunks(HASH_BATCH) {
+        let mut raw_leaves = [None; HASH_BATCH];
+        for (lane, buffer) in buffers.iter_mut().enumerate() {
+            buffer.clear();
+            if let Some(value) = batch.get(lane) {
+                raw_leaves[lane] = encode_leaf_with_options(
+                    value,
+                    options,
+                    cache_columns.then_some(&mut cache),
+                    buffer,
+                )?;
+            }
+        }
+        // The last batch may be partial, and its leaves are hashed one by one.
+        let inputs = buffers.each_ref().map(Vec::as_slice);
+        let hashes = match options.leaf_hash_mode {
+            LeafHashMode::Raw => [B256::ZERO; HASH_BATCH],
+            _ if batch.len() < HASH_BATCH => core::array::from_fn(|lane| match batch.get(lane) {
+                Some(_) => leaf_hash_of_encoding::<H>(inputs[lane], options.leaf_hash_mode),
+                None => B256::ZERO,
+            }),
+            LeafHashMode::Single => H::hash_batch(inputs),
+            LeafHashMode::Double => H::hash_leaf_batch(inputs),
+        };
+        leaf_hashes.extend(
+            raw_leaves
+                .into_iter()
+                .zip(hashes)
+                .take(batch.len())
+                .map(|(raw_leaf, hash)| raw_leaf.unwrap_or(hash)),
+        );
+    }
+    Ok(leaf_hashes)
+}
+
+/// Computes the leaf hash of a value in a tree built with [`StandardMerkleTree::of`], as
+/// OpenZeppelin does: `keccak256(keccak256(encoded))`.
+///
+/// Use it to index claims off-chain by their leaf, or to feed an external verifier. Returns
+/// [`MerkleTreeError::NotSupportedType`] if the value isn't of a supported leaf type.
+pub fn standard_leaf_hash(value: &DynSolValue) -> Result<B256, MerkleTreeError> {
+    Ok(Keccak256Hasher::hash_leaf(&leaf_encoding(value)?))
+}
+
+/// Computes the leaf hash for a given value under the given tree options, hashing with `H`.
+fn leaf_hash_with_options<H: Hasher>(
+    value: &DynSolValue,
+    options: &TreeOptions,
+) -> Result<B256, MerkleTreeError> {
+    let mut encoded = Vec::new();
+    match encode_leaf_with_options(value, options, None, &mut encoded)? {
+        Some(raw_leaf) => Ok(raw_leaf),
+        None => Ok(leaf_hash_of_encoding::<H>(&encoded, options.leaf_hash_mode)),
+    }
+}
+
+/// Hashes a leaf encoding into its leaf node under the given hashing mode, other than
+/// [`LeafHashMode::Raw`].
+fn leaf_hash_of_encoding<H: Hasher>(encoded: &[u8], mode: LeafHashMode) -> B256 {
+    match mode {
+        LeafHashMode::Single => H::hash(encoded),
+        _ => H::hash_leaf(encoded),
+    }
+}
+
+/// Appends the bytes the leaf hash of a value is computed over under the given tree options to
+/// `buffer`, encoding the columns of a tuple leaf through `cache` if given.
+///
+/// With [`LeafHashMode::Raw`] nothing is hashed, and the leaf itself is returned instead.
+fn encode_leaf_with_options<'a>(
+    value: &'a DynSolValue,
+    options: &TreeOptions,
+    cache: Option<&mut ColumnCache<'a>>,
+    buffer: &mut Vec<u8>,
+) -> Result<Option<B256>, MerkleTreeError> {
+    options.check()?;
+    if options.leaf_hash_mode == LeafHashMode::Raw {
+        return match value {
+            DynSolValue::FixedBytes(word, 32) => Ok(Some(*word)),
+            DynSolValue::Uint(word, 256) => Ok(Some(B256::from(word.to_be_bytes()))),
+            _ => Err(MerkleTreeError::NotSupportedType),
+        };
+    }
+    if let Some(chain_id) = options.chain_id {
+        buffer.extend_from_slice(&U256::from(chain_id).to_be_bytes::<32>());
+    }
+    encode_leaf_into(value, buffer, cache)?;
+    Ok(None)
+}
+
+/// Computes the leaf hash of `keccak256(abi.encodePacked(parts))` leaves: the `bytes32` parts are
+/// concatenated and double-hashed like a standard leaf, giving
+/// `keccak256(keccak256(parts[0] ++ parts[1] ++ ...))`.
+pub fn leaf_hash_packed_bytes32(parts: &[B256]) -> B256 {
+    let packed = parts.iter().flat_map(|part| part.0).collect::<Vec<u8>>();
+    keccak256(keccak256(packed))
+}
+
+/// Returns the leaf packing `parts`, as built by [`StandardMerkleTree::of_packed_bytes32`].
+fn packed_bytes32_leaf(parts: &[B256]) -> DynSolValue {
+    DynSolValue::Tuple(
+        parts
+            .iter()
+            .map(|part| DynSolValue::FixedBytes(*part, 32))
+            .collect(),
+    )
+}
+
+/// Returns the leaf binding `value` to `nonce`, as built by [`StandardMerkleTree::of_with_nonce`].
+///
+/// Returns [`MerkleTreeError::NotSupportedType`] if the value isn't of a supported leaf type.
+fn nonce_leaf(value: &DynSolValue, nonce: u64) -> Result<DynSolValue, MerkleTreeError> {
+    leaf_encoding(value)?;
+    Ok(DynSolValue::Tuple(vec![
+        value.clone(),
+        DynSolValue::Uint(U256::from(nonce), 256),
+    ]))
+}
+
+/// Encodes a leaf value into the bytes its leaf hash is computed over.
+fn leaf_encoding(value: &DynSolValue) -> Result<Vec<u8>, MerkleTreeError> {
+    let mut encoded = Vec::new();
+    encode_leaf_into(value, &mut encoded, None)?;
+    Ok(encoded)
+}
+
+/// Appends the encoding of a leaf value to `buffer`, so that a buffer can be reused across leaves.
+/// The dynamic columns of a tuple leaf are encoded through `cache` if given.
+///
+/// Returns [`MerkleTreeError::NotSupportedType`], leaving `buffer` untouched, if the value isn't of
+/// a supported leaf type.
+fn encode_leaf_into<'a>(
+    value: &'a DynSolValue,
+    buffer: &mut Vec<u8>,
+    cache: Option<&mut ColumnCache<'a>>,
+) -> Result<(), MerkleTreeError> {
+    match value {
+        DynSolValue::String(inner_value) => buffer.extend_from_slice(inner_value.as_bytes()),
+        DynSolValue::FixedBytes(inner_value, _) => buffer.extend_from_slice(inner_value.as_slice()),
+        // Uints are hashed over their 32-byte big-endian ABI word.
+        DynSolValue::Uint(inner_value, _) => {
+            buffer.extend_from_slice(&inner_value.to_be_bytes::<32>())
+        }
+        // Other single values are hashed over their 32-byte ABI word too, like OpenZeppelin's
+        // single-column trees, e.g. `["address"]`.
+        DynSolValue::Address(_) | DynSolValue::Int(_, _) | DynSolValue::Bool(_) => {
+            if let Some(word) = abi_word(value) {
+                buffer.extend_from_slice(word.as_slice());
+            }
+        }
+        // Multi-value leaves are ABI-encoded like OpenZeppelin's `abi.encode(types, values)`. The
+        // encoding of a tuple of single words is those words in order, written in place.
+        DynSolValue::Tuple(columns) if columns.iter().all(is_abi_word) => {
+            buffer.reserve(32 * columns.len());
+            columns
+                .iter()
+                .filter_map(abi_word)
+                .for_each(|word| buffer.extend_from_slice(word.as_slice()));
+        }
+        DynSolValue::Tuple(columns) => match cache {
+            Some(cache)
+                if columns
+                    .iter()
+                    .all(|column| is_abi_word(column) || column.is_dynamic()) =>
+            {
+                cache.encode_tuple(columns, buffer)
+            }
+            _ => buffer.extend_from_slice(&value.abi_encode_params()),
+        },
+        _ => return Err(MerkleTreeError::NotSupportedType),
+    }
+    Ok(())
+}
+
+/// ABI encodings of the dynamic columns of tuple leaves, e.g. a `string`, keyed by the column value,
+/// so that a value repeated across the leaves of a tree is encoded once.
+///
+/// Word columns, e.g. an `address`, are cheaper to write than to look up and aren't cached. At most
+/// [`ColumnCache::CAPACITY`] encodings are kept; values seen after that are encoded every time.
+#[derive(Default)]
+struct ColumnCache<'a> {
+    tails: HashMap<ColumnKey<'a>, Vec<u8>>,
+}
+
+impl<'a> ColumnCache<'a> {
+    /// The number of column encodings kept at most.
+    const CAPACITY: usize = 1024;
+
+    /// Appends `abi.encode(columns)` to `buffer`, where every column is an ABI word or dynamic.
+    ///
+    /// The head holds the words in place and, for each dynamic column, the offset of its tail,
+    /// which is appended right after the previous one.
+    fn encode_tuple(&mut self, columns: &'a [DynSolValue], buffer: &mut Vec<u8>) {
+        let start = buffer.len();
+        buffer.resize(start + 32 * columns.len(), 0);
+        for (i, column) in columns.iter().enumerate() {
+            let head = start + 32 * i..start + 32 * (i + 1);
+            match abi_word(column) {
+                Some(word) => buffer[head].copy_from_slice(word.as_slice()),
+                None => {
+                    let offset = U256::from(buffer.len() - start);
+                    buffer[head].copy_from_slice(&offset.to_be_bytes::<32>());
+                    self.push_tail(column, buffer);
+                }
+            }
+        }
+    }
+
+    /// Appends the tail of a dynamic column, its encoding without the leading offset word, to
+    /// `buffer`.
+    fn push_tail(&mut self, column: &'a DynSolValue, buffer: &mut Vec<u8>) {
+        if let Some(tail) = self.tails.get(&ColumnKey(column)) {
+            buffer.extend_from_slice(tail);
+            return;
+        }
+        let mut tail = column.abi_encode();
+        tail.drain(..32);
+        buffer.extend_from_slice(&tail);
+        if self.tails.len() < Self::CAPACITY {
+            self.tails.insert(ColumnKey(column), tail);
+        }
+    }
+}
+
+/// A column value as a [`ColumnCache`] key, hashed over its contents.
+#[derive(PartialEq)]
+struct ColumnKey<'a>(&'a DynSolValue);
+
+impl Eq for ColumnKey<'_> {}
+
+impl core::hash::Hash for ColumnKey<'_> {
+    fn hash<S: core::hash::Hasher>(&self, state: &mut S) {
+        hash_column(self.0, state);
+    }
+}
+
+/// Feeds the contents of a column value to `state`. Values of other kinds than these only feed
+/// their kind, and are told apart by equality.
+fn hash_column<S: core::hash::Hasher>(value: &DynSolValue, state: &mut S) {
+    use core::hash::Hash;
+
+    core::mem::discriminant(value).hash(state);
+    match value {
+        DynSolValue::String(string) => string.hash(state),
+        DynSolValue::Bytes(bytes) => bytes.hash(state),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) | DynSolValue::Tuple(items) => {
+            items.len().hash(state);
+            items.iter().for_each(|item| hash_column(item, state));
+        }
+        _ => {
+            if let Some(word) = abi_word(value) {
+                word.hash(state);
+            }
+        }
+    }
+}
+
+/// Whether `value` is ABI-encoded as a single 32-byte word, see [`abi_word`].
+fn is_abi_word(value: &DynSolValue) -> bool {
+    abi_word(value).is_some()
+}
+
+/// Returns the 32-byte ABI word of an address, bool, int, uint or fixed bytes value, or `None` for
+/// any other value.
+fn abi_word(value: &DynSolValue) -> Option<B256> {
+    match value {
+        DynSolValue::Address(address) => Some(address.into_word()),
+        DynSolValue::Bool(flag) => Some(B256::with_last_byte(u8::from(*flag))),
+        DynSolValue::Int(int, _) => Some(B256::from(int.to_be_bytes::<32>())),
+        DynSolValue::Uint(uint, _) => Some(B256::from(uint.to_be_bytes::<32>())),
+        DynSolValue::FixedBytes(word, _) => Some(*word),
+        _ => None,
+    }
+}
+
+/// Calculates the index of the left child for a given parent index..
+fn left_child_index(index: usize) -> usize {
+    2 * index + 1
+}
+
+/// Calculates the index of the right child for a given parent index.
+fn right_child_index(index: usize) -> usize {
+    2 * index + 2
+}
+
+/// Determines the sibling index for a given node index..
+pub(crate) fn sibling_index(index: usize) -> Result<usize, MerkleTreeError> {
+    if index == 0 {
+        return Err(MerkleTreeError::RootHaveNoSiblings);
+    }
+
+    if index % 2 == 0 {
+        Ok(index - 1)
+    } else {
+        Ok(index + 1)
+    }
+}
+
+/// Calculates the parent index for a given child index.
+pub(crate) fn parent_index(index: usize) -> usize {
+    (index - 1) / 2
+}
+
+/// Calculates the distance from the root to the node at a given index.
+fn node_depth(index: usize) -> usize {
+    (index + 1).ilog2() as usize
+}
+
+/// Checks if a given index corresponds to a node within the tree.
+fn is_tree_node(tree: &[B256], index: usize) -> bool {
+    index < tree.len()
+}
+
+/// Checks if a given index corresponds to an internal node (non-leaf).
+fn is_internal_node(tree: &[B256], index: usize) -> bool {
+    is_tree_node(tree, left_child_index(index))
+}
+
+/// Checks if a given index corresponds to a leaf node.
+fn is_leaf_node(tree: &[B256], index: usize) -> bool {
+    !is_internal_node(tree, index) && is_tree_node(tree, index)
+}
+
+/// Validates that a given index corresponds to a leaf node.
+fn check_leaf_node(tree: &[B256], index: usize) -> Result<(), MerkleTreeError> {
+    if !is_leaf_node(tree, index) {
+        Err(MerkleTreeError::InvalidCheck)
+    } else {
+        Ok(())
+    }
+}
+
+/// Constructs a Merkle tree from a vector of leaf hashes, hashing sorted pairs with `H`.
+fn make_merkle_tree<H: Hasher>(leaves: Vec<B256>, order: HashOrder) -> Vec<B256> {
+    make_merkle_tree_with::<H>(leaves, order, &mut |_, _| {})
+}
+
+/// Constructs a Merkle tree from a vector of leaf hashes, calling `on_level` with the depth and the
+/// nodes of every level of the array once it is complete, from the deepest level up to the root.
+fn make_merkle_tree_with<H: Hasher>(
+    leaves: Vec<B256>,
+    order: HashOrder,
+    on_level: &mut impl FnMut(usize, &[B256]),
+) -> Vec<B256> {
+    if leaves.is_empty() {
+        return Vec::new();
+    }
+    let tree_len = 2 * leaves.len() - 1;
+    let mut tree = vec![B256::default(); tree_len];
+    let leaves_len = leaves.len();
+
+    // A zero node is as valid as any other, so written slots can't be told apart from unwritten ones
+    // by value. They are tracked separately to check that no node is read before it is written.
+    #[cfg(debug_assertions)]
+    let mut written = vec![false; tree_len];
+
+    // Place leaves at the end of the tree array.
+    for (i, leaf) in leaves.into_iter().enumerate() {
+        tree[tree_len - 1 - i] = leaf;
+        #[cfg(debug_assertions)]
+        {
+            written[tree_len - 1 - i] = true;
+        }
+    }
+
+    // Build the tree by hashing pairs of nodes from the leaves up to the root.
+    let first_leaf = tree_len - leaves_len;
+    #[cfg(debug_assertions)]
+    debug_assert!(
+        written[first_leaf..].iter().all(|slot| *slot),
+        "every leaf slot must be written before internal nodes are computed"
+    );
+    let level_nodes = |level: usize| (1 << level) - 1..((2 << level) - 1).min(tree_len);
+
+    // The deepest levels may hold nothing but leaves, and are complete already.
+    for level in (0..=node_depth(tree_len - 1))
+        .rev()
+        .take_while(|level| level_nodes(*level).start >= first_leaf)
+    {
+        on_level(level, &tree[level_nodes(level)]);
+    }
+
+    // The nodes of a level only depend on the level below, so a level is computed at once.
+    for level in (0..=node_depth(tree_len - 1))
+        .rev()
+        .filter(|level| level_nodes(*level).start < first_leaf)
+    {
+        let nodes = level_nodes(level);
+        let internal = nodes.start..nodes.end.min(first_leaf);
+        #[cfg(debug_assertions)]
+        for i in internal.clone() {
+            debug_assert!(
+                written[left_child_index(i)] && written[right_child_index(i)],
+                "node {i} must be computed after its children"
+            );
+            written[i] = true;
+        }
+
+        let (upper, lower) = tree.split_at_mut(nodes.end);
+        hash_level::<H>(
+            &mut upper[internal.clone()],
+            internal.start,
+            lower,
+            nodes.end,
+            order,
+        );
+        on_level(level, &tree[nodes]);
+    }
+
+    tree
+}
+
+/// Computes the internal nodes of one level, the first of which is at `first` in the tree array,
+/// from the level below, whose nodes start at `children_start`. With the `rayon` feature the nodes
+/// are computed in parallel.
+fn hash_level<H: Hasher>(
+    nodes: &mut [B256],
+    first: usize,
+    children: &[B256],
+    children_start: usize,
+    order: HashOrder,
+) {
+    // Leaves are stored in reverse, so the left child of a node is the right one in the array.
+    let hash = |(offset, node): (usize, &mut B256)| {
+        let i = first + offset;
+        *node = order.hash_nodes::<H>(
+            children[right_child_index(i) - children_start],
+            children[left_child_index(i) - children_start],
+        );
+    };
+    #[cfg(feature = "rayon")]
+    nodes.par_iter_mut().enumerate().for_each(hash);
+    #[cfg(not(feature = "rayon"))]
+    nodes.iter_mut().enumerate().for_each(hash);
+    #[cfg(feature = "stats")]
+    crate::stats::record_pair_hashes(nodes.len());
+}
+
+/// Constructs a perfect Merkle tree from a vector of leaf hashes, pairing the unpaired node of every
+/// odd level according to `policy` and hashing pairs according to `order`.
+///
+/// The array has the same heap layout as [`make_merkle_tree`], with every level stored in reverse:
+/// the `q`-th node of a level whose last slot is `level_end` lives at `level_end - q`. Slots past the
+/// nodes of a level (and their filler) stay zeroed and are never part of a proof.
+fn make_padded_merkle_tree<H: Hasher>(
+    leaves: Vec<B256>,
+    policy: OddNodePolicy,
+    order: HashOrder,
+) -> Vec<B256> {
+    if leaves.is_empty() {
+        return Vec::new();
+    }
+    let width = leaves.len().next_power_of_two();
+    let tree_len = 2 * width - 1;
+    let mut tree = vec![B256::default(); tree_len];
+
+    let mut level = leaves;
+    let mut level_end = tree_len - 1;
+    loop {
+        if level.len() > 1 && level.len() % 2 == 1 {
+            let filler = match policy {
+                OddNodePolicy::HashWithZero => B256::ZERO,
+                _ => level[level.len() - 1],
+            };
+            level.push(filler);
+        }
+        for (q, node) in level.iter().enumerate() {
+            tree[level_end - q] = *node;
+        }
+        if level.len() == 1 {
+            break;
+        }
+
+        level = level
+            .chunks(2)
+            .map(|pair| order.hash_nodes::<H>(pair[0], pair[1]))
+            .collect();
+        #[cfg(feature = "stats")]
+        crate::stats::record_pair_hashes(level.len());
+        level_end = (level_end - 1) / 2;
+    }
+
+    tree
+}
+
+/// Generates a Merkle proof for a leaf at a given index.
+///
+/// As in OpenZeppelin's `getProof`, every node on the path has a sibling: an unpaired node is
+/// carried up when the tree is built, so in an array of `2n - 1` nodes every node but the root is
+/// paired. An array where a sibling is missing isn't a tree of this layout, and yields
+/// [`MerkleTreeError::InvalidCheck`] rather than a proof that can't verify.
+fn make_proof(tree: &[B256], index: usize) -> Result<Vec<B256>, MerkleTreeError> {
+    check_leaf_node(tree, index)?;
+
+    let mut proof = Vec::new();
+    let mut current_index = index;
+    while current_index > 0 {
+        let sibling = tree
+            .get(sibling_index(current_index)?)
+            .ok_or(MerkleTreeError::InvalidCheck)?;
+        proof.push(*sibling);
+        current_index = parent_index(current_index);
+    }
+
+    Ok(proof)
+}
+
+/// Processes a Merkle proof to compute the implied root hash, like OpenZeppelin's
+/// `MerkleProof.processProof`.
+///
+/// `leaf` is the leaf hash, e.g. from [`standard_leaf_hash`]. Pairs are hashed sorted, so this only
+/// reproduces the root of trees built with [`HashOrder::Sorted`]; check proofs of positional trees
+/// with [`verify_positional`] or [`verify_with_positions`].
+///
+/// Returns `B256` hash of the implied Merkle root.
+pub fn process_proof(leaf: B256, proof: &[B256]) -> B256 {
+    process_proof_with::<Keccak256Hasher>(leaf, proof)
+}
+
+/// Processes a Merkle proof to compute the implied root hash, hashing pairs with `H`.
+fn process_proof_with<H: Hasher>(leaf: B256, proof: &[B256]) -> B256 {
+    proof.iter().copied().fold(leaf, H::hash_pair)
+}
+
+/// Processes a [`MultiProof`] to compute the implied root hash, like OpenZeppelin's
+/// `processMultiProof`.
+///
+/// Returns [`MerkleTreeError::InvalidMultiProof`] if the proof runs out of nodes, or if nodes are
+/// left over once every flag has been consumed.
+pub fn process_multi_proof(multi_proof: &MultiProof) -> Result<B256, MerkleTreeError> {
+    process_multi_proof_with::<Keccak256Hasher>(multi_proof)
+}
+
+/// Returns the hashes of the leaves a [`MultiProof`] claims to prove, to inspect before trusting
+/// it. See [`StandardMerkleTree::verify_multiproof_covers`] to check them against expected leaves.
+pub fn multiproof_leaves(multiproof: &MultiProof) -> &[B256] {
+    &multiproof.leaves
+}
+
+/// Processes a [`MultiProof`] like [`process_multi_proof`], hashing pairs with `H`.
+fn process_multi_proof_with<H: Hasher>(multi_proof: &MultiProof) -> Result<B256, MerkleTreeError> {
+    if multi_proof.leaves.len() + multi_proof.proof.len() != multi_proof.proof_flags.len() + 1 {
+        return Err(MerkleTreeError::InvalidMultiProof);
+    }
+
+    let mut stack: VecDeque<B256> = multi_proof.leaves.iter().copied().collect();
+    let mut proof = multi_proof.proof.iter().copied();
+    for flag in multi_proof.proof_flags.iter() {
+        let a = stack.pop_front();
+        let b = if *flag {
+            stack.pop_front()
+        } else {
+            proof.next()
+        };
+        match (a, b) {
+            (Some(a), Some(b)) => stack.push_back(H::hash_pair(a, b)),
+            _ => return Err(MerkleTreeError::InvalidMultiProof),
+        }
+    }
+
+    stack
+        .pop_back()
+        .or_else(|| proof.next())
+        .ok_or(MerkleTreeError::InvalidMultiProof)
+}
+
+/// Hashes a pair of `B256` values to compute their parent hash.
+pub(crate) fn hash_pair(left: B256, right: B256) -> B256 {
+    Keccak256Hasher::hash_pair(left, right)
+}
+
+/// Hashes a left and a right node into their parent, `keccak256(left ++ right)`, without sorting.
+fn positional_hash_pair(left: B256, right: B256) -> B256 {
+    Keccak256Hasher::hash_pair_ordered(left, right)
+}
+
+/// (De)serializes leaf values as their Solidity type and `0x`-prefixed ABI encoding, since
+/// [`DynSolValue`] isn't serializable itself.
+#[cfg(feature = "serde")]
+mod serde_values {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use alloy_dyn_abi::{DynSolType, DynSolValue};
+    use alloy_primitives::hex;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        values: &[DynSolValue],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        values
+            .iter()
+            .map(|value| {
+                (
+                    super::sol_type_name(value),
+                    hex::encode_prefixed(value.abi_encode()),
+                )
+            })
+            .collect::<Vec<(String, String)>>()
+            .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<DynSolValue>, D::Error> {
+        Vec::<(String, String)>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(ty, encoded)| {
+                let ty = DynSolType::parse(&ty).map_err(D::Error::custom)?;
+                let encoded = hex::decode(encoded).map_err(D::Error::custom)?;
+                ty.abi_decode(&encoded).map_err(D::Error::custom)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::alloc::string::ToString;
+    use crate::standard_binary_tree::{
+        bloom_maybe_contains, empty_subtree_root, encode_leaf_into, hash_leaves,
+        hash_leaves_with_cache, hash_pair, is_leaf_node, leaf_encoding, leaf_hash_packed_bytes32,
+        make_merkle_tree, make_proof, multiproof_leaves, node_depth, paths_agree,
+        plausible_leaf_count, process_multi_proof, process_proof, proofs_target_same_leaf,
+        rebuild_hash_cost, short_hex, sibling_index, standard_leaf_hash, uniform_root, verify,
+        verify_arity, verify_bound, verify_hash, verify_positional, verify_stored_root,
+        verify_with_chain_id, verify_with_positions, verify_with_scheme, which_root,
+        AggregateCommitment, ByteOrder, ColumnCache, DiagResult, HashOrder, Hasher,
+        Keccak256Hasher, LeafHashMode, LeafScheme, MerkleTreeError, MultiProof, MultiProofVerifier,
+        OddNodePolicy, StandardMerkleTree, TreeOptions, VerifyOutcome,
+    };
+    use alloc::boxed::Box;
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use alloy_dyn_abi::{DynSolType, DynSolValue};
+    use alloy_primitives::{b256, hex::FromHex, keccak256, Address, FixedBytes, B256, I256, U256};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Tests the [`StandardMerkleTree`] with string-type leaves.
+    #[test]
+    fn test_tree_string_type() {
+        let num_leaves = 1000;
+        let mut leaves = Vec::new();
+        for i in 0..num_leaves {
+            leaves.push(DynSolValue::String(i.to_string()));
+        }
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        for leaf in leaves.into_iter() {
+            let proof = tree.get_proof(&leaf).unwrap();
+            let is_valid = tree.verify_proof(&leaf, proof);
+            assert!(is_valid);
+        }
+    }
+
+    /// Tests the `StandardMerkleTree` with bytes32-type leaves.
+    #[test]
+    fn test_tree_bytes32_type() {
+        let mut leaves = Vec::new();
+
+        let leaf = DynSolValue::FixedBytes(
+            FixedBytes::<32>::from_hex(
+                "0x46296bc9cb11408bfa46c5c31a542f12242db2412ee2217b4e8add2bc1927d0b",
+            )
+            .unwrap(),
+            32,
+        );
+
+        leaves.push(leaf);
+
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        for leaf in leaves.into_iter() {
+            let proof = tree.get_proof(&leaf).unwrap();
+            let is_valid = tree.verify_proof(&leaf, proof);
+            assert!(is_valid);
+        }
+    }
+
+    /// Tests that [`StandardMerkleTree::verify_at_index`] binds a proof to the leaf's position.
+    #[test]
+    fn test_verify_at_index() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        let proof = tree.get_proof(&leaves[2]).unwrap();
+        assert!(tree.verify_at_index(&leaves[2], 2, &proof).unwrap());
+        // The same valid proof presented for another position must fail.
+        assert!(!tree.verify_at_index(&leaves[2], 3, &proof).unwrap());
+        assert!(!tree.verify_at_index(&leaves[2], 0, &proof).unwrap());
+        assert!(tree.verify_at_index(&leaves[2], 5, &proof).is_err());
+    }
+
+    /// Tests building a [`StandardMerkleTree`] from ABI-encoded uint leaves.
+    #[test]
+    fn test_tree_of_abi_encoded() {
+        let leaves: Vec<DynSolValue> = (0..10)
+            .map(|i| DynSolValue::Uint(U256::from(i), 256))
+            .collect();
+        let encoded: Vec<Vec<u8>> = leaves.iter().map(|leaf| leaf.abi_encode()).collect();
+
+        let tree = StandardMerkleTree::of_abi_encoded(&encoded, &DynSolType::Uint(256)).unwrap();
+        assert_eq!(
+            tree.root().unwrap(),
+            StandardMerkleTree::of(&leaves).unwrap().root().unwrap()
+        );
+
+        for leaf in leaves.iter() {
+            let proof = tree.get_proof(leaf).unwrap();
+            assert!(tree.verify_proof(leaf, proof));
+        }
+
+        // A truncated buffer reports the index of the offending leaf.
+        let mut malformed = encoded.clone();
+        malformed[3].truncate(16);
+        assert!(matches!(
+            StandardMerkleTree::of_abi_encoded(&malformed, &DynSolType::Uint(256)),
+            Err(MerkleTreeError::InvalidAbiEncoding(3))
+        ));
+
+        // Decoding to a type the tree can't hash is an error rather than a panic.
+        let bytes = [DynSolValue::Bytes(vec![1]).abi_encode()];
+        assert!(matches!(
+            StandardMerkleTree::of_abi_encoded(&bytes, &DynSolType::Bytes),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+    }
+
+    /// Tests the leaf pairing reported by [`StandardMerkleTree::leaf_pairs`] for an odd tree.
+    #[test]
+    fn test_leaf_pairs() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        assert_eq!(
+            tree.leaf_pairs(),
+            [
+                (0, Some(1)),
+                (1, Some(0)),
+                (2, Some(3)),
+                (3, Some(2)),
+                (4, None)
+            ]
+        );
+    }
+
+    /// Tests that proofs verify against a bound root only under the matching metadata.
+    #[test]
+    fn test_bound_root() {
+        let leaves: Vec<DynSolValue> = (0..6).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        let round_1 = b"round-1";
+        let bound_root = tree.bound_root(round_1).unwrap();
+        assert_ne!(bound_root, tree.root().unwrap());
+        assert_ne!(bound_root, tree.bound_root(b"round-2").unwrap());
+
+        for leaf in leaves.iter() {
+            let proof = tree.get_proof(leaf).unwrap();
+            let leaf_hash = standard_leaf_hash(leaf).unwrap();
+            assert!(verify_bound(
+                bound_root,
+                leaf_hash,
+                &proof,
+                tree.root().unwrap(),
+                round_1
+            ));
+            assert!(!verify_bound(
+                bound_root,
+                leaf_hash,
+                &proof,
+                tree.root().unwrap(),
+                b"round-2"
+            ));
+        }
+    }
+
+    /// Tests that [`StandardMerkleTree::iter_proofs`] yields a valid proof for every leaf.
+    #[test]
+    fn test_iter_proofs() {
+        let leaves: Vec<DynSolValue> = (0..7).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        let proofs = tree.iter_proofs();
+        assert_eq!(proofs.len(), leaves.len());
+
+        let mut count = 0;
+        for (leaf_index, proof) in proofs {
+            assert_eq!(leaf_index, count);
+            assert!(tree.verify_proof(&leaves[leaf_index], proof));
+            count += 1;
+        }
+        assert_eq!(count, leaves.len());
+    }
+
+    /// Tests that a tree of identical leaves keeps every position provable.
+    #[test]
+    fn test_tree_all_equal_leaves() {
+        let leaf = DynSolValue::String("same".to_string());
+        let leaves = vec![leaf.clone(); 8];
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        assert_eq!(tree.tree.len(), 15);
+        assert!(tree.all_leaves_equal());
+        assert_eq!(
+            tree.leaf_positions(&leaf).unwrap(),
+            (0..8).collect::<Vec<_>>()
+        );
+
+        for leaf_index in 0..8 {
+            let tree_index = tree.leaf_index_to_tree_index(leaf_index).unwrap();
+            let proof = make_proof(&tree.tree, tree_index).unwrap();
+            assert!(tree.verify_at_index(&leaf, leaf_index, &proof).unwrap());
+        }
+
+        let distinct =
+            StandardMerkleTree::of(&[leaf, DynSolValue::String("other".to_string())]).unwrap();
+        assert!(!distinct.all_leaves_equal());
+    }
+
+    /// Tests verifying proofs produced by Murky against a captured fixture.
+    ///
+    /// The fixture was computed with Murky's `hashLevel`/`getProof` algorithm over the leaves
+    /// `keccak256(abi.encodePacked(uint256(i)))` for `i` in `1..=5`. Murky pads odd levels with
+    /// `bytes32(0)`, which shows up as zero elements in the proof of the last leaf.
+    #[test]
+    fn test_verify_murky_proof() {
+        let root = b256!("95ef5364f1e661863242bf0049fc37d7baeca20c5188538b5a58ec8d772aa147");
+        let fixtures = [
+            (
+                3,
+                vec![
+                    b256!("8a35acfbc15ff81a39ae7d344fd709f28e8600b4aa8c65c6b64bfe7fe36bd19b"),
+                    b256!("2a171b5bcd1449348c3e09a5424946b5e6d6f5471221941d585131d673952ee4"),
+                    b256!("2ec8ac573bb9eec168d90d5e4017c8bf36828d1a6262dc493723a7752a8e29da"),
+                ],
+            ),
+            (
+                5,
+                vec![
+                    B256::ZERO,
+                    B256::ZERO,
+                    b256!("9cb86f87624f55e4956a62a87acdd72769cdb21f746c27d345ef90343a9b2316"),
+                ],
+            ),
+        ];
+
+        for (value, proof) in fixtures {
+            let leaf = DynSolValue::Uint(U256::from(value), 256);
+            assert!(verify_with_scheme(root, &leaf, &proof, LeafScheme::Murky));
+            assert!(!verify_with_scheme(
+                root,
+                &leaf,
+                &proof,
+                LeafScheme::Standard
+            ));
+        }
+    }
+
+    /// Tests [`empty_subtree_root`] against hand-computed roots of all-empty subtrees.
+    #[test]
+    fn test_empty_subtree_root() {
+        let empty_leaf = keccak256(b"empty");
+        let hash_self = |node: B256| keccak256([node.as_slice(), node.as_slice()].concat());
+
+        let depth_1 = hash_self(empty_leaf);
+        let depth_2 = hash_self(depth_1);
+        let depth_3 = hash_self(depth_2);
+
+        assert_eq!(empty_subtree_root(0, empty_leaf), empty_leaf);
+        assert_eq!(empty_subtree_root(1, empty_leaf), depth_1);
+        assert_eq!(empty_subtree_root(2, empty_leaf), depth_2);
+        assert_eq!(empty_subtree_root(3, empty_leaf), depth_3);
+    }
+
+    /// Tests the round-trip between leaf indices and tree indices.
+    #[test]
+    fn test_leaf_index_conversion() {
+        let leaves: Vec<DynSolValue> = (0..6).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let tree_index = tree.leaf_index_to_tree_index(leaf_index).unwrap();
+            assert_eq!(
+                tree.tree_index_to_leaf_index(tree_index).unwrap(),
+                leaf_index
+            );
+            assert_eq!(tree.tree[tree_index], standard_leaf_hash(leaf).unwrap());
+        }
+
+        assert!(matches!(
+            tree.leaf_index_to_tree_index(leaves.len()),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+        // The root is an internal node, not a leaf.
+        assert!(matches!(
+            tree.tree_index_to_leaf_index(0),
+            Err(MerkleTreeError::InvalidCheck)
+        ));
+    }
+
+    /// Tests that a proof built for one chain fails verification for another.
+    #[test]
+    fn test_tree_chain_id() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::builder()
+            .chain_id(1)
+            .build(&leaves)
+            .unwrap();
+        assert_eq!(tree.options().chain_id, Some(1));
+        assert_ne!(
+            tree.root().unwrap(),
+            StandardMerkleTree::of(&leaves).unwrap().root().unwrap()
+        );
+
+        for leaf in leaves.iter() {
+            let proof = tree.get_proof(leaf).unwrap();
+            assert!(tree.verify_proof(leaf, proof.clone()));
+            assert!(verify_with_chain_id(tree.root().unwrap(), 1, leaf, &proof));
+            assert!(!verify_with_chain_id(
+                tree.root().unwrap(),
+                10,
+                leaf,
+                &proof
+            ));
+        }
+    }
+
+    /// Mirrors OpenZeppelin's `MerkleProof.processProof`: each step hashes the sorted pair with
+    /// `_efficientHash`, i.e. `keccak256` over the two words stored at scratch space `0x00..0x40`.
+    fn solidity_process_proof(leaf: B256, proof: &[B256]) -> B256 {
+        let efficient_hash = |a: B256, b: B256| {
+            let mut scratch = [0u8; 64];
+            scratch[..32].copy_from_slice(a.as_slice());
+            scratch[32..].copy_from_slice(b.as_slice());
+            keccak256(scratch)
+        };
+
+        let mut computed_hash = leaf;
+        for proof_element in proof {
+            computed_hash = if computed_hash < *proof_element {
+                efficient_hash(computed_hash, *proof_element)
+            } else {
+                efficient_hash(*proof_element, computed_hash)
+            };
+        }
+        computed_hash
+    }
+
+    /// Tests that [`process_proof`] agrees with OpenZeppelin's Solidity `processProof`.
+    #[test]
+    fn test_process_proof_matches_solidity() {
+        // Fixture: the tree of the strings "a", "b" and "c", and the proof of "a".
+        let leaves = ["a", "b", "c"].map(|leaf| DynSolValue::String(leaf.to_string()));
+        let leaf_a = b256!("e882a0dd840cc7b99d5f9ff05216be547c7b7d84a61d474353c4d9cb90cb2cdd");
+        let proof_a = [
+            b256!("5e56c48f79378189dc48d49b24d2d1328e3279d4491b42f5f49825c17b416fcd"),
+            b256!("05098bd04354a2fc3a93037c505c12d75216a5b8e8b2700782de9617154c2bca"),
+        ];
+        let root = b256!("ec5728bad90d3185989eddaae1e83be5f3e03ad9f48bd743df58dad4b7625505");
+
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        assert_eq!(tree.root().unwrap(), root);
+        assert_eq!(standard_leaf_hash(&leaves[0]).unwrap(), leaf_a);
+        assert_eq!(tree.get_proof(&leaves[0]).unwrap(), proof_a);
+        assert_eq!(solidity_process_proof(leaf_a, &proof_a), root);
+        assert_eq!(process_proof(leaf_a, &proof_a), root);
+
+        let leaves: Vec<DynSolValue> = (0..10)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        for leaf in leaves.iter() {
+            let leaf_hash = standard_leaf_hash(leaf).unwrap();
+            let proof = tree.get_proof(leaf).unwrap();
+            assert_eq!(
+                solidity_process_proof(leaf_hash, &proof),
+                process_proof(leaf_hash, &proof)
+            );
+            assert_eq!(
+                solidity_process_proof(leaf_hash, &proof),
+                tree.root().unwrap()
+            );
+        }
+    }
+
+    /// Tests building a [`StandardMerkleTree`] from a stream of leaves.
+    #[cfg(feature = "futures")]
+    #[test]
+    fn test_tree_of_stream() {
+        use futures::{executor::block_on, stream};
+
+        let leaves: Vec<DynSolValue> = (0..10)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect();
+        let tree = block_on(StandardMerkleTree::of_stream(stream::iter(leaves.clone()))).unwrap();
+        assert_eq!(
+            tree.root().unwrap(),
+            StandardMerkleTree::of(&leaves).unwrap().root().unwrap()
+        );
+
+        for leaf in leaves.iter() {
+            let proof = tree.get_proof(leaf).unwrap();
+            assert!(tree.verify_proof(leaf, proof));
+        }
+
+        let empty = block_on(StandardMerkleTree::of_stream(stream::empty())).unwrap();
+        assert_eq!(empty.leaf_count(), 0);
+
+        let unsupported = stream::iter([DynSolValue::Bytes(vec![1])]);
+        assert!(matches!(
+            block_on(StandardMerkleTree::of_stream(unsupported)),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+    }
+
+    /// A keccak256 [`rs_merkle::Hasher`] using `rs_merkle`'s default left-right pair hashing.
+    #[derive(Clone)]
+    struct RsMerkleKeccak;
+
+    impl rs_merkle::Hasher for RsMerkleKeccak {
+        type Hash = [u8; 32];
+
+        fn hash(data: &[u8]) -> [u8; 32] {
+            keccak256(data).0
+        }
+    }
+
+    /// A keccak256 [`rs_merkle::Hasher`] sorting each pair before hashing, like this module does.
+    #[derive(Clone)]
+    struct RsMerkleSortedKeccak;
+
+    impl rs_merkle::Hasher for RsMerkleSortedKeccak {
+        type Hash = [u8; 32];
+
+        fn hash(data: &[u8]) -> [u8; 32] {
+            keccak256(data).0
+        }
+
+        fn concat_and_hash(left: &[u8; 32], right: Option<&[u8; 32]>) -> [u8; 32] {
+            match right {
+                Some(right) => {
+                    let (first, second) = if left <= right {
+                        (left, right)
+                    } else {
+                        (right, left)
+                    };
+                    keccak256([first.as_slice(), second.as_slice()].concat()).0
+                }
+                None => *left,
+            }
+        }
+    }
+
+    /// Tests how the roots of [`StandardMerkleTree`] compare to those of `rs_merkle`.
+    #[test]
+    fn test_root_matches_rs_merkle() {
+        let rs_merkle_root = |leaves: &[DynSolValue]| {
+            let leaf_hashes: Vec<[u8; 32]> = leaves
+                .iter()
+                .map(|v| standard_leaf_hash(v).unwrap().0)
+                .collect();
+            rs_merkle::MerkleTree::<RsMerkleSortedKeccak>::from_leaves(&leaf_hashes)
+                .root()
+                .map(B256::from)
+                .unwrap()
+        };
+        let leaves_of = |count: usize| -> Vec<DynSolValue> {
+            (0..count)
+                .map(|i| DynSolValue::String(i.to_string()))
+                .collect()
+        };
+
+        // With the same leaf hashes and sorted pairs, the roots match whenever the shapes agree.
+        for count in [1, 2, 3, 4, 8, 16] {
+            let leaves = leaves_of(count);
+            assert_eq!(
+                StandardMerkleTree::of(&leaves).unwrap().root().unwrap(),
+                rs_merkle_root(&leaves)
+            );
+        }
+        // With five leaves, `rs_merkle` promotes the last leaf where this tree is complete.
+        let leaves = leaves_of(5);
+        assert_ne!(
+            StandardMerkleTree::of(&leaves).unwrap().root().unwrap(),
+            rs_merkle_root(&leaves)
+        );
+
+        // With single-hashed leaves and left-right pairs, the roots differ even for four leaves.
+        let leaves = leaves_of(4);
+        let leaf_hashes: Vec<[u8; 32]> = leaves
+            .iter()
+            .map(|leaf| keccak256(leaf.as_str().unwrap()).0)
+            .collect();
+        let root = rs_merkle::MerkleTree::<RsMerkleKeccak>::from_leaves(&leaf_hashes)
+            .root()
+            .map(B256::from)
+            .unwrap();
+        assert_ne!(
+            StandardMerkleTree::of(&leaves).unwrap().root().unwrap(),
+            root
+        );
+    }
+
+    /// Tests that the `rs_merkle` preset reproduces `rs_merkle`'s roots for power-of-two leaf counts,
+    /// and only for those.
+    #[test]
+    fn test_rs_merkle_compatible() {
+        let rs_merkle_root = |leaf_hashes: &[B256]| {
+            let leaf_hashes = leaf_hashes
+                .iter()
+                .map(|hash| hash.0)
+                .collect::<Vec<[u8; 32]>>();
+            rs_merkle::MerkleTree::<RsMerkleKeccak>::from_leaves(&leaf_hashes)
+                .root()
+                .map(B256::from)
+        };
+        let words = ["a", "b", "c", "d", "e", "f", "g", "h"];
+
+        // `rs_merkle`'s root of the keccak256 of "a" to "d", computed by hand.
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(keccak256);
+        let pair = Keccak256Hasher::hash_pair_ordered;
+        let root = pair(pair(a, b), pair(c, d));
+        assert_eq!(rs_merkle_root(&[a, b, c, d]), Some(root));
+
+        for count in 1..=words.len() {
+            let leaf_hashes = words[..count].iter().map(keccak256).collect::<Vec<B256>>();
+            let leaves = leaf_hashes
+                .iter()
+                .map(|hash| DynSolValue::FixedBytes(*hash, 32))
+                .collect::<Vec<DynSolValue>>();
+            let tree = StandardMerkleTree::builder()
+                .rs_merkle_compatible()
+                .build(&leaves)
+                .unwrap();
+
+            // Leaves hashed once from their strings give the same tree.
+            let strings = words[..count]
+                .iter()
+                .map(|word| DynSolValue::String(word.to_string()))
+                .collect::<Vec<DynSolValue>>();
+            let single = StandardMerkleTree::builder()
+                .rs_merkle_compatible()
+                .leaf_hash_mode(LeafHashMode::Single)
+                .build(&strings)
+                .unwrap();
+            assert_eq!(single.root(), tree.root());
+
+            if count.is_power_of_two() {
+                assert_eq!(tree.root(), rs_merkle_root(&leaf_hashes));
+            } else {
+                assert_ne!(tree.root(), rs_merkle_root(&leaf_hashes));
+            }
+        }
+    }
+
+    /// Tests that each odd-node policy yields its own root for a 3-leaf tree.
+    #[test]
+    fn test_odd_node_policy_roots() {
+        let leaves: Vec<DynSolValue> = (0..3).map(|i| DynSolValue::String(i.to_string())).collect();
+        let [l0, l1, l2] = [0, 1, 2].map(|i| standard_leaf_hash(&leaves[i]).unwrap());
+        let build = |policy| {
+            StandardMerkleTree::builder()
+                .odd_node_policy(policy)
+                .build(&leaves)
+                .unwrap()
+        };
+
+        let promote = build(OddNodePolicy::Promote);
+        let with_self = build(OddNodePolicy::HashWithSelf);
+        let with_zero = build(OddNodePolicy::HashWithZero);
+
+        assert_eq!(
+            promote.root().unwrap(),
+            StandardMerkleTree::of(&leaves).unwrap().root().unwrap()
+        );
+        assert_eq!(
+            with_self.root().unwrap(),
+            hash_pair(hash_pair(l0, l1), hash_pair(l2, l2))
+        );
+        assert_eq!(
+            with_zero.root().unwrap(),
+            hash_pair(hash_pair(l0, l1), hash_pair(l2, B256::ZERO))
+        );
+        assert_ne!(promote.root().unwrap(), with_self.root().unwrap());
+        assert_ne!(promote.root().unwrap(), with_zero.root().unwrap());
+        assert_ne!(with_self.root().unwrap(), with_zero.root().unwrap());
+        assert_eq!(
+            with_self.options().odd_node_policy,
+            OddNodePolicy::HashWithSelf
+        );
+    }
+
+    /// Tests that every leaf of a padded tree has a valid proof, and that filler slots aren't leaves.
+    #[test]
+    fn test_odd_node_policy_proofs() {
+        for policy in [OddNodePolicy::HashWithSelf, OddNodePolicy::HashWithZero] {
+            for count in 1..=9 {
+                let leaves: Vec<DynSolValue> = (0..count)
+                    .map(|i| DynSolValue::String(i.to_string()))
+                    .collect();
+                let tree = StandardMerkleTree::builder()
+                    .odd_node_policy(policy)
+                    .build(&leaves)
+                    .unwrap();
+
+                assert_eq!(tree.iter_proofs().len(), count);
+                for (leaf_index, proof) in tree.iter_proofs() {
+                    assert!(tree.verify_proof(&leaves[leaf_index], proof.clone()));
+                    assert!(tree
+                        .verify_at_index(&leaves[leaf_index], leaf_index, &proof)
+                        .unwrap());
+                }
+                if count < count.next_power_of_two() {
+                    let first_unused = tree.nodes().len() - 1 - count;
+                    assert!(matches!(
+                        tree.tree_index_to_leaf_index(first_unused),
+                        Err(MerkleTreeError::InvalidCheck)
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Tests that tagged proof levels are contiguous and start at the depth of the leaf.
+    #[test]
+    fn test_get_proof_tagged() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        assert_eq!(tree.depth(), 3);
+
+        for leaf in leaves.iter() {
+            let tagged = tree.get_proof_tagged(leaf).unwrap();
+            let proof: Vec<B256> = tagged.iter().map(|(_, sibling)| *sibling).collect();
+            assert_eq!(proof, tree.get_proof(leaf).unwrap());
+
+            let levels: Vec<usize> = tagged.iter().map(|(level, _)| *level).collect();
+            assert!(levels[0] <= tree.depth());
+            assert_eq!(levels, (1..=levels[0]).rev().collect::<Vec<_>>());
+        }
+        // The first leaves sit on the deepest level.
+        assert_eq!(
+            tree.get_proof_tagged(&leaves[0]).unwrap()[0].0,
+            tree.depth()
+        );
+    }
+
+    /// Tests that an aggregate commitment expands and verifies against the root, and only the root.
+    #[test]
+    fn test_aggregate_proofs() {
+        let leaves: Vec<DynSolValue> = (0..6).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        let aggregate = tree.aggregate_proofs(&leaves[1..4]).unwrap();
+        assert_eq!(aggregate.entries.len(), 3);
+        for ((leaf_hash, proof), leaf) in aggregate.entries.iter().zip(&leaves[1..4]) {
+            assert_eq!(*leaf_hash, standard_leaf_hash(leaf).unwrap());
+            assert_eq!(*proof, tree.get_proof(leaf).unwrap());
+        }
+        assert!(aggregate.expand_and_verify(tree.root().unwrap()));
+        assert!(!aggregate.expand_and_verify(B256::ZERO));
+
+        // A single entry is `leaf_hash ++ uint256(proof.len()) ++ proof`.
+        let single = tree.aggregate_proofs(&leaves[..1]).unwrap();
+        let (leaf_hash, proof) = &single.entries[0];
+        let mut layout = leaf_hash.to_vec();
+        layout.extend_from_slice(&U256::from(proof.len()).to_be_bytes::<32>());
+        proof
+            .iter()
+            .for_each(|sibling| layout.extend_from_slice(sibling.as_slice()));
+        assert_eq!(single.commitment, keccak256(&layout));
+        assert_eq!(
+            AggregateCommitment::commit(&single.entries),
+            single.commitment
+        );
+
+        // Reordering the entries changes the commitment.
+        let mut reordered = aggregate.clone();
+        reordered.entries.swap(0, 1);
+        assert!(!reordered.expand_and_verify(tree.root().unwrap()));
+
+        assert!(matches!(
+            tree.aggregate_proofs(&[DynSolValue::String("missing".to_string())]),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+    }
+
+    /// Tests that `which_root` finds the one candidate root a proof is valid for.
+    #[test]
+    fn test_which_root() {
+        let leaves: Vec<DynSolValue> = (0..4).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let older = StandardMerkleTree::of(&leaves[..3]).unwrap();
+        let other = StandardMerkleTree::of(&[DynSolValue::String("other".to_string())]).unwrap();
+
+        let leaf_hash = standard_leaf_hash(&leaves[3]).unwrap();
+        let proof = tree.get_proof(&leaves[3]).unwrap();
+        let candidates = [
+            older.root().unwrap(),
+            other.root().unwrap(),
+            tree.root().unwrap(),
+            B256::ZERO,
+        ];
+        assert_eq!(which_root(leaf_hash, &proof, &candidates), Some(2));
+        assert_eq!(which_root(leaf_hash, &proof, &candidates[..2]), None);
+        assert_eq!(which_root(leaf_hash, &proof, &[]), None);
+    }
+
+    /// Tests that equal values with different nonces are distinct, separately provable leaves.
+    #[test]
+    fn test_of_with_nonce() {
+        let claim = DynSolValue::String("alice".to_string());
+        let other = DynSolValue::String("bob".to_string());
+        let tree = StandardMerkleTree::of_with_nonce(&[
+            (claim.clone(), 0),
+            (other.clone(), 0),
+            (claim.clone(), 1),
+        ])
+        .unwrap();
+        let bound = |value: &DynSolValue, nonce: u64| {
+            DynSolValue::Tuple(vec![
+                value.clone(),
+                DynSolValue::Uint(U256::from(nonce), 256),
+            ])
+        };
+        assert_eq!(tree.leaf_positions(&bound(&claim, 0)).unwrap(), vec![0]);
+        assert_eq!(tree.leaf_positions(&bound(&claim, 1)).unwrap(), vec![2]);
+
+        let first = tree.get_proof_with_nonce(&claim, 0).unwrap();
+        let second = tree.get_proof_with_nonce(&claim, 1).unwrap();
+        assert_ne!(first, second);
+        assert!(tree.verify_proof_with_nonce(&claim, 0, &first));
+        assert!(tree.verify_proof_with_nonce(&claim, 1, &second));
+        assert!(tree.verify_proof_with_nonce(
+            &other,
+            0,
+            &tree.get_proof_with_nonce(&other, 0).unwrap()
+        ));
+
+        // The nonce must match.
+        assert!(!tree.verify_proof_with_nonce(&claim, 1, &first));
+        assert!(!tree.verify_proof(&claim, first));
+        assert!(matches!(
+            tree.get_proof_with_nonce(&claim, 2),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+
+        // The stored values keep their nonces, so every method hashes them the same way.
+        for (leaf_index, value) in tree.values().iter().enumerate() {
+            let proof = tree.get_proof_by_index(leaf_index).unwrap();
+            assert!(tree.verify_proof(value, proof));
+        }
+        assert_eq!(tree.verify_leaf_consistency(), Ok(()));
+        assert_eq!(
+            StandardMerkleTree::load(tree.dump()).unwrap().root(),
+            tree.root()
+        );
+
+        // A static value is followed by the nonce as a `uint256` word.
+        let amount = DynSolValue::Uint(U256::from(7), 256);
+        let word = |n: u64| U256::from(n).to_be_bytes::<32>();
+        let single = StandardMerkleTree::of_with_nonce(&[(amount, 3)]).unwrap();
+        assert_eq!(
+            single.root(),
+            Some(keccak256(keccak256([word(7), word(3)].concat())))
+        );
+        let mut updated = tree.clone();
+        updated.update_leaf(1, &bound(&other, 5)).unwrap();
+        assert!(updated.verify_proof_with_nonce(
+            &other,
+            5,
+            &updated.get_proof_by_index(1).unwrap()
+        ));
+    }
+
+    /// Tests a transaction tree against a hand-computed block-style root.
+    #[test]
+    fn test_of_tx_hashes() {
+        let hashes: Vec<B256> = (0u8..5).map(|i| keccak256([i])).collect();
+        let cat = |left: B256, right: B256| keccak256([left.as_slice(), right.as_slice()].concat());
+
+        assert_eq!(
+            StandardMerkleTree::of_tx_hashes(&hashes[..1])
+                .root()
+                .unwrap(),
+            hashes[0]
+        );
+
+        let [a, b, c, d, e] = [0, 1, 2, 3, 4].map(|i| hashes[i]);
+        let abcd = cat(cat(a, b), cat(c, d));
+        let eeee = cat(cat(e, e), cat(e, e));
+        let tree = StandardMerkleTree::of_tx_hashes(&hashes);
+        assert_eq!(tree.root().unwrap(), cat(abcd, eeee));
+        assert_eq!(
+            StandardMerkleTree::of_tx_hashes(&hashes[..3])
+                .root()
+                .unwrap(),
+            cat(cat(a, b), cat(c, c))
+        );
+
+        // The order of the hashes matters.
+        assert_ne!(
+            StandardMerkleTree::of_tx_hashes(&[b, a]).root().unwrap(),
+            StandardMerkleTree::of_tx_hashes(&[a, b]).root().unwrap()
+        );
+
+        for (leaf_index, proof) in tree.iter_proofs() {
+            let leaf = DynSolValue::FixedBytes(hashes[leaf_index], 32);
+            assert!(tree.verify_proof(&leaf, proof.clone()));
+            assert!(tree.verify_at_index(&leaf, leaf_index, &proof).unwrap());
+            assert!(verify_positional(
+                tree.root().unwrap(),
+                hashes[leaf_index],
+                leaf_index,
+                &proof
+            ));
+            // Claiming the leaf is in the other half of the tree flips the top-level order.
+            assert!(!verify_positional(
+                tree.root().unwrap(),
+                hashes[leaf_index],
+                leaf_index ^ 4,
+                &proof
+            ));
+        }
+    }
+
+    /// Tests `leaves_are_sorted` and the `require_sorted` builder option.
+    #[test]
+    fn test_leaves_are_sorted() {
+        let mut leaves: Vec<DynSolValue> =
+            (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let unsorted = StandardMerkleTree::of(&leaves).unwrap();
+        assert!(!unsorted.leaves_are_sorted());
+        assert!(matches!(
+            StandardMerkleTree::builder()
+                .require_sorted()
+                .build(&leaves),
+            Err(MerkleTreeError::UnsortedLeaves)
+        ));
+
+        leaves.sort_by_key(|leaf| standard_leaf_hash(leaf).unwrap());
+        assert!(StandardMerkleTree::of(&leaves).unwrap().leaves_are_sorted());
+        let sorted = StandardMerkleTree::builder()
+            .require_sorted()
+            .build(&leaves)
+            .unwrap();
+        assert!(sorted.leaves_are_sorted());
+        assert!(StandardMerkleTree::of(&leaves[..1])
+            .unwrap()
+            .leaves_are_sorted());
+    }
+
+    /// Tests that the ABI blob decodes back to the root and proof.
+    #[test]
+    fn test_get_proof_abi_blob() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        let blob = tree.get_proof_abi_blob(&leaves[2]).unwrap();
+        let ty = DynSolType::Tuple(vec![
+            DynSolType::FixedBytes(32),
+            DynSolType::Array(Box::new(DynSolType::FixedBytes(32))),
+        ]);
+        let decoded = ty.abi_decode_params(&blob).unwrap();
+        let DynSolValue::Tuple(fields) = decoded else {
+            panic!("expected a tuple");
+        };
+        assert_eq!(
+            fields[0].as_fixed_bytes(),
+            Some((tree.root().unwrap().as_slice(), 32))
+        );
+        let proof: Vec<B256> = fields[1]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|sibling| B256::from_slice(sibling.as_fixed_bytes().unwrap().0))
+            .collect();
+        assert_eq!(proof, tree.get_proof(&leaves[2]).unwrap());
+    }
+
+    /// Tests that the previewed root of a leaf change matches the root after `update_leaf`.
+    #[test]
+    fn test_root_if_leaf_changed() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let new_value = DynSolValue::String("new".to_string());
+
+        let trees = [
+            StandardMerkleTree::of(&leaves).unwrap(),
+            StandardMerkleTree::builder()
+                .odd_node_policy(OddNodePolicy::HashWithSelf)
+                .build(&leaves)
+                .unwrap(),
+            StandardMerkleTree::of_tx_hashes(&[keccak256("a"), keccak256("b"), keccak256("c")]),
+        ];
+        for mut tree in trees {
+            let last = tree.iter_proofs().len() - 1;
+            for leaf_index in [0, last] {
+                let value = match tree.options().leaf_hash_mode {
+                    LeafHashMode::Raw => DynSolValue::FixedBytes(keccak256("new"), 32),
+                    LeafHashMode::Double | LeafHashMode::Single => new_value.clone(),
+                };
+                let original_root = tree.root().unwrap();
+                let preview = tree.root_if_leaf_changed(leaf_index, &value).unwrap();
+                assert_eq!(tree.root().unwrap(), original_root);
+                assert_ne!(preview, original_root);
+
+                tree.update_leaf(leaf_index, &value).unwrap();
+                assert_eq!(tree.root().unwrap(), preview);
+                assert!(tree.leaf_positions(&value).unwrap().contains(&leaf_index));
+                let (_, proof) = tree.iter_proofs().nth(leaf_index).unwrap();
+                assert!(tree.verify_at_index(&value, leaf_index, &proof).unwrap());
+            }
+        }
+
+        // Updating must agree with building the changed leaves from scratch.
+        let mut tree = StandardMerkleTree::of(&leaves).unwrap();
+        tree.update_leaf(3, &new_value).unwrap();
+        let mut changed = leaves.clone();
+        changed[3] = new_value.clone();
+        assert_eq!(
+            tree.root().unwrap(),
+            StandardMerkleTree::of(&changed).unwrap().root().unwrap()
+        );
+        assert!(matches!(
+            tree.leaf_positions(&leaves[3]),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+        assert!(matches!(
+            tree.root_if_leaf_changed(5, &new_value),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+    }
+
+    /// Tests a packed `bytes32` leaf against one computed with Solidity's `abi.encodePacked`.
+    #[test]
+    fn test_packed_bytes32_fixture() {
+        let parts = [
+            B256::repeat_byte(0xaa),
+            B256::with_last_byte(42),
+            B256::repeat_byte(0xff),
+        ];
+        // keccak256(abi.encodePacked(bytes32(0xaa..aa), bytes32(uint256(42)), bytes32(0xff..ff)))
+        let packed_hash = b256!("be606b488f89435976ba92606e0d3c311fe890c980638c79d50386c75c6dba92");
+        assert_eq!(leaf_hash_packed_bytes32(&parts), keccak256(packed_hash));
+        assert_eq!(
+            leaf_hash_packed_bytes32(&parts),
+            b256!("8f299a36609032d19e1ac11d77aadeaba43fdf1520da69c923c0c695a6342442")
+        );
+    }
+
+    /// Tests packed `bytes32` leaves against alloy's `abi.encodePacked` encoder, and that their
+    /// stored values hash to their leaves.
+    #[test]
+    fn test_packed_bytes32() {
+        let leaves: Vec<Vec<B256>> = (0u8..3)
+            .map(|i| (0u8..3).map(|j| keccak256([i, j])).collect())
+            .collect();
+        let tree = StandardMerkleTree::of_packed_bytes32(&leaves);
+
+        for parts in leaves.iter() {
+            let packed = DynSolValue::Tuple(
+                parts
+                    .iter()
+                    .map(|part| DynSolValue::FixedBytes(*part, 32))
+                    .collect(),
+            )
+            .abi_encode_packed();
+            assert_eq!(packed.len(), 96);
+            assert_eq!(
+                leaf_hash_packed_bytes32(parts),
+                keccak256(keccak256(packed))
+            );
+
+            let proof = tree.get_proof_packed_bytes32(parts).unwrap();
+            assert!(tree.verify_proof_packed_bytes32(parts, &proof));
+            assert!(!tree.verify_proof_packed_bytes32(&parts[..2], &proof));
+        }
+        assert!(matches!(
+            tree.get_proof_packed_bytes32(&[B256::ZERO]),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+
+        for (leaf_index, value) in tree.values().iter().enumerate() {
+            let proof = tree.get_proof_by_index(leaf_index).unwrap();
+            assert!(tree.verify_proof(value, proof));
+        }
+        assert_eq!(tree.verify_leaf_consistency(), Ok(()));
+        let tuples = leaves
+            .iter()
+            .map(|parts| {
+                parts
+                    .iter()
+                    .map(|part| DynSolValue::FixedBytes(*part, 32))
+                    .collect()
+            })
+            .collect::<Vec<Vec<DynSolValue>>>();
+        assert_eq!(
+            tree.leaf_set_fingerprint(),
+            StandardMerkleTree::of_multi(&tuples)
+                .unwrap()
+                .leaf_set_fingerprint()
+        );
+        let loaded = StandardMerkleTree::load(tree.dump()).unwrap();
+        assert_eq!(loaded.root(), tree.root());
+        assert!(
+            loaded.verify_proof_packed_bytes32(&leaves[1], &tree.get_proof_by_index(1).unwrap())
+        );
+
+        let mut updated = tree.clone();
+        let new_parts = [B256::ZERO; 3];
+        let new_leaf = DynSolValue::Tuple(vec![DynSolValue::FixedBytes(B256::ZERO, 32); 3]);
+        updated.update_leaf(2, &new_leaf).unwrap();
+        let proof = updated.get_proof_packed_bytes32(&new_parts).unwrap();
+        assert!(updated.verify_proof_packed_bytes32(&new_parts, &proof));
+        assert_eq!(updated.verify_leaf_consistency(), Ok(()));
+    }
+
+    /// Tests that `shrink_to_fit` releases over-reserved capacity.
+    #[test]
+    fn test_shrink_to_fit() {
+        let leaves: Vec<DynSolValue> = (0..3).map(|i| DynSolValue::String(i.to_string())).collect();
+        let built = StandardMerkleTree::of(&leaves).unwrap();
+
+        let mut nodes = Vec::with_capacity(1024);
+        nodes.extend_from_slice(built.nodes());
+        let values = leaves
+            .iter()
+            .enumerate()
+            .map(|(leaf_index, leaf)| (leaf, built.leaf_index_to_tree_index(leaf_index).unwrap()))
+            .collect();
+        let mut tree = StandardMerkleTree::new(nodes, values).unwrap();
+        tree.tree_values.reserve(1024);
+        let (tree_capacity, values_capacity) = (tree.tree.capacity(), tree.tree_values.capacity());
+
+        tree.shrink_to_fit();
+        assert!(tree.tree.capacity() < tree_capacity);
+        assert_eq!(tree.tree.capacity(), tree.tree.len());
+        assert!(tree.tree_values.capacity() < values_capacity);
+        assert_eq!(tree.root().unwrap(), built.root().unwrap());
+        assert_eq!(
+            tree.get_proof(&leaves[1]).unwrap(),
+            built.get_proof(&leaves[1]).unwrap()
+        );
+    }
+
+    /// Tests that `verify_diagnostic` reports the level of a corrupted proof element.
+    #[test]
+    fn test_verify_diagnostic() {
+        let leaves: Vec<DynSolValue> = (0..8).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let proof = tree.get_proof(&leaves[5]).unwrap();
+        assert_eq!(proof.len(), 3);
+
+        assert_eq!(
+            tree.verify_diagnostic(&leaves[5], &proof).unwrap(),
+            DiagResult {
+                verified: true,
+                last_matched_level: 3
+            }
+        );
+        for corrupted in 0..proof.len() {
+            let mut bad_proof = proof.clone();
+            bad_proof[corrupted] = B256::repeat_byte(0xee);
+            assert_eq!(
+                tree.verify_diagnostic(&leaves[5], &bad_proof).unwrap(),
+                DiagResult {
+                    verified: false,
+                    last_matched_level: corrupted
+                }
+            );
+        }
+        assert!(matches!(
+            tree.verify_diagnostic(&DynSolValue::String("missing".to_string()), &proof),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+
+        // Positional trees are folded in the order of the leaves.
+        let positional = StandardMerkleTree::builder()
+            .hash_order(HashOrder::Positional)
+            .build(&leaves)
+            .unwrap();
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let proof = positional.get_proof_by_index(leaf_index).unwrap();
+            assert_eq!(
+                positional.verify_diagnostic(leaf, &proof).unwrap(),
+                DiagResult {
+                    verified: true,
+                    last_matched_level: 3
+                }
+            );
+            for corrupted in 0..proof.len() {
+                let mut bad_proof = proof.clone();
+                bad_proof[corrupted] = B256::repeat_byte(0xee);
+                assert_eq!(
+                    positional.verify_diagnostic(leaf, &bad_proof).unwrap(),
+                    DiagResult {
+                        verified: false,
+                        last_matched_level: corrupted
+                    }
+                );
+            }
+        }
+        let mut too_long = positional.get_proof(&leaves[5]).unwrap();
+        too_long.push(B256::ZERO);
+        assert!(
+            !positional
+                .verify_diagnostic(&leaves[5], &too_long)
+                .unwrap()
+                .verified
+        );
+    }
+
+    /// Tests that the sorted leaf table is sorted and finds every leaf by binary search.
+    #[test]
+    fn test_sorted_leaf_table() {
+        let leaves: Vec<DynSolValue> = (0..7).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let table = tree.sorted_leaf_table();
+
+        assert_eq!(table.len(), leaves.len());
+        assert!(table.windows(2).all(|pair| pair[0] <= pair[1]));
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let found = table
+                .binary_search_by_key(&standard_leaf_hash(leaf).unwrap(), |(leaf_hash, _)| {
+                    *leaf_hash
+                })
+                .unwrap();
+            assert_eq!(table[found].1, leaf_index);
+        }
+        assert!(table
+            .binary_search_by_key(&B256::ZERO, |(leaf_hash, _)| *leaf_hash)
+            .is_err());
+    }
+
+    /// Tests arity-2 and arity-4 proofs, and that a proof is rejected under the wrong arity.
+    #[test]
+    fn test_verify_arity() {
+        // Arity 2 is the standard tree.
+        let leaves: Vec<DynSolValue> = (0..4).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let leaf_hash = standard_leaf_hash(&leaves[1]).unwrap();
+        let groups: Vec<Vec<B256>> = tree
+            .get_proof(&leaves[1])
+            .unwrap()
+            .into_iter()
+            .map(|sibling| vec![sibling])
+            .collect();
+        assert!(verify_arity(tree.root().unwrap(), leaf_hash, &groups, 2));
+
+        // A two-level arity-4 tree over 16 leaves.
+        let hash_sorted = |nodes: &[B256]| {
+            let mut nodes = nodes.to_vec();
+            nodes.sort_unstable();
+            keccak256(nodes.concat())
+        };
+        let hashes: Vec<B256> = (0u8..16).map(|i| keccak256([i])).collect();
+        let parents: Vec<B256> = hashes.chunks(4).map(hash_sorted).collect();
+        let root = hash_sorted(&parents);
+
+        let groups4 = vec![
+            vec![hashes[4], hashes[6], hashes[7]],
+            vec![parents[0], parents[2], parents[3]],
+        ];
+        assert!(verify_arity(root, hashes[5], &groups4, 4));
+        assert!(!verify_arity(root, hashes[4], &groups4, 4));
+
+        // The same siblings regrouped for another arity don't verify.
+        let flat: Vec<B256> = groups4.concat();
+        let groups2: Vec<Vec<B256>> = flat.iter().map(|sibling| vec![*sibling]).collect();
+        assert!(!verify_arity(root, hashes[5], &groups2, 2));
+        assert!(!verify_arity(root, hashes[5], &groups4, 2));
+        assert!(!verify_arity(tree.root().unwrap(), leaf_hash, &groups, 4));
+        assert!(!verify_arity(root, hashes[5], &[], 1));
+    }
+
+    /// The number of hashes computed by [`CountingHasher`].
+    static HASH_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    /// OpenZeppelin's hasher, counting every leaf and pair hash it computes in [`HASH_COUNT`].
+    struct CountingHasher;
+
+    impl Hasher for CountingHasher {
+        fn hash(data: &[u8]) -> B256 {
+            Keccak256Hasher::hash(data)
+        }
+
+        fn hash_leaf(data: &[u8]) -> B256 {
+            HASH_COUNT.fetch_add(1, Ordering::Relaxed);
+            Keccak256Hasher::hash_leaf(data)
+        }
+
+        fn hash_pair_ordered(left: B256, right: B256) -> B256 {
+            HASH_COUNT.fetch_add(1, Ordering::Relaxed);
+            Keccak256Hasher::hash_pair_ordered(left, right)
+        }
+
+        fn hash_pair(left: B256, right: B256) -> B256 {
+            HASH_COUNT.fetch_add(1, Ordering::Relaxed);
+            Keccak256Hasher::hash_pair(left, right)
+        }
+    }
+
+    /// Tests that the rebuild cost is the number of hashes building a tree actually computes, with
+    /// every odd node policy.
+    #[test]
+    fn test_rebuild_hash_cost() {
+        assert_eq!(rebuild_hash_cost(0, OddNodePolicy::Promote), 0);
+        assert_eq!(rebuild_hash_cost(1, OddNodePolicy::Promote), 1);
+        assert_eq!(rebuild_hash_cost(5, OddNodePolicy::Promote), 9);
+        // Five leaves pair up as 3, 2 and 1 nodes once padded.
+        assert_eq!(rebuild_hash_cost(5, OddNodePolicy::HashWithSelf), 11);
+        for policy in [
+            OddNodePolicy::Promote,
+            OddNodePolicy::HashWithSelf,
+            OddNodePolicy::HashWithZero,
+        ] {
+            let options = TreeOptions {
+                odd_node_policy: policy,
+                ..TreeOptions::default()
+            };
+            for leaf_count in 0..20 {
+                let leaves: Vec<DynSolValue> = (0..leaf_count)
+                    .map(|i| DynSolValue::String(i.to_string()))
+                    .collect();
+                HASH_COUNT.store(0, Ordering::Relaxed);
+                let leaf_hashes = hash_leaves::<CountingHasher>(&leaves, &options).unwrap();
+                StandardMerkleTree::<CountingHasher>::from_hashed_values(
+                    &leaves,
+                    leaf_hashes,
+                    options,
+                )
+                .unwrap();
+                assert_eq!(
+                    HASH_COUNT.load(Ordering::Relaxed),
+                    rebuild_hash_cost(leaf_count, policy)
+                );
+            }
+        }
+    }
+
+    /// Tests that tuple leaves hash the same with the column cache on and off, for repeated and
+    /// distinct column values, including more distinct values than the cache keeps.
+    #[test]
+    fn test_column_cache_keeps_roots() {
+        let leaves: Vec<DynSolValue> = (0..1_200u64)
+            .map(|i| {
+                DynSolValue::Tuple(vec![
+                    DynSolValue::Address(Address::with_last_byte((i % 3) as u8)),
+                    DynSolValue::String(alloc::format!("token {}", i % 4)),
+                    DynSolValue::Uint(U256::from(i), 256),
+                    DynSolValue::Bytes(vec![0xab; (i % 70) as usize]),
+                    DynSolValue::String(alloc::format!("holder {i}")),
+                    DynSolValue::Array(vec![DynSolValue::Uint(U256::from(i % 5), 8); 2]),
+                    DynSolValue::Tuple(vec![
+                        DynSolValue::Bool(i % 2 == 0),
+                        DynSolValue::String(String::new()),
+                    ]),
+                ])
+            })
+            .collect();
+        for options in [
+            TreeOptions::default(),
+            TreeOptions {
+                chain_id: Some(1),
+                ..TreeOptions::default()
+            },
+        ] {
+            let cached =
+                hash_leaves_with_cache::<Keccak256Hasher>(&leaves, &options, true).unwrap();
+            let uncached =
+                hash_leaves_with_cache::<Keccak256Hasher>(&leaves, &options, false).unwrap();
+            assert_eq!(cached, uncached);
+            assert_eq!(
+                make_merkle_tree::<Keccak256Hasher>(cached, HashOrder::Sorted)[0],
+                make_merkle_tree::<Keccak256Hasher>(uncached, HashOrder::Sorted)[0]
+            );
+        }
+        assert_eq!(
+            StandardMerkleTree::root_of(&leaves).unwrap(),
+            StandardMerkleTree::of(&leaves).unwrap().root()
+        );
+
+        let mut cache = ColumnCache::default();
+        let mut buffer = Vec::new();
+        for leaf in &leaves[..10] {
+            buffer.clear();
+            encode_leaf_into(leaf, &mut buffer, Some(&mut cache)).unwrap();
+            assert_eq!(buffer, leaf.abi_encode_params());
+        }
+    }
+
+    /// Tests that leaves hashed in batches, in parallel lanes with the `simd` feature, match leaves
+    /// hashed one by one with scalar `keccak256`, and give the same roots.
+    #[test]
+    fn test_batched_leaf_hashes_match_scalar() {
+        for leaf_count in (0..=9).chain([1_001]) {
+            let leaves: Vec<DynSolValue> = (0..leaf_count)
+                .map(|i| match i % 3 {
+                    0 => DynSolValue::String("x".repeat(i * 7)),
+                    1 => DynSolValue::Uint(U256::from(i), 256),
+                    _ => DynSolValue::Tuple(vec![
+                        DynSolValue::Address(Address::with_last_byte(i as u8)),
+                        DynSolValue::Bytes(vec![i as u8; i % 300]),
+                    ]),
+                })
+                .collect();
+            let scalar: Vec<B256> = leaves
+                .iter()
+                .map(|leaf| keccak256(keccak256(leaf_encoding(leaf).unwrap())))
+                .collect();
+            assert_eq!(
+                hash_leaves::<Keccak256Hasher>(&leaves, &TreeOptions::default()).unwrap(),
+                scalar
+            );
+            assert_eq!(
+                StandardMerkleTree::of(&leaves).unwrap().root(),
+                StandardMerkleTree::of_hashes(&scalar).root()
+            );
+
+            let single = TreeOptions {
+                leaf_hash_mode: LeafHashMode::Single,
+                ..TreeOptions::default()
+            };
+            assert_eq!(
+                hash_leaves::<Keccak256Hasher>(&leaves, &single).unwrap(),
+                leaves
+                    .iter()
+                    .map(|leaf| keccak256(leaf_encoding(leaf).unwrap()))
+                    .collect::<Vec<B256>>()
+            );
+        }
+    }
+
+    /// Tests that the `stats` counters of a keccak build add up to the rebuild cost, with every odd
+    /// node policy.
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_rebuild_hash_cost_stats() {
+        for policy in [
+            OddNodePolicy::Promote,
+            OddNodePolicy::HashWithSelf,
+            OddNodePolicy::HashWithZero,
+        ] {
+            let options = TreeOptions {
+                odd_node_policy: policy,
+                ..TreeOptions::default()
+            };
+            for leaf_count in 1..20 {
+                let leaves: Vec<DynSolValue> = (0..leaf_count)
+                    .map(|i| DynSolValue::String(i.to_string()))
+                    .collect();
+                crate::stats::reset();
+                StandardMerkleTree::of_with_options(&leaves, options).unwrap();
+                let stats = crate::stats::snapshot();
+                assert_eq!(stats.leaf_hashes, leaf_count);
+                assert_eq!(stats.total(), rebuild_hash_cost(leaf_count, policy));
+            }
+        }
+    }
+
+    /// Tests each rejection of `precheck_proof`.
+    #[test]
+    fn test_precheck_proof() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        for (_, proof) in tree.iter_proofs() {
+            assert!(tree.precheck_proof(&proof).is_ok());
+        }
+
+        assert!(matches!(
+            tree.precheck_proof(&[]),
+            Err(MerkleTreeError::EmptyProof)
+        ));
+        assert!(matches!(
+            tree.precheck_proof(&[B256::ZERO; 4]),
+            Err(MerkleTreeError::ProofTooLong)
+        ));
+
+        let single = StandardMerkleTree::of(&leaves[..1]).unwrap();
+        assert!(single.precheck_proof(&[]).is_ok());
+        assert!(matches!(
+            single.precheck_proof(&[B256::ZERO]),
+            Err(MerkleTreeError::ProofTooLong)
+        ));
+    }
+
+    /// Tests that a proof response survives a JSON round trip and its proof verifies.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_proof_response() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let response = tree.proof_response(&leaves[3]).unwrap();
+        assert_eq!(response.leaf_index, 3);
+
+        let json = serde_json::to_string(&response).unwrap();
+        let parsed: crate::standard_binary_tree::ProofResponse =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, response);
+
+        assert_eq!(B256::from_hex(&parsed.root).unwrap(), tree.root().unwrap());
+        assert_eq!(
+            B256::from_hex(&parsed.leaf).unwrap(),
+            standard_leaf_hash(&leaves[3]).unwrap()
+        );
+        let proof: Vec<B256> = parsed
+            .proof
+            .iter()
+            .map(|sibling| B256::from_hex(sibling).unwrap())
+            .collect();
+        assert!(tree.verify_proof(&leaves[3], proof));
+    }
+
+    /// Tests recovering the original string and `bytes32` leaves.
+    #[test]
+    fn test_original_leaves() {
+        let strings: Vec<String> = ["b", "a", "c", "a"].iter().map(|s| s.to_string()).collect();
+        let leaves: Vec<DynSolValue> = strings.iter().cloned().map(DynSolValue::String).collect();
+        let mut tree = StandardMerkleTree::of(&leaves).unwrap();
+        assert_eq!(tree.values(), &leaves[..]);
+        assert_eq!(tree.string_leaves().unwrap(), strings);
+        assert!(matches!(
+            tree.bytes32_leaves(),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+
+        tree.update_leaf(1, &DynSolValue::String("d".to_string()))
+            .unwrap();
+        assert_eq!(tree.string_leaves().unwrap(), ["b", "d", "c", "a"]);
+
+        let loaded = StandardMerkleTree::load(tree.dump()).unwrap();
+        assert_eq!(loaded.root(), tree.root());
+        assert_eq!(loaded.string_leaves().unwrap(), ["b", "d", "c", "a"]);
+        let proof = loaded.get_proof(&leaves[2]).unwrap();
+        assert!(loaded.verify_proof(&leaves[2], proof));
+
+        // OpenZeppelin's `["string"]` trees hash their leaves as one-column tuples, and still load
+        // as such.
+        let tuples = strings
+            .iter()
+            .map(|string| vec![DynSolValue::String(string.clone())])
+            .collect::<Vec<Vec<DynSolValue>>>();
+        let openzeppelin = StandardMerkleTree::of_multi(&tuples).unwrap();
+        let loaded = StandardMerkleTree::load(openzeppelin.dump()).unwrap();
+        assert_eq!(loaded.root(), openzeppelin.root());
+        assert_eq!(loaded.values(), openzeppelin.values());
+
+        let hashes: Vec<B256> = (0u8..3).map(|i| keccak256([i])).collect();
+        let tree = StandardMerkleTree::of_tx_hashes(&hashes);
+        assert_eq!(tree.bytes32_leaves().unwrap(), hashes);
+        assert!(matches!(
+            tree.string_leaves(),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+    }
+
+    /// Tests that `root_of` agrees with the root of the built tree.
+    #[test]
+    fn test_root_of() {
+        for leaf_count in 0..10 {
+            let leaves: Vec<DynSolValue> = (0..leaf_count)
+                .map(|i| DynSolValue::Uint(U256::from(i), 256))
+                .collect();
+            assert_eq!(
+                StandardMerkleTree::root_of(&leaves).unwrap(),
+                StandardMerkleTree::of(&leaves).unwrap().root()
+            );
+        }
+        let leaves = [
+            DynSolValue::String("a".to_string()),
+            DynSolValue::FixedBytes(B256::repeat_byte(1), 32),
+        ];
+        assert_eq!(
+            StandardMerkleTree::root_of(&leaves).unwrap(),
+            StandardMerkleTree::of(&leaves).unwrap().root()
+        );
+    }
+
+    /// Tests that leaves encoded in place match their ABI encoding.
+    #[test]
+    fn test_encode_leaf_into_matches_abi_encoding() {
+        let words = [
+            DynSolValue::Address(Address::repeat_byte(0xab)),
+            DynSolValue::Bool(true),
+            DynSolValue::Bool(false),
+            DynSolValue::Int(I256::MINUS_ONE, 256),
+            DynSolValue::Int(I256::try_from(-5).unwrap(), 8),
+            DynSolValue::Int(I256::try_from(7).unwrap(), 64),
+            DynSolValue::Uint(U256::MAX, 256),
+            DynSolValue::Uint(U256::from(3), 8),
+            DynSolValue::FixedBytes(B256::left_padding_from(&[1, 2]), 2),
+            DynSolValue::FixedBytes(B256::repeat_byte(0xcd), 32),
+        ];
+        let mut buffer = Vec::new();
+        for word in words.iter().filter(|word| {
+            matches!(
+                word,
+                DynSolValue::Address(_) | DynSolValue::Bool(_) | DynSolValue::Int(_, _)
+            )
+        }) {
+            buffer.clear();
+            encode_leaf_into(word, &mut buffer, None).unwrap();
+            assert_eq!(buffer, word.abi_encode());
+        }
+
+        let static_tuple = DynSolValue::Tuple(words.to_vec());
+        let mut dynamic_columns = words.to_vec();
+        dynamic_columns.insert(1, DynSolValue::String("a".to_string()));
+        let dynamic_tuple = DynSolValue::Tuple(dynamic_columns);
+        for tuple in [static_tuple, dynamic_tuple] {
+            buffer.clear();
+            encode_leaf_into(&tuple, &mut buffer, None).unwrap();
+            assert_eq!(buffer, tuple.abi_encode_params());
+        }
+    }
+
+    /// Tests that `verify_with_schema` rejects leaves of the wrong type before verifying.
+    #[test]
+    fn test_verify_with_schema() {
+        let leaves: Vec<DynSolValue> = (0..4)
+            .map(|i| DynSolValue::Uint(U256::from(i), 256))
+            .collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let proof = tree.get_proof(&leaves[2]).unwrap();
+
+        let uint = DynSolType::Uint(256);
+        assert!(tree.verify_with_schema(&leaves[2], &uint, &proof).unwrap());
+        assert!(!tree.verify_with_schema(&leaves[1], &uint, &proof).unwrap());
+
+        // A bytes32 with the same word as the uint is rejected rather than verified.
+        let confused = DynSolValue::FixedBytes(B256::from(U256::from(2)), 32);
+        assert!(matches!(
+            tree.verify_with_schema(&confused, &uint, &proof),
+            Err(MerkleTreeError::SchemaMismatch)
+        ));
+        assert!(matches!(
+            tree.verify_with_schema(&leaves[2], &DynSolType::Uint(128), &proof),
+            Err(MerkleTreeError::SchemaMismatch)
+        ));
+        assert!(matches!(
+            tree.verify_with_schema(&DynSolValue::Bytes(vec![1]), &DynSolType::Bytes, &proof),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+    }
+
+    /// Tests that the level callback fires once per level, with every level correctly sized.
+    #[test]
+    fn test_build_with_level_callback() {
+        for leaf_count in 1..12 {
+            let leaves: Vec<DynSolValue> = (0..leaf_count)
+                .map(|i| DynSolValue::String(i.to_string()))
+                .collect();
+            let mut levels: Vec<(usize, Vec<B256>)> = Vec::new();
+            let tree = StandardMerkleTree::build_with_level_callback(&leaves, |level, nodes| {
+                levels.push((level, nodes.to_vec()))
+            })
+            .unwrap();
+            assert_eq!(
+                tree.root().unwrap(),
+                StandardMerkleTree::of(&leaves).unwrap().root().unwrap()
+            );
+
+            assert_eq!(levels.len(), tree.depth() + 1);
+            let expected: Vec<usize> = (0..=tree.depth()).rev().collect();
+            assert_eq!(
+                levels.iter().map(|(level, _)| *level).collect::<Vec<_>>(),
+                expected
+            );
+            for (level, nodes) in levels.iter() {
+                let start = (1 << level) - 1;
+                let end = ((2 << level) - 1).min(tree.nodes().len());
+                assert_eq!(nodes[..], tree.nodes()[start..end]);
+            }
+            assert_eq!(levels[levels.len() - 1].1, vec![tree.root().unwrap()]);
+        }
+    }
+
+    /// Tests positional proofs against positional trees and against sorted pairs.
+    #[test]
+    fn test_proof_with_positions() {
+        let hashes: Vec<B256> = (0u8..6).map(|i| keccak256([i])).collect();
+        let tx_tree = StandardMerkleTree::of_tx_hashes(&hashes);
+        for (leaf_index, hash) in hashes.iter().enumerate() {
+            let leaf = DynSolValue::FixedBytes(*hash, 32);
+            let (siblings, positions) = tx_tree.get_proof_with_positions(&leaf).unwrap();
+            assert_eq!(siblings, tx_tree.get_proof(&leaf).unwrap());
+            // Leaf `i` is a right node exactly when `i` is odd.
+            assert_eq!(positions[0], leaf_index % 2 == 1);
+            assert!(verify_with_positions(
+                tx_tree.root().unwrap(),
+                *hash,
+                &siblings,
+                &positions
+            ));
+
+            let flipped: Vec<bool> = positions.iter().map(|position| !position).collect();
+            assert!(!verify_with_positions(
+                tx_tree.root().unwrap(),
+                *hash,
+                &siblings,
+                &flipped
+            ));
+            assert!(!verify_with_positions(
+                tx_tree.root().unwrap(),
+                *hash,
+                &siblings,
+                &positions[1..]
+            ));
+        }
+
+        // A sorted pair puts the smaller node first, so positions derived from the hashes verify.
+        let leaves: Vec<DynSolValue> = (0..6).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        for leaf in leaves.iter() {
+            let leaf_hash = standard_leaf_hash(leaf).unwrap();
+            let (siblings, _) = tree.get_proof_with_positions(leaf).unwrap();
+            let mut node = leaf_hash;
+            let sorted_positions: Vec<bool> = siblings
+                .iter()
+                .map(|sibling| {
+                    let sibling_is_left = *sibling < node;
+                    node = hash_pair(node, *sibling);
+                    sibling_is_left
+                })
+                .collect();
+            assert!(verify_with_positions(
+                tree.root().unwrap(),
+                leaf_hash,
+                &siblings,
+                &sorted_positions
+            ));
+        }
+    }
+
+    /// Tests that identical trees share a content id and different trees don't.
+    #[test]
+    fn test_content_id() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        assert_eq!(
+            tree.content_id(),
+            StandardMerkleTree::of(&leaves).unwrap().content_id()
+        );
+        assert_eq!(tree.content_id(), keccak256(tree.nodes().concat()));
+
+        assert_ne!(
+            tree.content_id(),
+            StandardMerkleTree::of(&leaves[..4]).unwrap().content_id()
+        );
+        // Swapping two sibling leaves keeps the sorted-pair root but changes the layout.
+        let mut swapped = leaves.clone();
+        swapped.swap(0, 1);
+        let swapped = StandardMerkleTree::of(&swapped).unwrap();
+        assert_eq!(swapped.root().unwrap(), tree.root().unwrap());
+        assert_ne!(swapped.content_id(), tree.content_id());
+    }
+
+    /// Tests that the plausible leaf counts are exactly those of trees with a proof of that length,
+    /// for default and padded trees.
+    #[test]
+    fn test_plausible_leaf_count() {
+        assert_eq!(plausible_leaf_count(0, OddNodePolicy::HashWithSelf), 1..=1);
+        assert_eq!(plausible_leaf_count(1, OddNodePolicy::HashWithSelf), 2..=2);
+        assert_eq!(plausible_leaf_count(3, OddNodePolicy::HashWithSelf), 5..=8);
+        assert_eq!(plausible_leaf_count(0, OddNodePolicy::Promote), 1..=1);
+        assert_eq!(plausible_leaf_count(1, OddNodePolicy::Promote), 2..=3);
+        assert_eq!(plausible_leaf_count(3, OddNodePolicy::Promote), 5..=15);
+
+        for policy in [
+            OddNodePolicy::Promote,
+            OddNodePolicy::HashWithSelf,
+            OddNodePolicy::HashWithZero,
+        ] {
+            // The leaf counts whose trees have a proof of each length.
+            let mut counts_by_length = vec![Vec::new(); 7];
+            for leaf_count in 1..=40 {
+                let leaves: Vec<DynSolValue> = (0..leaf_count)
+                    .map(|i| DynSolValue::String(i.to_string()))
+                    .collect();
+                let tree = StandardMerkleTree::builder()
+                    .odd_node_policy(policy)
+                    .build(&leaves)
+                    .unwrap();
+                for (_, proof) in tree.iter_proofs() {
+                    assert!(plausible_leaf_count(proof.len(), policy).contains(&leaf_count));
+                    let counts: &mut Vec<usize> = &mut counts_by_length[proof.len()];
+                    if counts.last() != Some(&leaf_count) {
+                        counts.push(leaf_count);
+                    }
+                }
+            }
+            for (proof_len, counts) in counts_by_length.iter().enumerate().take(5) {
+                assert_eq!(
+                    *counts,
+                    plausible_leaf_count(proof_len, policy).collect::<Vec<usize>>()
+                );
+            }
+        }
+    }
+
+    /// Tests sharing a frozen tree across threads and verifying proofs concurrently.
+    #[test]
+    fn test_freeze() {
+        extern crate std;
+
+        let leaves: Vec<DynSolValue> = (0..16)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let root = tree.root().unwrap();
+        let frozen = tree.freeze();
+        assert_eq!(frozen.root().unwrap(), root);
+
+        let handles: Vec<_> = (0..4)
+            .map(|worker| {
+                let frozen = frozen.clone();
+                let leaves = leaves.clone();
+                std::thread::spawn(move || {
+                    leaves.iter().skip(worker).step_by(4).all(|leaf| {
+                        let proof = frozen.get_proof(leaf).unwrap();
+                        frozen.verify_proof(leaf, proof)
+                    })
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+    }
+
+    /// Tests fetching proofs by a key derived from the leaves.
+    #[test]
+    fn test_get_proof_by_key() {
+        // `address:amount` claims, looked up by address.
+        let claims = [
+            "0x1111111111111111111111111111111111111111:100",
+            "0x2222222222222222222222222222222222222222:250",
+            "0x3333333333333333333333333333333333333333:75",
+        ];
+        let leaves: Vec<DynSolValue> = claims
+            .iter()
+            .map(|claim| DynSolValue::String(claim.to_string()))
+            .collect();
+        let mut tree = StandardMerkleTree::builder()
+            .key_fn(|value| match value {
+                DynSolValue::String(claim) => claim.split(':').next().unwrap().to_string(),
+                _ => String::new(),
+            })
+            .build(&leaves)
+            .unwrap();
+
+        let proof = tree
+            .get_proof_by_key("0x2222222222222222222222222222222222222222")
+            .unwrap();
+        assert_eq!(proof, tree.get_proof(&leaves[1]).unwrap());
+        assert!(tree.verify_proof(&leaves[1], proof));
+        assert!(matches!(
+            tree.get_proof_by_key(claims[1]),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+
+        tree.update_leaf(1, &DynSolValue::String("replaced".to_string()))
+            .unwrap();
+        assert!(matches!(
+            tree.get_proof_by_key("0x2222222222222222222222222222222222222222"),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+        assert!(tree
+            .get_proof_by_key("0x3333333333333333333333333333333333333333")
+            .is_ok());
+    }
+
+    /// Tests that zero leaves are hashed like any other leaf.
+    #[test]
+    fn test_zero_leaves() {
+        let a = keccak256("a");
+        let tree =
+            make_merkle_tree::<Keccak256Hasher>(vec![B256::ZERO, a, B256::ZERO], HashOrder::Sorted);
+        assert_eq!(tree[0], hash_pair(hash_pair(B256::ZERO, a), B256::ZERO));
+
+        let all_zero = make_merkle_tree::<Keccak256Hasher>(vec![B256::ZERO; 4], HashOrder::Sorted);
+        assert_eq!(all_zero[0], empty_subtree_root(2, B256::ZERO));
+
+        let tx_tree = StandardMerkleTree::of_tx_hashes(&[B256::ZERO, a, B256::ZERO]);
+        let cat = |left: B256, right: B256| keccak256([left.as_slice(), right.as_slice()].concat());
+        assert_eq!(
+            tx_tree.root().unwrap(),
+            cat(cat(B256::ZERO, a), cat(B256::ZERO, B256::ZERO))
+        );
+        for (leaf_index, proof) in tx_tree.iter_proofs() {
+            assert!(tx_tree
+                .verify_at_index(&tx_tree.values()[leaf_index], leaf_index, &proof)
+                .unwrap());
+        }
+    }
+
+    /// Tests verifying against a normal and a byte-reversed stored root.
+    #[test]
+    fn test_verify_stored_root() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let leaf_hash = standard_leaf_hash(&leaves[2]).unwrap();
+        let proof = tree.get_proof(&leaves[2]).unwrap();
+
+        let mut reversed = tree.root().unwrap();
+        reversed.0.reverse();
+        assert_eq!(
+            ByteOrder::LittleEndian.normalize(reversed),
+            tree.root().unwrap()
+        );
+
+        assert!(verify_stored_root(
+            tree.root().unwrap(),
+            ByteOrder::BigEndian,
+            leaf_hash,
+            &proof
+        ));
+        assert!(verify_stored_root(
+            reversed,
+            ByteOrder::LittleEndian,
+            leaf_hash,
+            &proof
+        ));
+        assert!(!verify_stored_root(
+            reversed,
+            ByteOrder::BigEndian,
+            leaf_hash,
+            &proof
+        ));
+        assert!(!verify_stored_root(
+            tree.root().unwrap(),
+            ByteOrder::LittleEndian,
+            leaf_hash,
+            &proof
+        ));
+    }
+
+    /// Locks the proof ordering contract with OpenZeppelin's `MerkleProof.verify`: proofs run from
+    /// the leaf's sibling up to a child of the root, and don't verify reversed.
+    #[test]
+    fn test_proof_order_matches_oz_verify() {
+        // `MerkleProof.verify(proof, root, leaf)` is `processProof(proof, leaf) == root`.
+        let solidity_verify =
+            |proof: &[B256], root: B256, leaf: B256| solidity_process_proof(leaf, proof) == root;
+
+        let leaves: Vec<DynSolValue> = (0..11)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        for (leaf_index, proof) in tree.iter_proofs() {
+            let leaf_hash = standard_leaf_hash(&leaves[leaf_index]).unwrap();
+            assert!(solidity_verify(&proof, tree.root().unwrap(), leaf_hash));
+
+            let tree_index = tree.leaf_index_to_tree_index(leaf_index).unwrap();
+            assert_eq!(proof[0], tree.nodes()[sibling_index(tree_index).unwrap()]);
+            let top = proof[proof.len() - 1];
+            assert!(top == tree.nodes()[1] || top == tree.nodes()[2]);
+
+            if proof.len() > 1 {
+                let reversed: Vec<B256> = proof.iter().rev().copied().collect();
+                assert!(!solidity_verify(&reversed, tree.root().unwrap(), leaf_hash));
+            }
+        }
+    }
+
+    /// Tests sorting numeric string leaves numerically before hashing.
+    #[test]
+    fn test_sort_by() {
+        let leaves: Vec<DynSolValue> = ["10", "9", "100", "1", "25"]
+            .iter()
+            .map(|amount| DynSolValue::String(amount.to_string()))
+            .collect();
+        let numeric = |value: &DynSolValue| value.as_str().unwrap().parse::<u64>().unwrap();
+        let tree = StandardMerkleTree::builder()
+            .sort_by(move |a, b| numeric(a).cmp(&numeric(b)))
+            .build(&leaves)
+            .unwrap();
+        assert!(tree.options().custom_sort);
+        assert!(
+            !StandardMerkleTree::of(&leaves)
+                .unwrap()
+                .options()
+                .custom_sort
+        );
+
+        let expected: Vec<B256> = ["1", "9", "10", "25", "100"]
+            .iter()
+            .map(|amount| standard_leaf_hash(&DynSolValue::String(amount.to_string())).unwrap())
+            .collect();
+        assert_eq!(tree.leaf_hashes(), expected);
+        for leaf in leaves.iter() {
+            assert!(tree.verify_proof(leaf, tree.get_proof(leaf).unwrap()));
+        }
+    }
+
+    /// Mirrors OpenZeppelin's Solidity `MerkleProof.processMultiProof`, which walks the leaves, the
+    /// computed hashes and the proof with three cursors instead of a queue.
+    fn solidity_process_multi_proof(proof: &[B256], proof_flags: &[bool], leaves: &[B256]) -> B256 {
+        let total_hashes = proof_flags.len();
+        assert_eq!(
+            leaves.len() + proof.len(),
+            total_hashes + 1,
+            "MerkleProofInvalidMultiproof"
+        );
+
+        let mut hashes = vec![B256::ZERO; total_hashes];
+        let (mut leaf_pos, mut hash_pos, mut proof_pos) = (0, 0, 0);
+        let next = |hashes: &[B256], leaf_pos: &mut usize, hash_pos: &mut usize| {
+            if *leaf_pos < leaves.len() {
+                *leaf_pos += 1;
+                leaves[*leaf_pos - 1]
+            } else {
+                *hash_pos += 1;
+                hashes[*hash_pos - 1]
+            }
+        };
+        for i in 0..total_hashes {
+            let a = next(&hashes, &mut leaf_pos, &mut hash_pos);
+            let b = if proof_flags[i] {
+                next(&hashes, &mut leaf_pos, &mut hash_pos)
+            } else {
+                proof_pos += 1;
+                proof[proof_pos - 1]
+            };
+            hashes[i] = solidity_process_proof(a, &[b]);
+        }
+
+        if total_hashes > 0 {
+            hashes[total_hashes - 1]
+        } else if !leaves.is_empty() {
+            leaves[0]
+        } else {
+            proof[0]
+        }
+    }
+
+    /// Tests multiproofs against single-leaf proofs and OpenZeppelin's Solidity verifier.
+    #[test]
+    fn test_multi_proof() {
+        let leaves: Vec<DynSolValue> = (0..9).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        let subsets: [&[usize]; 5] = [&[0], &[1, 2], &[8, 0, 4], &[3, 4, 5, 6, 7], &[]];
+        for subset in subsets {
+            let values: Vec<DynSolValue> = subset.iter().map(|i| leaves[*i].clone()).collect();
+            for value in values.iter() {
+                assert!(tree.verify_proof(value, tree.get_proof(value).unwrap()));
+            }
+
+            let multi_proof = tree.get_multi_proof(&values).unwrap();
+            let mut expected: Vec<B256> = values
+                .iter()
+                .map(|value| standard_leaf_hash(value).unwrap())
+                .collect();
+            let mut proven = multi_proof.leaves.clone();
+            expected.sort_unstable();
+            proven.sort_unstable();
+            assert_eq!(proven, expected);
+
+            assert!(tree.verify_multi_proof(&multi_proof));
+            assert_eq!(
+                process_multi_proof(&multi_proof).unwrap(),
+                tree.root().unwrap()
+            );
+            assert_eq!(
+                solidity_process_multi_proof(
+                    &multi_proof.proof,
+                    &multi_proof.proof_flags,
+                    &multi_proof.leaves
+                ),
+                tree.root().unwrap()
+            );
+        }
+
+        // Every leaf at once needs no proof elements.
+        let all = tree.get_multi_proof(&leaves).unwrap();
+        assert!(all.proof.is_empty());
+        assert!(all.proof_flags.iter().all(|flag| *flag));
+        assert!(tree.verify_multi_proof(&all));
+
+        let mut tampered = tree.get_multi_proof(&leaves[1..3]).unwrap();
+        tampered.leaves.swap(0, 1);
+        tampered.leaves[0] = B256::ZERO;
+        assert!(!tree.verify_multi_proof(&tampered));
+        tampered.proof_flags.push(true);
+        assert!(matches!(
+            process_multi_proof(&tampered),
+            Err(MerkleTreeError::InvalidMultiProof)
+        ));
+        let empty = MultiProof {
+            leaves: vec![],
+            proof: vec![],
+            proof_flags: vec![],
+        };
+        assert!(matches!(
+            process_multi_proof(&empty),
+            Err(MerkleTreeError::InvalidMultiProof)
+        ));
+        assert!(matches!(
+            tree.get_multi_proof(&[leaves[2].clone(), leaves[2].clone()]),
+            Err(MerkleTreeError::DuplicatedIndex)
+        ));
+    }
+
+    /// Tests the hex node array against the nodes of the "a", "b", "c" fixture.
+    #[test]
+    fn test_tree_hex() {
+        let leaves = ["a", "b", "c"].map(|leaf| DynSolValue::String(leaf.to_string()));
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let leaf_a = b256!("e882a0dd840cc7b99d5f9ff05216be547c7b7d84a61d474353c4d9cb90cb2cdd");
+        let leaf_b = b256!("5e56c48f79378189dc48d49b24d2d1328e3279d4491b42f5f49825c17b416fcd");
+        let ab = hash_pair(leaf_a, leaf_b).to_string();
+        assert_eq!(
+            tree.tree_hex(),
+            [
+                "0xec5728bad90d3185989eddaae1e83be5f3e03ad9f48bd743df58dad4b7625505",
+                ab.as_str(),
+                "0x05098bd04354a2fc3a93037c505c12d75216a5b8e8b2700782de9617154c2bca",
+                "0x5e56c48f79378189dc48d49b24d2d1328e3279d4491b42f5f49825c17b416fcd",
+                "0xe882a0dd840cc7b99d5f9ff05216be547c7b7d84a61d474353c4d9cb90cb2cdd",
+            ]
+        );
+    }
+
+    /// Tests verifying proofs streamed from a reader.
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_verify_proof_reader() {
+        use std::io::Cursor;
+
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let proof = tree.get_proof(&leaves[4]).unwrap().concat();
+
+        assert!(tree
+            .verify_proof_reader(&leaves[4], Cursor::new(&proof))
+            .unwrap());
+        assert!(!tree
+            .verify_proof_reader(&leaves[3], Cursor::new(&proof))
+            .unwrap());
+        assert!(!tree
+            .verify_proof_reader(&leaves[4], Cursor::new(&proof[..32]))
+            .unwrap());
+        assert!(matches!(
+            tree.verify_proof_reader(&leaves[4], Cursor::new(&proof[..40])),
+            Err(MerkleTreeError::TruncatedProof)
+        ));
+
+        // Positional proofs are folded along the position of the leaf, including its duplicates.
+        let mut positional_leaves = leaves.clone();
+        positional_leaves.push(leaves[1].clone());
+        let positional = StandardMerkleTree::builder()
+            .hash_order(HashOrder::Positional)
+            .build(&positional_leaves)
+            .unwrap();
+        for (leaf_index, leaf) in positional_leaves.iter().enumerate() {
+            let proof = positional.get_proof_by_index(leaf_index).unwrap();
+            assert!(positional
+                .verify_proof_reader(leaf, Cursor::new(proof.concat()))
+                .unwrap());
+            let mut reversed = proof.clone();
+            reversed.reverse();
+            assert_eq!(
+                positional
+                    .verify_proof_reader(leaf, Cursor::new(reversed.concat()))
+                    .unwrap(),
+                positional.verify_proof(leaf, reversed)
+            );
+        }
+        let proof = positional.get_proof(&leaves[0]).unwrap();
+        assert!(!tree
+            .verify_proof_reader(&leaves[0], Cursor::new(proof.concat()))
+            .unwrap());
+        assert!(!positional
+            .verify_proof_reader(&leaves[0], Cursor::new(Vec::new()))
+            .unwrap());
+    }
+
+    /// Tests a two-column `(address, uint256)` tree against the root OpenZeppelin's JS library
+    /// computes for the example of its README.
+    #[test]
+    fn test_of_multi_matches_openzeppelin() {
+        let values: Vec<Vec<DynSolValue>> = [
+            (
+                "0x1111111111111111111111111111111111111111",
+                5_000_000_000_000_000_000u128,
+            ),
+            (
+                "0x2222222222222222222222222222222222222222",
+                2_500_000_000_000_000_000u128,
+            ),
+        ]
+        .iter()
+        .map(|(address, amount)| {
+            vec![
+                DynSolValue::Address(address.parse().unwrap()),
+                DynSolValue::Uint(U256::from(*amount), 256),
+            ]
+        })
+        .collect();
+        let tree = StandardMerkleTree::of_multi(&values).unwrap();
+        assert_eq!(
+            tree.root().unwrap(),
+            b256!("d4dee0beab2d53f2cc83e567171bd2820e49898130a22622b10ead383e90bd77")
+        );
+
+        for columns in values.iter() {
+            let leaf = DynSolValue::Tuple(columns.clone());
+            let encoded = leaf.abi_encode_params();
+            assert_eq!(encoded.len(), 64);
+            assert_eq!(
+                standard_leaf_hash(&leaf).unwrap(),
+                keccak256(keccak256(encoded))
+            );
+
+            let proof = tree.get_proof(&leaf).unwrap();
+            assert!(tree.verify_proof(&leaf, proof));
+        }
+        assert_eq!(tree.values()[0], DynSolValue::Tuple(values[0].clone()));
+    }
+
+    /// Tests that every node of a witness is recomputed from the one below it and its sibling.
+    #[test]
+    fn test_witness_nodes_match_recomputation() {
+        let leaves = (0..7)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        for leaf in leaves.iter() {
+            let witness = tree.witness(leaf).unwrap();
+            assert_eq!(witness.leaf_hash, standard_leaf_hash(leaf).unwrap());
+            assert_eq!(witness.siblings, tree.get_proof(leaf).unwrap());
+            assert_eq!(
+                witness.path_bits,
+                tree.get_proof_with_positions(leaf).unwrap().1
+            );
+            assert_eq!(witness.node_at_level.len(), witness.siblings.len());
+
+            let mut node = witness.leaf_hash;
+            for (sibling, expected) in witness.siblings.iter().zip(witness.node_at_level.iter()) {
+                node = hash_pair(node, *sibling);
+                assert_eq!(node, *expected);
+            }
+            assert_eq!(witness.node_at_level.last(), Some(&tree.root().unwrap()));
+            assert_eq!(witness.root, tree.root().unwrap());
+        }
+        assert!(matches!(
+            tree.witness(&DynSolValue::String("missing".to_string())),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+    }
+
+    /// Tests that unsupported leaf types are reported as errors instead of panicking.
+    #[test]
+    fn test_unsupported_leaf_type_is_an_error() {
+        let leaves = [
+            DynSolValue::String("a".to_string()),
+            DynSolValue::Bytes(vec![1]),
+        ];
+        assert!(matches!(
+            StandardMerkleTree::of(&leaves),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+        assert!(matches!(
+            StandardMerkleTree::root_of(&leaves),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+        assert!(matches!(
+            StandardMerkleTree::new(vec![B256::ZERO], vec![(&leaves[1], 0)]),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+
+        let mut tree = StandardMerkleTree::of(&leaves[..1]).unwrap();
+        assert!(!tree.verify_proof(&leaves[1], vec![]));
+        assert!(matches!(
+            tree.get_proof(&leaves[1]),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+        assert!(matches!(
+            tree.update_leaf(0, &leaves[1]),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+        assert_eq!(tree.values(), &leaves[..1]);
+    }
+
+    /// Tests that a tree built with positional hashing proves every leaf and commits to their order.
+    #[test]
+    fn test_builder_hash_order_positional() {
+        let leaves = (0..5)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::builder()
+            .hash_order(HashOrder::Positional)
+            .build(&leaves)
+            .unwrap();
+        assert_eq!(tree.options().hash_order, HashOrder::Positional);
+        assert_ne!(
+            tree.root().unwrap(),
+            StandardMerkleTree::of(&leaves).unwrap().root().unwrap()
+        );
+
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let (siblings, positions) = tree.get_proof_with_positions(leaf).unwrap();
+            let leaf_hash = standard_leaf_hash(leaf).unwrap();
+            assert!(tree.verify_proof(leaf, siblings.clone()));
+            assert!(tree.verify_at_index(leaf, leaf_index, &siblings).unwrap());
+            assert!(verify_with_positions(
+                tree.root().unwrap(),
+                leaf_hash,
+                &siblings,
+                &positions
+            ));
+        }
+
+        let mut swapped = leaves.clone();
+        swapped.swap(0, 1);
+        let swapped_tree = StandardMerkleTree::builder()
+            .hash_order(HashOrder::Positional)
+            .build(&swapped)
+            .unwrap();
+        assert_ne!(swapped_tree.root().unwrap(), tree.root().unwrap());
+    }
+
+    /// Tests trees of zero, one and two leaves: an empty tree has no root, and a single leaf is its
+    /// own root, as in OpenZeppelin.
+    #[test]
+    fn test_empty_single_and_two_leaf_trees() {
+        let empty = StandardMerkleTree::of(&[]).unwrap();
+        assert_eq!(empty.root(), None);
+        assert_eq!(empty.depth(), 0);
+        assert_eq!(StandardMerkleTree::root_of(&[]).unwrap(), None);
+        assert!(!empty.verify_proof(&DynSolValue::String("a".to_string()), vec![]));
+        assert!(matches!(
+            empty.get_proof(&DynSolValue::String("a".to_string())),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+        let empty_padded = StandardMerkleTree::builder()
+            .odd_node_policy(OddNodePolicy::HashWithSelf)
+            .build(&[])
+            .unwrap();
+        assert_eq!(empty_padded.root(), None);
+
+        let a = DynSolValue::String("a".to_string());
+        let b = DynSolValue::String("b".to_string());
+        let single = StandardMerkleTree::of(&[a.clone()]).unwrap();
+        assert_eq!(single.root(), Some(standard_leaf_hash(&a).unwrap()));
+        assert_eq!(single.depth(), 0);
+        let proof = single.get_proof(&a).unwrap();
+        assert!(proof.is_empty());
+        assert!(single.verify_proof(&a, proof));
+
+        let pair = StandardMerkleTree::of(&[a.clone(), b.clone()]).unwrap();
+        assert_eq!(
+            pair.root(),
+            Some(hash_pair(
+                standard_leaf_hash(&a).unwrap(),
+                standard_leaf_hash(&b).unwrap()
+            ))
+        );
+        assert_eq!(
+            pair.get_proof(&a).unwrap(),
+            vec![standard_leaf_hash(&b).unwrap()]
+        );
+        assert!(pair.verify_proof(&b, pair.get_proof(&b).unwrap()));
+    }
+
+    /// Tests that two sibling leaves share the proof nodes above their parent, and only those.
+    #[test]
+    fn test_shared_proof_nodes_of_siblings() {
+        let leaves = (0..8)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        // Leaves 0 and 1 are siblings, so their proofs only differ in their first element.
+        let shared = tree.shared_proof_nodes(&leaves[..2]).unwrap();
+        let proof = tree.get_proof(&leaves[0]).unwrap();
+        assert_eq!(shared.len(), proof.len() - 1);
+        let mut hashes = shared.iter().map(|(_, hash)| *hash).collect::<Vec<B256>>();
+        hashes.reverse();
+        assert_eq!(hashes, proof[1..]);
+        for (tree_index, hash) in shared.iter() {
+            assert_eq!(tree.nodes()[*tree_index], *hash);
+        }
+
+        assert!(tree.shared_proof_nodes(&leaves[..1]).unwrap().is_empty());
+        assert!(matches!(
+            tree.shared_proof_nodes(&[leaves[0].clone(), leaves[0].clone()]),
+            Err(MerkleTreeError::DuplicatedIndex)
+        ));
+    }
+
+    /// Tests loading the dump OpenZeppelin's JS library produces for the example of its README, and
+    /// dumping it back to the same JSON.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_load_openzeppelin_dump() {
+        let json = r#"{
+            "format": "standard-v1",
+            "tree": [
+                "0xd4dee0beab2d53f2cc83e567171bd2820e49898130a22622b10ead383e90bd77",
+                "0xeb02c421cfa48976e66dfb29120745909ea3a0f843456c263cf8f1253483e283",
+                "0xb92c48e9d7abe27fd8dfd6b5dfdbfb1c9a463f80c712b66f3a5180a090cccafc"
+            ],
+            "values": [
+                {
+                    "value": ["0x1111111111111111111111111111111111111111", "5000000000000000000"],
+                    "treeIndex": 1
+                },
+                {
+                    "value": ["0x2222222222222222222222222222222222222222", "2500000000000000000"],
+                    "treeIndex": 2
+                }
+            ],
+            "leafEncoding": ["address", "uint256"]
+        }"#;
+        let data: crate::standard_binary_tree::StandardMerkleTreeData =
+            serde_json::from_str(json).unwrap();
+        let tree = StandardMerkleTree::load(data.clone()).unwrap();
+        assert_eq!(
+            tree.root(),
+            Some(b256!(
+                "d4dee0beab2d53f2cc83e567171bd2820e49898130a22622b10ead383e90bd77"
+            ))
+        );
+
+        let leaf = DynSolValue::Tuple(vec![
+            DynSolValue::Address(
+                "0x1111111111111111111111111111111111111111"
+                    .parse()
+                    .unwrap(),
+            ),
+            DynSolValue::Uint(U256::from(5_000_000_000_000_000_000u128), 256),
+        ]);
+        assert_eq!(
+            tree.get_proof(&leaf).unwrap(),
+            vec![b256!(
+                "b92c48e9d7abe27fd8dfd6b5dfdbfb1c9a463f80c712b66f3a5180a090cccafc"
+            )]
+        );
+
+        let mut dumped = tree.dump();
+        dumped.values.sort_by_key(|entry| entry.tree_index);
+        assert_eq!(dumped, data);
+        assert_eq!(
+            serde_json::to_value(&dumped).unwrap(),
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+    }
+
+    /// Tests that a dump round-trips through [`StandardMerkleTree::load`] and that tampered dumps
+    /// are rejected.
+    #[test]
+    fn test_dump_load_round_trip() {
+        let leaves = (0..5)
+            .map(|i| DynSolValue::Uint(U256::from(i), 256))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let data = tree.dump();
+        assert_eq!(data.leaf_encoding, vec!["uint256".to_string()]);
+
+        let loaded = StandardMerkleTree::load(data.clone()).unwrap();
+        assert_eq!(loaded.root(), tree.root());
+        assert_eq!(loaded.values(), tree.values());
+        assert_eq!(
+            loaded.get_proof(&leaves[3]).unwrap(),
+            tree.get_proof(&leaves[3]).unwrap()
+        );
+
+        let mut tampered_node = data.clone();
+        tampered_node.tree[1] = B256::ZERO.to_string();
+        let mut tampered_value = data.clone();
+        tampered_value.values[0].value[0] = "42".to_string();
+        let mut swapped_indices = data.clone();
+        swapped_indices.values[0].tree_index = swapped_indices.values[1].tree_index;
+        for tampered in [tampered_node, tampered_value, swapped_indices] {
+            assert!(matches!(
+                StandardMerkleTree::load(tampered),
+                Err(MerkleTreeError::InvalidCheck)
+            ));
+        }
+    }
+
+    /// Tests that every leaf of a tree passes its Bloom filter, and that few other leaves do.
+    #[test]
+    fn test_build_with_bloom() {
+        let leaves = (0..100)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let (tree, bloom) = StandardMerkleTree::build_with_bloom(&leaves, 1000, 7).unwrap();
+        assert_eq!(bloom.len(), 125);
+        assert_eq!(tree.root(), StandardMerkleTree::of(&leaves).unwrap().root());
+
+        for leaf_hash in tree.leaf_hashes() {
+            assert!(bloom_maybe_contains(&bloom, 7, leaf_hash));
+        }
+        let false_positives = (100..1100)
+            .map(|i| standard_leaf_hash(&DynSolValue::String(i.to_string())).unwrap())
+            .filter(|leaf_hash| bloom_maybe_contains(&bloom, 7, *leaf_hash))
+            .count();
+        assert!(false_positives < 50, "{false_positives} false positives");
+
+        assert!(matches!(
+            StandardMerkleTree::build_with_bloom(&leaves, 0, 7),
+            Err(MerkleTreeError::InvalidBloomParameters)
+        ));
+        assert!(matches!(
+            StandardMerkleTree::build_with_bloom(&leaves, 1000, 0),
+            Err(MerkleTreeError::InvalidBloomParameters)
+        ));
+    }
+
+    /// Tests that proofs from a full tree verify against its root alone.
+    #[test]
+    fn test_standalone_verify() {
+        let leaves = (0..9)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let root = tree.root().unwrap();
+        let proofs = tree
+            .iter_proofs()
+            .map(|(_, proof)| proof)
+            .collect::<Vec<_>>();
+        drop(tree);
+
+        for (leaf, proof) in leaves.iter().zip(proofs.iter()) {
+            assert!(verify(root, leaf, proof));
+            assert!(verify_hash(root, standard_leaf_hash(leaf).unwrap(), proof));
+            assert!(!verify(B256::ZERO, leaf, proof));
+        }
+        assert!(!verify(root, &leaves[1], &proofs[0]));
+        assert!(!verify(root, &DynSolValue::Bool(true), &proofs[0]));
+    }
+
+    /// Tests that proofs by leaf index match the proofs by value, for every leaf.
+    #[test]
+    fn test_get_proof_by_index() {
+        let leaves = (0..6)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        assert_eq!(tree.leaf_count(), leaves.len());
+        assert!(!tree.is_empty());
+        assert!(StandardMerkleTree::of(&[]).unwrap().is_empty());
+
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            assert_eq!(
+                tree.get_proof_by_index(leaf_index).unwrap(),
+                tree.get_proof(leaf).unwrap()
+            );
+        }
+        assert!(matches!(
+            tree.get_proof_by_index(leaves.len()),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+    }
+
+    /// Tests that the incremental multiproof verifier agrees with the batch one.
+    #[test]
+    fn test_multi_proof_verifier() {
+        let leaves: Vec<DynSolValue> = (0..9).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let root = tree.root().unwrap();
+
+        let subsets: [&[usize]; 6] = [
+            &[0],
+            &[1, 2],
+            &[8, 0, 4],
+            &[3, 4, 5, 6, 7],
+            &[],
+            &[0, 1, 2, 3, 4, 5, 6, 7, 8],
+        ];
+        for subset in subsets {
+            let values: Vec<DynSolValue> = subset.iter().map(|i| leaves[*i].clone()).collect();
+            let multi_proof = tree.get_multi_proof(&values).unwrap();
+
+            let mut verifier =
+                MultiProofVerifier::new(&multi_proof.proof, &multi_proof.proof_flags);
+            for leaf_hash in multi_proof.leaves.iter() {
+                verifier.feed_leaf(*leaf_hash);
+            }
+            assert_eq!(
+                verifier.clone().finalize(root),
+                process_multi_proof(&multi_proof).ok() == Some(root)
+            );
+            assert!(verifier.clone().finalize(root));
+            assert!(!verifier.finalize(B256::ZERO));
+
+            let mut tampered = multi_proof.leaves.clone();
+            if let Some(first) = tampered.first_mut() {
+                *first = B256::ZERO;
+                let mut verifier =
+                    MultiProofVerifier::new(&multi_proof.proof, &multi_proof.proof_flags);
+                tampered
+                    .iter()
+                    .for_each(|leaf_hash| verifier.feed_leaf(*leaf_hash));
+                assert!(!verifier.finalize(root));
+
+                let mut missing_leaf =
+                    MultiProofVerifier::new(&multi_proof.proof, &multi_proof.proof_flags);
+                missing_leaf.feed_leaf(multi_proof.leaves[0]);
+                assert_eq!(missing_leaf.finalize(root), multi_proof.leaves.len() == 1);
+            }
+
+            let mut extra_leaf =
+                MultiProofVerifier::new(&multi_proof.proof, &multi_proof.proof_flags);
+            multi_proof
+                .leaves
+                .iter()
+                .for_each(|leaf_hash| extra_leaf.feed_leaf(*leaf_hash));
+            extra_leaf.feed_leaf(root);
+            assert!(!extra_leaf.finalize(root));
+        }
+    }
+
+    /// Tests detecting proofs for the same leaf, for different leaves and for invalid proofs.
+    #[test]
+    fn test_proofs_target_same_leaf() {
+        let leaves = (0..4)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .chain([DynSolValue::String("0".to_string())])
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let root = tree.root().unwrap();
+        let first = tree.get_proof_by_index(0).unwrap();
+        let duplicate = tree.get_proof_by_index(4).unwrap();
+        let other = tree.get_proof_by_index(1).unwrap();
+        assert_ne!(first, duplicate);
+
+        assert!(proofs_target_same_leaf(&leaves[0], &first, &leaves[0], &first, root).unwrap());
+        assert!(proofs_target_same_leaf(&leaves[0], &first, &leaves[4], &duplicate, root).unwrap());
+        assert!(!proofs_target_same_leaf(&leaves[0], &first, &leaves[1], &other, root).unwrap());
+        assert!(!proofs_target_same_leaf(&leaves[0], &first, &leaves[0], &other, root).unwrap());
+        assert!(!proofs_target_same_leaf(&leaves[0], &[], &leaves[0], &first, root).unwrap());
+        assert!(matches!(
+            proofs_target_same_leaf(
+                &DynSolValue::Bytes(vec![1]),
+                &first,
+                &leaves[0],
+                &first,
+                root
+            ),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+    }
+
+    use sha2::Digest;
+
+    /// A SHA-256 hasher, left to the default [`Hasher::hash_pair`] to sort pairs.
+    struct Sha256Hasher;
+
+    impl Hasher for Sha256Hasher {
+        fn hash(data: &[u8]) -> B256 {
+            B256::from_slice(&sha2::Sha256::digest(data))
+        }
+
+        fn hash_pair_ordered(left: B256, right: B256) -> B256 {
+            let mut hasher = sha2::Sha256::new();
+            hasher.update(left);
+            hasher.update(right);
+            B256::from_slice(&hasher.finalize())
+        }
+    }
+
+    /// Tests that a tree built with another hasher hashes every node with it, and that its proofs
+    /// verify.
+    #[test]
+    fn test_of_with_hasher_sha256() {
+        let leaves = (0..5)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::<Sha256Hasher>::of_with_hasher(&leaves).unwrap();
+        let keccak_tree = StandardMerkleTree::of(&leaves).unwrap();
+        assert_ne!(tree.root(), keccak_tree.root());
+        assert_eq!(
+            tree.root(),
+            StandardMerkleTree::<Sha256Hasher>::of_with_hasher(&leaves)
+                .unwrap()
+                .root()
+        );
+
+        for leaf in leaves.iter() {
+            let proof = tree.get_proof(leaf).unwrap();
+            assert!(tree.verify_proof(leaf, proof.clone()));
+            assert!(!keccak_tree.verify_proof(leaf, proof));
+        }
+
+        let pair = &leaves[..2];
+        let tree = StandardMerkleTree::<Sha256Hasher>::of_with_hasher(pair).unwrap();
+        let leaf_hashes = pair
+            .iter()
+            .map(|leaf| Sha256Hasher::hash_leaf(&leaf_encoding(leaf).unwrap()))
+            .collect::<Vec<B256>>();
+        assert_eq!(
+            tree.root(),
+            Some(Sha256Hasher::hash_pair(leaf_hashes[0], leaf_hashes[1]))
+        );
+    }
+
+    /// Tests that a positional tree hashes pairs with the unsorted pair hash of its hasher, and that
+    /// its proofs verify.
+    #[test]
+    fn test_positional_tree_with_hasher() {
+        // Leaves in descending hash order, so that no pair of leaves is already sorted.
+        let mut leaves = (0..4)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        leaves.sort_by_key(|leaf| {
+            core::cmp::Reverse(Sha256Hasher::hash_leaf(&leaf_encoding(leaf).unwrap()))
+        });
+        let [l0, l1, l2, l3] =
+            [0, 1, 2, 3].map(|i| Sha256Hasher::hash_leaf(&leaf_encoding(&leaves[i]).unwrap()));
+        assert_ne!(
+            Sha256Hasher::hash_pair_ordered(l0, l1),
+            Sha256Hasher::hash_pair_ordered(l1, l0)
+        );
+
+        let tree = StandardMerkleTree::builder()
+            .hash_order(HashOrder::Positional)
+            .build_with_hasher::<Sha256Hasher>(&leaves)
+            .unwrap();
+        assert_eq!(
+            tree.root(),
+            Some(Sha256Hasher::hash_pair_ordered(
+                Sha256Hasher::hash_pair_ordered(l0, l1),
+                Sha256Hasher::hash_pair_ordered(l2, l3)
+            ))
+        );
+        let sorted = StandardMerkleTree::<Sha256Hasher>::of_with_hasher(&leaves).unwrap();
+        assert_ne!(tree.root(), sorted.root());
+
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.get_proof(leaf).unwrap();
+            assert!(tree.verify_proof(leaf, proof.clone()));
+            assert!(tree.verify_at_index(leaf, leaf_index, &proof).unwrap());
+            assert!(!sorted.verify_proof(leaf, proof));
+        }
+    }
+
+    /// Tests that single-hash leaves are hashed once with the hasher of the tree, not with keccak256.
+    #[test]
+    fn test_single_leaf_hash_mode_with_hasher() {
+        let leaves = (0..3)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let options = TreeOptions {
+            leaf_hash_mode: LeafHashMode::Single,
+            ..TreeOptions::default()
+        };
+        let leaf_hashes = hash_leaves::<Sha256Hasher>(&leaves, &options).unwrap();
+        let tree =
+            StandardMerkleTree::<Sha256Hasher>::from_hashed_values(&leaves, leaf_hashes, options)
+                .unwrap();
+
+        for (leaf, leaf_hash) in leaves.iter().zip(tree.leaf_hashes()) {
+            let encoded = leaf_encoding(leaf).unwrap();
+            assert_eq!(leaf_hash, B256::from_slice(&sha2::Sha256::digest(&encoded)));
+            assert_ne!(leaf_hash, Sha256Hasher::hash_leaf(&encoded));
+            assert_ne!(leaf_hash, keccak256(&encoded));
+            assert_eq!(tree.leaf_hash(leaf).unwrap(), leaf_hash);
+
+            let proof = tree.get_proof(leaf).unwrap();
+            assert!(tree.verify_proof(leaf, proof));
+        }
+    }
+
+    /// Tests that only `bytes32` and `uint256` values are raw leaves, even when other values encode to
+    /// 32 bytes.
+    #[test]
+    fn test_raw_leaves_reject_other_types() {
+        let raw = |values: &[DynSolValue]| {
+            StandardMerkleTree::builder()
+                .leaf_hash_mode(LeafHashMode::Raw)
+                .build(values)
+        };
+        let word = keccak256("a");
+        assert_eq!(
+            raw(&[DynSolValue::FixedBytes(word, 32)]).unwrap().root(),
+            Some(word)
+        );
+        assert_eq!(
+            raw(&[DynSolValue::Uint(U256::from_be_bytes(word.0), 256)])
+                .unwrap()
+                .root(),
+            Some(word)
+        );
+
+        let string = DynSolValue::String("a".repeat(32));
+        let address = DynSolValue::Address(Address::repeat_byte(1));
+        for value in [
+            string,
+            address,
+            DynSolValue::Bool(true),
+            DynSolValue::Int(I256::ONE, 256),
+            DynSolValue::Uint(U256::from(1), 128),
+        ] {
+            assert!(matches!(
+                raw(core::slice::from_ref(&value)),
+                Err(MerkleTreeError::NotSupportedType)
+            ));
+        }
+
+        let tree = raw(&[DynSolValue::FixedBytes(word, 32)]).unwrap();
+        assert!(matches!(
+            tree.leaf_hash(&DynSolValue::String("a".repeat(32))),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+    }
+
+    /// Tests that a chain id can't be combined with raw leaves, which have nothing to bind it into.
+    #[test]
+    fn test_raw_leaves_reject_chain_id() {
+        let leaves = [DynSolValue::FixedBytes(keccak256("a"), 32)];
+        let options = TreeOptions {
+            chain_id: Some(1),
+            leaf_hash_mode: LeafHashMode::Raw,
+            ..TreeOptions::default()
+        };
+        for values in [&leaves[..], &[]] {
+            assert!(matches!(
+                StandardMerkleTree::of_with_options(values, options),
+                Err(MerkleTreeError::IncompatibleOptions)
+            ));
+            assert!(matches!(
+                StandardMerkleTree::builder()
+                    .leaf_hash_mode(LeafHashMode::Raw)
+                    .chain_id(1)
+                    .build(values),
+                Err(MerkleTreeError::IncompatibleOptions)
+            ));
+        }
+
+        let raw = StandardMerkleTree::builder()
+            .leaf_hash_mode(LeafHashMode::Raw)
+            .build(&leaves)
+            .unwrap();
+        assert_eq!(raw.root(), Some(keccak256("a")));
+    }
+
+    /// Tests that an address-only tree hashes its leaves like OpenZeppelin's `["address"]` trees,
+    /// i.e. as one-column tuples, and that its proofs round-trip.
+    #[test]
+    fn test_address_leaves() {
+        let leaves = (1..=5u8)
+            .map(|i| DynSolValue::Address(Address::repeat_byte(i)))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        for leaf in leaves.iter() {
+            assert_eq!(
+                standard_leaf_hash(leaf).unwrap(),
+                standard_leaf_hash(&DynSolValue::Tuple(vec![leaf.clone()])).unwrap()
+            );
+            let proof = tree.get_proof(leaf).unwrap();
+            assert!(tree.verify_proof(leaf, proof));
+        }
+
+        // The key of an address doesn't collide with the string of its hex form.
+        let as_string = DynSolValue::String(Address::repeat_byte(1).to_string());
+        assert!(matches!(
+            tree.get_proof(&as_string),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+    }
+
+    /// Tests that `Uint`, `Int`, and `Bool` leaves are hashed over their 32-byte ABI words, and that
+    /// equal words of different types are distinct leaves.
+    #[test]
+    fn test_word_leaves() {
+        let leaves = [
+            DynSolValue::Uint(U256::from(1), 256),
+            DynSolValue::Int(I256::MINUS_ONE, 256),
+            DynSolValue::Bool(true),
+            DynSolValue::Bool(false),
+            DynSolValue::String("1".to_string()),
+        ];
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        for leaf in leaves.iter() {
+            let proof = tree.get_proof(leaf).unwrap();
+            assert!(tree.verify_proof(leaf, proof));
+        }
+        assert_eq!(
+            standard_leaf_hash(&leaves[1]).unwrap(),
+            keccak256(keccak256(B256::repeat_byte(0xff)))
+        );
+        // `true` and `uint256(1)` share a word, so they hash alike but are looked up separately.
+        assert_eq!(
+            standard_leaf_hash(&leaves[0]).unwrap(),
+            standard_leaf_hash(&leaves[2]).unwrap()
+        );
+        assert_ne!(
+            tree.get_proof(&leaves[0]).unwrap(),
+            tree.get_proof(&leaves[2]).unwrap()
+        );
+    }
+
+    /// Tests that the canonical JSON of a tree is the same for every build of it, and that it loads
+    /// back.
+    #[test]
+    fn test_to_canonical_json_is_stable() {
+        let values = (1..=3u8)
+            .map(|i| {
+                vec![
+                    DynSolValue::Address(Address::repeat_byte(0xa0 + i)),
+                    DynSolValue::Uint(U256::from(i), 256),
+                ]
+            })
+            .collect::<Vec<Vec<DynSolValue>>>();
+        let tree = StandardMerkleTree::of_multi(&values).unwrap();
+        let json = tree.to_canonical_json();
+        assert_eq!(
+            json,
+            StandardMerkleTree::of_multi(&values)
+                .unwrap()
+                .to_canonical_json()
+        );
+        assert!(json.starts_with(
+            r#"{"format":"standard-v1","leafEncoding":["address","uint256"],"tree":["#
+        ));
+        assert!(!json.contains(char::is_whitespace));
+        let address = Address::repeat_byte(0xa1).to_string();
+        assert!(!json.contains(&address));
+        assert!(json.contains(&address.to_lowercase()));
+
+        #[cfg(feature = "serde")]
+        {
+            let data: crate::standard_binary_tree::StandardMerkleTreeData =
+                serde_json::from_str(&json).unwrap();
+            assert_eq!(data.values.len(), values.len());
+            assert_eq!(StandardMerkleTree::load(data).unwrap().root(), tree.root());
+        }
+    }
+
+    /// Tests that [`StandardMerkleTree::verify_any_mode`] finds the leaf hash mode of trees built in
+    /// each mode.
+    #[test]
+    fn test_verify_any_mode() {
+        let leaves = (0..5u64)
+            .map(|i| DynSolValue::FixedBytes(keccak256(i.to_be_bytes()), 32))
+            .collect::<Vec<DynSolValue>>();
+        let hashes = leaves
+            .iter()
+            .map(|leaf| leaf.as_fixed_bytes().unwrap().0.try_into().unwrap())
+            .collect::<Vec<B256>>();
+        let double = StandardMerkleTree::of(&leaves).unwrap();
+        let raw = StandardMerkleTree::of_with_options(
+            &leaves,
+            TreeOptions {
+                leaf_hash_mode: LeafHashMode::Raw,
+                ..TreeOptions::default()
+            },
+        )
+        .unwrap();
+        let tx_hashes = StandardMerkleTree::of_tx_hashes(&hashes);
+
+        for (tree, mode) in [
+            (&double, LeafHashMode::Double),
+            (&raw, LeafHashMode::Raw),
+            (&tx_hashes, LeafHashMode::Raw),
+        ] {
+            for leaf in leaves.iter() {
+                let proof = tree.get_proof(leaf).unwrap();
+                assert_eq!(tree.verify_any_mode(leaf, &proof).unwrap(), Some(mode));
+                assert_eq!(tree.verify_any_mode(leaf, &proof[1..]).unwrap(), None);
+            }
+        }
+
+        let string = DynSolValue::String("a".to_string());
+        let strings = StandardMerkleTree::of(&[string.clone()]).unwrap();
+        assert_eq!(
+            strings.verify_any_mode(&string, &[]).unwrap(),
+            Some(LeafHashMode::Double)
+        );
+        assert!(matches!(
+            strings.verify_any_mode(&DynSolValue::Bytes(vec![1]), &[]),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+    }
+
+    /// Tests that sorted and positional trees built from options have different roots, and that both
+    /// verify their proofs.
+    #[test]
+    fn test_of_with_options_positional() {
+        let leaves = (0..6)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let positional_options = TreeOptions {
+            hash_order: HashOrder::Positional,
+            ..TreeOptions::default()
+        };
+        let sorted = StandardMerkleTree::of_with_options(&leaves, TreeOptions::default()).unwrap();
+        let positional = StandardMerkleTree::of_with_options(&leaves, positional_options).unwrap();
+        assert_eq!(
+            sorted.root(),
+            StandardMerkleTree::of(&leaves).unwrap().root()
+        );
+        assert_eq!(
+            positional.root(),
+            StandardMerkleTree::builder()
+                .hash_order(HashOrder::Positional)
+                .build(&leaves)
+                .unwrap()
+                .root()
+        );
+        assert_ne!(sorted.root(), positional.root());
+
+        for leaf in leaves.iter() {
+            assert!(sorted.verify_proof(leaf, sorted.get_proof(leaf).unwrap()));
+            let (siblings, positions) = positional.get_proof_with_positions(leaf).unwrap();
+            assert!(positional.verify_proof(leaf, siblings.clone()));
+            assert!(verify_with_positions(
+                positional.root().unwrap(),
+                standard_leaf_hash(leaf).unwrap(),
+                &siblings,
+                &positions
+            ));
+        }
+
+        let rebuilt = StandardMerkleTree::of_with_options(&leaves, *positional.options()).unwrap();
+        assert_eq!(rebuilt.root(), positional.root());
+    }
+
+    /// Tests that a tree serialized to JSON deserializes to a tree with the same root and proofs.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let leaves = [
+            DynSolValue::String("a".to_string()),
+            DynSolValue::FixedBytes(keccak256("b"), 32),
+            DynSolValue::Uint(U256::from(3), 256),
+            DynSolValue::Tuple(vec![
+                DynSolValue::Address(Address::repeat_byte(4)),
+                DynSolValue::Uint(U256::from(5), 256),
+            ]),
+            DynSolValue::String("a".to_string()),
+        ];
+        let tree = StandardMerkleTree::builder()
+            .hash_order(HashOrder::Positional)
+            .build(&leaves)
+            .unwrap();
+
+        let json = serde_json::to_string(&tree).unwrap();
+        let loaded: StandardMerkleTree = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.root(), tree.root());
+        assert_eq!(loaded.options(), tree.options());
+        assert_eq!(loaded.values(), tree.values());
+        for leaf in leaves.iter() {
+            let proof = loaded.get_proof(leaf).unwrap();
+            assert_eq!(proof, tree.get_proof(leaf).unwrap());
+            assert!(loaded.verify_proof(leaf, proof));
+        }
+        assert_eq!(
+            loaded.get_proof_by_index(4).unwrap(),
+            tree.get_proof_by_index(4).unwrap()
+        );
+
+        let error = serde_json::to_string(&MerkleTreeError::InvalidAbiEncoding(3)).unwrap();
+        assert!(matches!(
+            serde_json::from_str(&error).unwrap(),
+            MerkleTreeError::InvalidAbiEncoding(3)
+        ));
+    }
+
+    /// Tests [`uniform_root`] against full builds of trees of identical leaves.
+    #[test]
+    fn test_uniform_root() {
+        let leaf_hash = keccak256("leaf");
+        for count in [1, 2, 4, 6] {
+            let leaves = vec![DynSolValue::FixedBytes(leaf_hash, 32); count];
+            let tree = StandardMerkleTree::of_with_options(
+                &leaves,
+                TreeOptions {
+                    leaf_hash_mode: LeafHashMode::Raw,
+                    ..TreeOptions::default()
+                },
+            )
+            .unwrap();
+            assert_eq!(uniform_root(leaf_hash, count), tree.root());
+        }
+        assert_eq!(uniform_root(leaf_hash, 0), None);
+    }
+
+    /// Tests that a tree, built in parallel with the `rayon` feature, has the root of a plain
+    /// sequential build. `benches/parallel_build.rs` checks the same for 100k leaves.
+    #[test]
+    fn test_tree_matches_sequential_build() {
+        let leaves = (0..1_000u64)
+            .map(|i| DynSolValue::Uint(U256::from(i), 256))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        let tree_len = 2 * leaves.len() - 1;
+        let mut nodes = vec![B256::ZERO; tree_len];
+        for (i, leaf) in leaves.iter().enumerate() {
+            nodes[tree_len - 1 - i] = standard_leaf_hash(leaf).unwrap();
+        }
+        for i in (0..tree_len - leaves.len()).rev() {
+            nodes[i] = hash_pair(nodes[2 * i + 2], nodes[2 * i + 1]);
+        }
+        assert_eq!(tree.root(), Some(nodes[0]));
+    }
+
+    /// Tests that [`StandardMerkleTree::proofs_consistent`] accepts every pair of valid proofs, in
+    /// sorted and positional trees, and rejects pairs with an invalid or foreign proof.
+    #[test]
+    fn test_proofs_consistent() {
+        let leaves = (0..7)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let other = StandardMerkleTree::of(&leaves[1..]).unwrap();
+        for order in [HashOrder::Sorted, HashOrder::Positional] {
+            let tree = StandardMerkleTree::builder()
+                .hash_order(order)
+                .build(&leaves)
+                .unwrap();
+            let proofs = leaves
+                .iter()
+                .map(|leaf| tree.get_proof(leaf).unwrap())
+                .collect::<Vec<Vec<B256>>>();
+            for (a, proof_a) in proofs.iter().enumerate() {
+                for (b, proof_b) in proofs.iter().enumerate() {
+                    assert!(tree
+                        .proofs_consistent(&leaves[a], proof_a, &leaves[b], proof_b)
+                        .unwrap());
+                }
+            }
+
+            let mut tampered = proofs[1].clone();
+            tampered[0] = B256::ZERO;
+            assert!(!tree
+                .proofs_consistent(&leaves[0], &proofs[0], &leaves[1], &tampered)
+                .unwrap());
+            let foreign = other.get_proof(&leaves[1]).unwrap();
+            assert!(!tree
+                .proofs_consistent(&leaves[0], &proofs[0], &leaves[1], &foreign)
+                .unwrap());
+            assert!(matches!(
+                tree.proofs_consistent(&DynSolValue::Bytes(vec![1]), &[], &leaves[0], &proofs[0]),
+                Err(MerkleTreeError::NotSupportedType)
+            ));
+        }
+    }
+
+    /// Tests that [`paths_agree`] rejects paths that contradict each other, which hashing alone
+    /// can't produce.
+    #[test]
+    fn test_paths_agree_rejects_contradictions() {
+        let [root, a, b, c, d, leaf_a, leaf_b] = [1u8, 2, 3, 4, 5, 6, 7].map(B256::repeat_byte);
+
+        // Siblings under the root.
+        assert!(paths_agree(&[a, root], &[b], &[b, root], &[a]));
+        // Splitting into nodes that aren't each other's siblings.
+        assert!(!paths_agree(&[a, root], &[c], &[b, root], &[a]));
+        // Siblings under a shared node, and again with only one of them the other's sibling.
+        assert!(paths_agree(
+            &[leaf_a, a, root],
+            &[leaf_b, b],
+            &[leaf_b, a, root],
+            &[leaf_a, b]
+        ));
+        assert!(!paths_agree(
+            &[leaf_a, a, root],
+            &[c, b],
+            &[leaf_b, a, root],
+            &[leaf_a, b]
+        ));
+        // A shared node with different siblings.
+        assert!(!paths_agree(
+            &[leaf_a, a, root],
+            &[leaf_b, b],
+            &[leaf_b, a, root],
+            &[leaf_a, d]
+        ));
+        // A leaf that is an ancestor of another leaf.
+        assert!(!paths_agree(&[a, root], &[b], &[leaf_a, a, root], &[c, b]));
+    }
+
+    /// Tests that both occurrences of a duplicate leaf stay provable, whether the tree is built with
+    /// [`StandardMerkleTree::of`] or [`StandardMerkleTree::new`].
+    #[test]
+    fn test_duplicate_leaves_are_kept() {
+        let leaves = ["a", "b", "a", "c"].map(|leaf| DynSolValue::String(leaf.to_string()));
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let indexed = leaves
+            .iter()
+            .enumerate()
+            .map(|(leaf_index, leaf)| (leaf, tree.leaf_index_to_tree_index(leaf_index).unwrap()))
+            .collect::<Vec<(&DynSolValue, usize)>>();
+        let rebuilt = StandardMerkleTree::new(tree.tree.clone(), indexed).unwrap();
+
+        for tree in [&tree, &rebuilt] {
+            assert_eq!(tree.leaf_count(), 4);
+            assert_eq!(tree.leaf_positions(&leaves[0]).unwrap(), vec![0, 2]);
+            let first = tree.get_proof_by_index(0).unwrap();
+            let second = tree.get_proof_by_index(2).unwrap();
+            assert_ne!(first, second);
+            assert_eq!(tree.get_proof(&leaves[0]).unwrap(), first);
+            assert!(tree.verify_at_index(&leaves[0], 0, &first).unwrap());
+            assert!(tree.verify_at_index(&leaves[0], 2, &second).unwrap());
+        }
+    }
+
+    /// Tests that rendering a four-leaf tree shows the root, then every node indented under its
+    /// parent, with the four leaves marked.
+    #[test]
+    fn test_render() {
+        let leaves = (0..4)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let rendered = tree.render();
+
+        let lines = rendered.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(
+            lines[0],
+            alloc::format!("0) {}", short_hex(tree.root().unwrap()))
+        );
+        assert!(lines[1].starts_with("├─ 1) "));
+        assert!(lines[2].starts_with("│  ├─ 3) "));
+        assert!(lines[4].starts_with("└─ 2) "));
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let leaf_hash = short_hex(standard_leaf_hash(leaf).unwrap());
+            assert!(rendered.contains(&alloc::format!("{leaf_hash} leaf {leaf_index}\n")));
+        }
+        assert_eq!(StandardMerkleTree::of(&[]).unwrap().render(), "");
+    }
+
+    /// Tests the JSON field names and hex formatting of a [`ViemProof`].
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_viem_proof_json() {
+        let values = (1..=3u8)
+            .map(|i| {
+                vec![
+                    DynSolValue::Address(Address::repeat_byte(i)),
+                    DynSolValue::Uint(U256::from(i), 256),
+                ]
+            })
+            .collect::<Vec<Vec<DynSolValue>>>();
+        let tree = StandardMerkleTree::of_multi(&values).unwrap();
+        let leaf = DynSolValue::Tuple(values[1].clone());
+        let proof = tree.get_proof(&leaf).unwrap();
+
+        let json = serde_json::to_value(tree.viem_proof(&leaf).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "proof": proof.iter().map(ToString::to_string).collect::<Vec<String>>(),
+                "root": tree.root().unwrap().to_string(),
+                "value": [Address::repeat_byte(2).to_checksum(None), "2"],
+            })
+        );
+        assert!(json["root"].as_str().unwrap().starts_with("0x"));
+    }
+
+    /// Tests that [`StandardMerkleTree::verify_or_nearest`] reports the closest raw leaf to a
+    /// near-miss value, and verifies proofs for values of the tree.
+    #[test]
+    fn test_verify_or_nearest() {
+        let word = |i: u64| DynSolValue::FixedBytes(B256::from(U256::from(i)), 32);
+        let leaves = [100, 200, 300, 400].map(word);
+        let tree = StandardMerkleTree::of_with_options(
+            &leaves,
+            TreeOptions {
+                leaf_hash_mode: LeafHashMode::Raw,
+                ..TreeOptions::default()
+            },
+        )
+        .unwrap();
+
+        let proof = tree.get_proof(&leaves[1]).unwrap();
+        assert_eq!(
+            tree.verify_or_nearest(&leaves[1], &proof).unwrap(),
+            VerifyOutcome::Verified
+        );
+        assert_eq!(
+            tree.verify_or_nearest(&word(201), &proof).unwrap(),
+            VerifyOutcome::NotVerified {
+                nearest_leaf_hash: B256::from(U256::from(200))
+            }
+        );
+        assert_eq!(
+            tree.verify_or_nearest(&word(390), &[]).unwrap(),
+            VerifyOutcome::NotVerified {
+                nearest_leaf_hash: B256::from(U256::from(400))
+            }
+        );
+
+        let empty = StandardMerkleTree::of(&[]).unwrap();
+        assert!(matches!(
+            empty.verify_or_nearest(&word(1), &[]),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+    }
+
+    /// Tests that the leaf hash of a string is the double keccak256 of its bytes, with or without
+    /// a tree.
+    #[test]
+    fn test_leaf_hash_of_string() {
+        let value = DynSolValue::String("hello".to_string());
+        let expected = keccak256(keccak256(b"hello"));
+        assert_eq!(standard_leaf_hash(&value).unwrap(), expected);
+
+        let tree = StandardMerkleTree::of(&[DynSolValue::String("other".to_string())]).unwrap();
+        assert_eq!(tree.leaf_hash(&value).unwrap(), expected);
+        assert!(matches!(
+            tree.leaf_hash(&DynSolValue::Bytes(vec![1])),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+    }
+
+    /// Tests that the index [`StandardMerkleTree::index_of`] returns gives a valid proof through
+    /// [`StandardMerkleTree::get_proof_by_index`].
+    #[test]
+    fn test_index_of() {
+        let leaves = ["a", "b", "c", "b", "d"].map(|leaf| DynSolValue::String(leaf.to_string()));
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let index = tree.index_of(leaf).unwrap();
+            assert_eq!(index, if leaf_index == 3 { 1 } else { leaf_index });
+            let proof = tree.get_proof_by_index(index).unwrap();
+            assert!(tree.verify_at_index(leaf, index, &proof).unwrap());
+        }
+        assert!(matches!(
+            tree.index_of(&DynSolValue::String("e".to_string())),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+    }
+
+    /// Tests that a tree of commitments proves every one of them by index, hashed or raw.
+    #[test]
+    fn test_of_commitments() {
+        let commitments = (0..5u64)
+            .map(|i| keccak256(i.to_be_bytes()))
+            .collect::<Vec<B256>>();
+        let hashed = StandardMerkleTree::of_commitments(&commitments, LeafHashMode::Double);
+        let raw = StandardMerkleTree::of_commitments(&commitments, LeafHashMode::Raw);
+        assert_ne!(hashed.root(), raw.root());
+
+        for (leaf_index, commitment) in commitments.iter().enumerate() {
+            let value = DynSolValue::FixedBytes(*commitment, 32);
+            for tree in [&hashed, &raw] {
+                let proof = tree.get_proof_by_index(leaf_index).unwrap();
+                assert!(tree.verify_at_index(&value, leaf_index, &proof).unwrap());
+            }
+            let proof = hashed.get_proof_by_index(leaf_index).unwrap();
+            assert!(verify_hash(
+                hashed.root().unwrap(),
+                keccak256(keccak256(commitment)),
+                &proof
+            ));
+            let proof = raw.get_proof_by_index(leaf_index).unwrap();
+            assert!(verify_hash(raw.root().unwrap(), *commitment, &proof));
+        }
+    }
+
+    /// Tests the proof length histogram of a six-leaf tree, whose last two leaves sit a level
+    /// higher than the other four.
+    #[test]
+    fn test_proof_length_histogram() {
+        let leaves = (0..6)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        assert_eq!(tree.proof_length_histogram(), vec![(2, 2), (3, 4)]);
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let expected = if leaf_index < 4 { 3 } else { 2 };
+            assert_eq!(tree.get_proof(leaf).unwrap().len(), expected);
+        }
+
+        let perfect = StandardMerkleTree::of(&leaves[..4]).unwrap();
+        assert_eq!(perfect.proof_length_histogram(), vec![(2, 4)]);
+        assert!(StandardMerkleTree::of(&[])
+            .unwrap()
+            .proof_length_histogram()
+            .is_empty());
+    }
+
+    /// Tests that [`StandardMerkleTree::leaves`] and [`StandardMerkleTree::entries`] yield every leaf
+    /// of a five-leaf tree, in order.
+    #[test]
+    fn test_leaves_and_entries() {
+        let leaves = (0..5)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect::<Vec<DynSolValue>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+
+        let entries = tree.leaves().collect::<Vec<(usize, B256)>>();
+        assert_eq!(entries.len(), 5);
+        for (leaf_index, leaf_hash) in entries {
+            let tree_index = tree.leaf_index_to_tree_index(leaf_index).unwrap();
+            assert!(is_leaf_node(&tree.tree, tree_index));
+            assert_eq!(tree.tree[tree_index], leaf_hash);
+            assert_eq!(leaf_hash, standard_leaf_hash(&leaves[leaf_index]).unwrap());
+        }
+
+        assert_eq!(tree.entries().len(), 5);
+        for (leaf_index, value, leaf_hash) in tree.entries() {
+            assert_eq!(value, &leaves[leaf_index]);
+            assert_eq!(leaf_hash, tree.leaf_hashes()[leaf_index]);
+        }
+    }
+
+    /// Tests that hex proofs and roots are 66 characters and parse back to the original hashes.
+    #[test]
+    fn test_get_proof_hex() {
+        let leaves = ["a", "b", "c"].map(|leaf| DynSolValue::String(leaf.to_string()));
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        for leaf in leaves.iter() {
+            let proof = tree.get_proof(leaf).unwrap();
+            let proof_hex = tree.get_proof_hex(leaf).unwrap();
+            assert!(proof_hex.iter().all(|hash| hash.len() == 66));
+            let parsed = proof_hex
+                .iter()
+                .map(|hash| B256::from_hex(hash).unwrap())
+                .collect::<Vec<B256>>();
+            assert_eq!(parsed, proof);
+        }
+        assert_eq!(
+            tree.root_hex(),
+            "0xec5728bad90d3185989eddaae1e83be5f3e03ad9f48bd743df58dad4b7625505"
+        );
+        assert_eq!(
+            B256::from_hex(tree.root_hex()).unwrap(),
+            tree.root().unwrap()
+        );
+        assert_eq!(
+            StandardMerkleTree::default().root_hex(),
+            B256::ZERO.to_string()
+        );
+        assert!(matches!(
+            tree.get_proof_hex(&DynSolValue::String("d".to_string())),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+    }
+
+    /// Tests that trees over the same leaves built with different options share a fingerprint.
+    #[test]
+    fn test_leaf_set_fingerprint() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let configured = StandardMerkleTree::builder()
+            .chain_id(1)
+            .hash_order(HashOrder::Positional)
+            .sort_by(|a, b| b.as_str().cmp(&a.as_str()))
+            .build(&leaves)
+            .unwrap();
+        assert_ne!(configured.root(), tree.root());
+        assert_eq!(
+            configured.leaf_set_fingerprint(),
+            tree.leaf_set_fingerprint()
+        );
+
+        let mut reversed = leaves.clone();
+        reversed.reverse();
+        assert_eq!(
+            StandardMerkleTree::of(&reversed)
+                .unwrap()
+                .leaf_set_fingerprint(),
+            tree.leaf_set_fingerprint()
+        );
+
+        assert_ne!(
+            StandardMerkleTree::of(&leaves[..4])
+                .unwrap()
+                .leaf_set_fingerprint(),
+            tree.leaf_set_fingerprint()
+        );
+    }
+
+    /// Tests that built trees pass the integrity check and a tree with a flipped byte fails it.
+    #[test]
+    fn test_verify_integrity() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let trees = [
+            StandardMerkleTree::of(&leaves).unwrap(),
+            StandardMerkleTree::builder()
+                .hash_order(HashOrder::Positional)
+                .build(&leaves)
+                .unwrap(),
+            StandardMerkleTree::builder()
+                .odd_node_policy(OddNodePolicy::HashWithSelf)
+                .build(&leaves)
+                .unwrap(),
+            StandardMerkleTree::builder()
+                .odd_node_policy(OddNodePolicy::HashWithZero)
+                .build(&leaves)
+                .unwrap(),
+        ];
+        for mut tree in trees {
+            assert!(tree.verify_integrity().is_ok());
+            for index in 0..tree.tree.len() {
+                tree.tree[index].0[31] ^= 1;
+                assert!(matches!(
+                    tree.verify_integrity(),
+                    Err(MerkleTreeError::InvalidCheck)
+                ));
+                tree.tree[index].0[31] ^= 1;
+            }
+        }
+
+        assert!(StandardMerkleTree::default().verify_integrity().is_ok());
+        let single = StandardMerkleTree::of(&leaves[..1]).unwrap();
+        assert!(single.verify_integrity().is_ok());
+    }
+
+    /// Tests comparing a multiproof with individual proofs of the same and of different leaves.
+    #[test]
+    fn test_multiproof_matches_individual() {
+        let leaves: Vec<DynSolValue> = (0..9).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let individual = |subset: &[usize]| {
+            subset
+                .iter()
+                .map(|i| (leaves[*i].clone(), tree.get_proof(&leaves[*i]).unwrap()))
+                .collect::<Vec<(DynSolValue, Vec<B256>)>>()
+        };
+        let multi_proof = tree
+            .get_multi_proof(&[leaves[8].clone(), leaves[0].clone(), leaves[4].clone()])
+            .unwrap();
+
+        assert!(tree
+            .multiproof_matches_individual(&multi_proof, &individual(&[0, 4, 8]))
+            .unwrap());
+        assert!(tree
+            .multiproof_matches_individual(&multi_proof, &individual(&[4, 8, 0]))
+            .unwrap());
+        assert!(!tree
+            .multiproof_matches_individual(&multi_proof, &individual(&[0, 4]))
+            .unwrap());
+        assert!(!tree
+            .multiproof_matches_individual(&multi_proof, &individual(&[0, 4, 7]))
+            .unwrap());
+        assert!(!tree
+            .multiproof_matches_individual(&multi_proof, &individual(&[0, 4, 8, 8]))
+            .unwrap());
+
+        // A proof that doesn't verify fails the comparison even for the same leaves.
+        let mut forged = individual(&[0, 4, 8]);
+        forged[0].1 = forged[1].1.clone();
+        assert!(!tree
+            .multiproof_matches_individual(&multi_proof, &forged)
+            .unwrap());
+        let mut forged_multi_proof = multi_proof.clone();
+        forged_multi_proof.proof[0] = B256::ZERO;
+        assert!(!tree
+            .multiproof_matches_individual(&forged_multi_proof, &individual(&[0, 4, 8]))
+            .unwrap());
+
+        assert!(matches!(
+            tree.multiproof_matches_individual(
+                &multi_proof,
+                &[(DynSolValue::Bytes(vec![1]), Vec::new())]
+            ),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+    }
+
+    /// Tests that every proof of trees with odd levels verifies against the root, as OpenZeppelin
+    /// folds it, and that an array with an unpaired node yields no proof.
+    #[test]
+    fn test_proofs_with_unpaired_nodes() {
+        for count in [3, 5, 6, 7] {
+            let leaves: Vec<DynSolValue> = (0..count)
+                .map(|i| DynSolValue::String(i.to_string()))
+                .collect();
+            let tree = StandardMerkleTree::of(&leaves).unwrap();
+            let root = tree.root().unwrap();
+            for (leaf_index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.get_proof(leaf).unwrap();
+                let tree_index = tree.leaf_index_to_tree_index(leaf_index).unwrap();
+                assert_eq!(proof.len(), node_depth(tree_index));
+                let folded = proof
+                    .iter()
+                    .fold(standard_leaf_hash(leaf).unwrap(), |hash, sibling| {
+                        hash_pair(hash, *sibling)
+                    });
+                assert_eq!(folded, root);
+                assert!(verify(root, leaf, &proof));
+            }
+        }
+
+        let leaves = ["a", "b"].map(|leaf| DynSolValue::String(leaf.to_string()));
+        let malformed = StandardMerkleTree::new(
+            vec![B256::ZERO, standard_leaf_hash(&leaves[0]).unwrap()],
+            vec![(&leaves[0], 1)],
+        )
+        .unwrap();
+        assert!(matches!(
+            malformed.get_proof(&leaves[0]),
+            Err(MerkleTreeError::InvalidCheck)
+        ));
+    }
+
+    /// Tests that every proof returned with the root verifies against it.
+    #[test]
+    fn test_build_and_split() {
+        let leaves: Vec<DynSolValue> = (0..7).map(|i| DynSolValue::String(i.to_string())).collect();
+        let (root, proofs) = StandardMerkleTree::build_and_split(&leaves).unwrap();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        assert_eq!(root, tree.root().unwrap());
+        assert_eq!(proofs.len(), leaves.len());
+        for (leaf_index, proof) in proofs {
+            assert!(verify(root, &leaves[leaf_index], &proof));
+            assert_eq!(proof, tree.get_proof_by_index(leaf_index).unwrap());
+        }
+
+        assert!(matches!(
+            StandardMerkleTree::build_and_split(&[]),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+        assert!(matches!(
+            StandardMerkleTree::build_and_split(&[DynSolValue::Bytes(vec![1])]),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+    }
+
+    /// Tests that pushing and extending leaves one batch at a time matches building the tree at once.
+    #[test]
+    fn test_push_and_extend() {
+        let leaves: Vec<DynSolValue> = (0..20)
+            .map(|i| DynSolValue::String(i.to_string()))
+            .collect();
+        let mut tree = StandardMerkleTree::default();
+        tree.reserve(leaves.len());
+        for (count, leaf) in leaves.iter().enumerate() {
+            tree.push(leaf).unwrap();
+            let built = StandardMerkleTree::of(&leaves[..=count]).unwrap();
+            assert_eq!(tree.root(), built.root());
+            assert_eq!(tree.nodes(), built.nodes());
+        }
+        assert_eq!(tree.values(), leaves.as_slice());
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            assert_eq!(tree.index_of(leaf).unwrap(), leaf_index);
+            let proof = tree.get_proof(leaf).unwrap();
+            assert!(tree.verify_proof(leaf, proof));
+        }
+
+        let mut padded = StandardMerkleTree::builder()
+            .odd_node_policy(OddNodePolicy::HashWithSelf)
+            .build(&leaves[..3])
+            .unwrap();
+        padded.extend(&leaves[3..10]).unwrap();
+        padded.extend(&[]).unwrap();
+        padded.push(&leaves[2]).unwrap();
+        let mut expected = leaves[..10].to_vec();
+        expected.push(leaves[2].clone());
+        let built = StandardMerkleTree::builder()
+            .odd_node_policy(OddNodePolicy::HashWithSelf)
+            .build(&expected)
+            .unwrap();
+        assert_eq!(padded.root(), built.root());
+        assert_eq!(padded.leaf_positions(&leaves[2]).unwrap(), [2, 10]);
+
+        assert!(matches!(
+            padded.push(&DynSolValue::Bytes(vec![1])),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+        assert_eq!(padded.root(), built.root());
+    }
+
+    /// Tests that a single-hash tree matches a tree of single-keccak leaves computed by hand.
+    #[test]
+    fn test_single_leaf_hash_mode() {
+        let leaves = ["a", "b", "c"].map(|leaf| DynSolValue::String(leaf.to_string()));
+        let [a, b, c] = ["a", "b", "c"].map(keccak256);
+        let tree = StandardMerkleTree::builder()
+            .leaf_hash_mode(LeafHashMode::Single)
+            .build(&leaves)
+            .unwrap();
+        assert_eq!(tree.options().leaf_hash_mode, LeafHashMode::Single);
+
+        assert_eq!(tree.leaf_hashes(), [a, b, c]);
+        assert_eq!(tree.root().unwrap(), hash_pair(hash_pair(a, b), c));
+        assert_ne!(tree.root(), StandardMerkleTree::of(&leaves).unwrap().root());
+
+        for (leaf, leaf_hash) in leaves.iter().zip([a, b, c]) {
+            assert_eq!(tree.leaf_hash(leaf).unwrap(), leaf_hash);
+            let proof = tree.get_proof(leaf).unwrap();
+            assert_eq!(
+                tree.verify_any_mode(leaf, &proof).unwrap(),
+                Some(LeafHashMode::Single)
+            );
+            assert!(tree.verify_proof(leaf, proof));
+        }
+    }
+
+    /// Tests proofs of present and absent leaves of a tree built by index.
+    #[test]
+    fn test_of_by_index() {
+        let empty = keccak256("empty");
+        let entries: Vec<(usize, DynSolValue)> = [7, 1, 4]
+            .into_iter()
+            .map(|leaf_index| (leaf_index, DynSolValue::String(leaf_index.to_string())))
+            .collect();
+        let tree = StandardMerkleTree::of_by_index(&entries, 10, empty).unwrap();
+        let root = tree.root().unwrap();
+        assert_eq!(tree.leaf_count(), 10);
+        assert_eq!(
+            tree.values(),
+            [1, 4, 7].map(|leaf_index| DynSolValue::String(leaf_index.to_string()))
+        );
+
+        for (leaf_index, value) in entries.iter() {
+            let proof = tree.get_proof(value).unwrap();
+            assert_eq!(proof, tree.get_proof_by_index(*leaf_index).unwrap());
+            assert!(verify(root, value, &proof));
+            assert_eq!(tree.index_of(value).unwrap(), *leaf_index);
+        }
+        for leaf_index in [0, 2, 3, 5, 6, 8, 9] {
+            let proof = tree.get_proof_by_index(leaf_index).unwrap();
+            assert_eq!(process_proof(empty, &proof), root);
+        }
+
+        let mut leaf_hashes = vec![empty; 10];
+        for (leaf_index, value) in entries.iter() {
+            leaf_hashes[*leaf_index] = standard_leaf_hash(value).unwrap();
+        }
+        assert_eq!(tree.leaf_hashes(), leaf_hashes);
+
+        let value = DynSolValue::String("a".to_string());
+        assert!(matches!(
+            StandardMerkleTree::of_by_index(&[(10, value.clone())], 10, empty),
+            Err(MerkleTreeError::LeafNotFound)
+        ));
+        assert!(matches!(
+            StandardMerkleTree::of_by_index(&[(3, value.clone()), (3, value)], 10, empty),
+            Err(MerkleTreeError::DuplicatedIndex)
+        ));
+        assert!(matches!(
+            StandardMerkleTree::of_by_index(&[(3, DynSolValue::Bytes(vec![1]))], 10, empty),
+            Err(MerkleTreeError::NotSupportedType)
+        ));
+    }
+
+    /// Tests that the values of a tree built by index keep their own leaf index through
+    /// [`StandardMerkleTree::entries`], dumps, updates and appends.
+    #[test]
+    fn test_of_by_index_value_positions() {
+        let empty = keccak256("empty");
+        let value = |i: u64| DynSolValue::Uint(U256::from(i), 256);
+        let entries = [(7, value(7)), (1, value(1)), (4, value(4))];
+        let mut tree = StandardMerkleTree::of_by_index(&entries, 10, empty).unwrap();
+
+        let listed = tree
+            .entries()
+            .map(|(leaf_index, value, leaf_hash)| (leaf_index, value.clone(), leaf_hash))
+            .collect::<Vec<(usize, DynSolValue, B256)>>();
+        let expected = [1, 4, 7].map(|i| {
+            (
+                i,
+                value(i as u64),
+                standard_leaf_hash(&value(i as u64)).unwrap(),
+            )
+        });
+        assert_eq!(listed, expected);
+
+        let data = tree.dump();
+        assert_eq!(
+            data.values
+                .iter()
+                .map(|entry| entry.tree_index)
+                .collect::<Vec<usize>>(),
+            [1, 4, 7].map(|i| tree.leaf_index_to_tree_index(i).unwrap())
+        );
+        let loaded = StandardMerkleTree::load(data).unwrap();
+        assert_eq!(loaded.root(), tree.root());
+        assert_eq!(loaded.values(), tree.values());
+        assert_eq!(loaded.index_of(&value(4)).unwrap(), 4);
+        assert!(loaded.verify_leaf_consistency().is_ok());
+
+        // Updating a present leaf replaces its own value; an absent leaf gains one.
+        tree.update_leaf(4, &value(40)).unwrap();
+        tree.update_leaf(2, &value(2)).unwrap();
+        assert_eq!(tree.values(), [1, 2, 40, 7].map(value));
+        assert_eq!(tree.index_of(&value(2)).unwrap(), 2);
+        assert!(tree.verify_leaf_consistency().is_ok());
+        let proof = tree.get_proof(&value(40)).unwrap();
+        assert!(tree.verify_proof(&value(40), proof));
+
+        tree.push(&value(10)).unwrap();
+        assert_eq!(tree.leaf_count(), 11);
+        assert_eq!(
+            tree.entries()
+                .map(|(leaf_index, ..)| leaf_index)
+                .collect::<Vec<usize>>(),
+            [1, 2, 4, 7, 10]
+        );
+        assert!(tree.verify_leaf_consistency().is_ok());
+        let reloaded = StandardMerkleTree::load(tree.dump()).unwrap();
+        assert_eq!(reloaded.root(), tree.root());
+        assert_eq!(reloaded.index_of(&value(10)).unwrap(), 10);
+    }
+
+    /// Tests that built trees have consistent leaves and that a corrupted value is detected.
+    #[test]
+    fn test_verify_leaf_consistency() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let mut tree = StandardMerkleTree::of(&leaves).unwrap();
+        assert!(tree.verify_leaf_consistency().is_ok());
+        tree.update_leaf(1, &leaves[3]).unwrap();
+        assert!(tree.verify_leaf_consistency().is_ok());
+
+        tree.values[2] = DynSolValue::String("corrupted".to_string());
+        assert!(matches!(
+            tree.verify_leaf_consistency(),
+            Err(MerkleTreeError::InconsistentLeaf(2))
+        ));
+
+        let chain_bound = StandardMerkleTree::builder()
+            .chain_id(1)
+            .build(&leaves)
+            .unwrap();
+        assert!(chain_bound.verify_leaf_consistency().is_ok());
+        let entries = [(6, leaves[0].clone()), (2, leaves[1].clone())];
+        let by_index = StandardMerkleTree::of_by_index(&entries, 8, B256::ZERO).unwrap();
+        assert!(by_index.verify_leaf_consistency().is_ok());
+    }
+
+    /// Tests that a cloned tree compares equal and produces the same proofs.
+    #[test]
+    fn test_clone_and_eq() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let mut clone = tree.clone();
+        assert_eq!(clone, tree);
+        for leaf in leaves.iter() {
+            assert_eq!(
+                clone.get_proof(leaf).unwrap(),
+                tree.get_proof(leaf).unwrap()
+            );
+        }
+
+        clone.update_leaf(0, &leaves[1]).unwrap();
+        assert_ne!(clone, tree);
+        assert_ne!(StandardMerkleTree::of(&leaves[..4]).unwrap(), tree);
+        assert_eq!(
+            tree.get_proof(&DynSolValue::String("5".to_string())),
+            Err(MerkleTreeError::LeafNotFound)
+        );
+    }
+
+    /// Tests that processing the proof of every leaf hash yields the root.
+    #[test]
+    fn test_process_proof() {
+        let leaves: Vec<DynSolValue> = (0..6).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        for leaf in leaves.iter() {
+            let proof = tree.get_proof(leaf).unwrap();
+            let leaf_hash = standard_leaf_hash(leaf).unwrap();
+            assert_eq!(process_proof(leaf_hash, &proof), tree.root().unwrap());
+            assert_ne!(process_proof(leaf_hash, &proof[1..]), tree.root().unwrap());
+        }
+    }
+
+    /// Tests that the proof of a leaf hash is the proof of its value.
+    #[test]
+    fn test_get_proof_by_hash() {
+        let leaves: Vec<DynSolValue> = (0..7).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        for leaf in leaves.iter() {
+            assert_eq!(
+                tree.get_proof_by_hash(standard_leaf_hash(leaf).unwrap())
+                    .unwrap(),
+                tree.get_proof(leaf).unwrap()
+            );
+        }
+        assert_eq!(
+            tree.get_proof_by_hash(tree.root().unwrap()),
+            Err(MerkleTreeError::LeafNotFound)
+        );
+    }
+
+    /// Tests that over-long and empty proofs are rejected, except the empty proof of a single leaf.
+    #[test]
+    fn test_verify_proof_length() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        for leaf in leaves.iter() {
+            let mut proof = tree.get_proof(leaf).unwrap();
+            assert!(proof.len() <= tree.depth());
+            assert!(tree.verify_proof(leaf, proof.clone()));
+            proof.push(B256::ZERO);
+            assert!(!tree.verify_proof(leaf, proof));
+        }
+        let too_long = vec![B256::ZERO; tree.depth() + 1];
+        assert!(!tree.verify_proof(&leaves[0], too_long));
+        assert!(!tree.verify_proof(&leaves[0], Vec::new()));
+
+        let single = StandardMerkleTree::of(&leaves[..1]).unwrap();
+        assert_eq!(single.depth(), 0);
+        assert!(single.verify_proof(&leaves[0], Vec::new()));
+        assert!(!single.verify_proof(&leaves[0], vec![B256::ZERO]));
+    }
+
+    /// Tests that a tree of the leaf hashes of some values has the root of the tree of the values.
+    #[test]
+    fn test_of_hashes() {
+        let leaves: Vec<DynSolValue> = (0..6).map(|i| DynSolValue::String(i.to_string())).collect();
+        let leaf_hashes = leaves
+            .iter()
+            .map(|leaf| standard_leaf_hash(leaf).unwrap())
+            .collect::<Vec<B256>>();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let hashed = StandardMerkleTree::of_hashes(&leaf_hashes);
+        assert_eq!(hashed.root(), tree.root());
+        assert_eq!(hashed.nodes(), tree.nodes());
+
+        for (leaf, leaf_hash) in leaves.iter().zip(leaf_hashes) {
+            let proof = hashed.get_proof_by_hash(leaf_hash).unwrap();
+            assert_eq!(proof, tree.get_proof(leaf).unwrap());
+            assert!(hashed.verify_proof_by_hash(leaf_hash, &proof));
+            assert!(tree.verify_proof_by_hash(leaf_hash, &proof));
+            assert!(!hashed.verify_proof_by_hash(keccak256(leaf_hash), &proof));
+        }
+
+        let tx_hashes = [keccak256("a"), keccak256("b"), keccak256("c")];
+        let positional = StandardMerkleTree::of_tx_hashes(&tx_hashes);
+        for tx_hash in tx_hashes {
+            let proof = positional.get_proof_by_hash(tx_hash).unwrap();
+            assert!(positional.verify_proof_by_hash(tx_hash, &proof));
+        }
+    }
+
+    /// Tests reconstructing the root of a positional tree by hashing each sibling on its side.
+    #[test]
+    fn test_get_proof_with_sides() {
+        let leaves: Vec<DynSolValue> = (0..7).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::builder()
+            .hash_order(HashOrder::Positional)
+            .build(&leaves)
+            .unwrap();
+        for leaf in leaves.iter() {
+            let proof = tree.get_proof_with_sides(leaf).unwrap();
+            let root = proof.iter().fold(
+                standard_leaf_hash(leaf).unwrap(),
+                |node, (sibling, sibling_is_left)| {
+                    if *sibling_is_left {
+                        keccak256([sibling.as_slice(), node.as_slice()].concat())
+                    } else {
+                        keccak256([node.as_slice(), sibling.as_slice()].concat())
+                    }
+                },
+            );
+            assert_eq!(root, tree.root().unwrap());
+
+            let (siblings, positions) = tree.get_proof_with_positions(leaf).unwrap();
+            assert_eq!(
+                proof,
+                siblings.into_iter().zip(positions).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    /// Tests checking the tree and proofs against matching and mismatching external roots.
+    #[test]
+    fn test_external_root() {
+        let leaves: Vec<DynSolValue> = (0..5).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let root = tree.root().unwrap();
+        let other = StandardMerkleTree::of(&leaves[..4])
+            .unwrap()
+            .root()
+            .unwrap();
+
+        assert!(tree.has_root(root));
+        assert!(tree.assert_root(root).is_ok());
+        assert!(!tree.has_root(other));
+        assert_eq!(tree.assert_root(other), Err(MerkleTreeError::InvalidCheck));
+        assert!(!StandardMerkleTree::default().has_root(B256::ZERO));
+
+        let positional = StandardMerkleTree::builder()
+            .hash_order(HashOrder::Positional)
+            .build(&leaves)
+            .unwrap();
+        for tree in [tree, positional] {
+            let root = tree.root().unwrap();
+            for leaf in leaves.iter() {
+                let proof = tree.get_proof(leaf).unwrap();
+                assert!(tree.verify_proof_against(root, leaf, &proof));
+                assert!(!tree.verify_proof_against(other, leaf, &proof));
+            }
+        }
+    }
+
+    /// Tests that a string and the `bytes32` it spells out are distinct leaves with their own proofs.
+    #[test]
+    fn test_string_and_bytes_keys_dont_collide() {
+        let bytes = keccak256("a");
+        let leaves = [
+            DynSolValue::String(bytes.to_string()),
+            DynSolValue::FixedBytes(bytes, 32),
+            DynSolValue::String("b".to_string()),
+        ];
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            assert_eq!(tree.index_of(leaf).unwrap(), leaf_index);
+            let proof = tree.get_proof(leaf).unwrap();
+            assert_eq!(proof, tree.get_proof_by_index(leaf_index).unwrap());
+            assert!(tree.verify_proof(leaf, proof));
+        }
+        assert_ne!(
+            tree.get_proof(&leaves[0]).unwrap(),
+            tree.get_proof(&leaves[1]).unwrap()
+        );
+    }
+
+    /// Tests the [`Display`](core::fmt::Display) output of [`MerkleTreeError`].
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            MerkleTreeError::LeafNotFound.to_string(),
+            "leaf not found in the tree"
+        );
+        assert_eq!(
+            MerkleTreeError::NodeUnavailable(3).to_string(),
+            "node at tree index 3 is unavailable"
+        );
+        #[cfg(feature = "std")]
+        {
+            let error: Box<dyn std::error::Error> = Box::new(MerkleTreeError::ProofRead);
+            assert_eq!(error.to_string(), "failed to read the proof");
+        }
+    }
+
+    /// Tests that [`StandardMerkleTree::dump_to`] streams the same JSON as `serde_json` serializes
+    /// the dump to, escapes included.
+    #[cfg(all(feature = "serde", feature = "std"))]
+    #[test]
+    fn test_dump_to() {
+        let leaves = [
+            "alice",
+            "quote \" and \\ slash",
+            "tab\tcontrol\u{1}\u{7f} é",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            vec![
+                DynSolValue::String(name.to_string()),
+                DynSolValue::Uint(U256::from(i), 256),
+            ]
+        })
+        .collect::<Vec<Vec<DynSolValue>>>();
+        for tree in [
+            StandardMerkleTree::of_multi(&leaves).unwrap(),
+            StandardMerkleTree::of(&[]).unwrap(),
+        ] {
+            let mut streamed = Vec::new();
+            tree.dump_to(&mut streamed).unwrap();
+            assert_eq!(
+                String::from_utf8(streamed).unwrap(),
+                serde_json::to_string(&tree.dump()).unwrap()
+            );
+        }
+
+        struct Failing;
+        impl std::io::Write for Failing {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::Other.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let tree = StandardMerkleTree::of_multi(&leaves).unwrap();
+        assert_eq!(tree.dump_to(Failing), Err(MerkleTreeError::DumpWrite));
+    }
+
+    /// Tests that a multiproof covers exactly the leaves it proves, in any order, and no others.
+    #[test]
+    fn test_verify_multiproof_covers() {
+        let leaves: Vec<DynSolValue> = (0..9).map(|i| DynSolValue::String(i.to_string())).collect();
+        let tree = StandardMerkleTree::of(&leaves).unwrap();
+        let leaf_hashes = |subset: &[usize]| {
+            subset
+                .iter()
+                .map(|i| standard_leaf_hash(&leaves[*i]).unwrap())
+                .collect::<Vec<B256>>()
+        };
+        let multi_proof = tree
+            .get_multi_proof(&[leaves[2].clone(), leaves[7].clone(), leaves[5].clone()])
+            .unwrap();
+
+        assert_eq!(multiproof_leaves(&multi_proof), &multi_proof.leaves[..]);
+        let mut covered = multiproof_leaves(&multi_proof).to_vec();
+        covered.sort_unstable();
+        let mut expected = leaf_hashes(&[2, 5, 7]);
+        expected.sort_unstable();
+        assert_eq!(covered, expected);
+
+        assert_eq!(
+            tree.verify_multiproof_covers(&multi_proof, &leaf_hashes(&[2, 5, 7])),
+            Ok(true)
+        );
+        assert_eq!(
+            tree.verify_multiproof_covers(&multi_proof, &leaf_hashes(&[7, 2, 5])),
+            Ok(true)
+        );
+        for mismatched in [&[2, 5][..], &[2, 5, 6], &[2, 5, 7, 7], &[]] {
+            assert_eq!(
+                tree.verify_multiproof_covers(&multi_proof, &leaf_hashes(mismatched)),
+                Ok(false)
+            );
+        }
+
+        // A multiproof that doesn't verify covers nothing, even listing the expected leaves.
+        let mut forged = multi_proof.clone();
+        forged.proof[0] = B256::ZERO;
+        assert_eq!(
+            tree.verify_multiproof_covers(&forged, &leaf_hashes(&[2, 5, 7])),
+            Ok(false)
+        );
+        forged.proof_flags.push(true);
+        assert_eq!(
+            tree.verify_multiproof_covers(&forged, &leaf_hashes(&[2, 5, 7])),
+            Err(MerkleTreeError::InvalidMultiProof)
+        );
+    }
+
+    /// Tests that multiproofs are rejected for positional trees and verify in padded trees.
+    #[test]
+    fn test_multi_proof_options() {
+        let leaves: Vec<DynSolValue> = (0..7).map(|i| DynSolValue::String(i.to_string())).collect();
+
+        let positional = StandardMerkleTree::builder()
+            .hash_order(HashOrder::Positional)
+            .build(&leaves)
+            .unwrap();
+        assert!(matches!(
+            positional.get_multi_proof(&leaves[1..3]),
+            Err(MerkleTreeError::InvalidMultiProof)
+        ));
+        // A sorted multiproof of the same leaves folds to another root, and is never accepted.
+        let sorted = StandardMerkleTree::of(&leaves).unwrap();
+        let multi_proof = sorted.get_multi_proof(&leaves[1..3]).unwrap();
+        assert!(!positional.verify_multi_proof(&multi_proof));
+        assert!(matches!(
+            positional.verify_multiproof_covers(&multi_proof, &multi_proof.leaves),
+            Err(MerkleTreeError::InvalidMultiProof)
+        ));
+
+        for policy in [OddNodePolicy::HashWithSelf, OddNodePolicy::HashWithZero] {
+            let padded = StandardMerkleTree::builder()
+                .odd_node_policy(policy)
+                .build(&leaves)
+                .unwrap();
+            for subset in [&leaves[..1], &leaves[2..5], &leaves[5..], &leaves[..]] {
+                let multi_proof = padded.get_multi_proof(subset).unwrap();
+                assert!(padded.verify_multi_proof(&multi_proof));
+                assert_eq!(
+                    process_multi_proof(&multi_proof).unwrap(),
+                    padded.root().unwrap()
+                );
+            }
         }
     }
 }
@@ -0,0 +1,57 @@
+//! Counters of the hashes computed building a [`StandardMerkleTree`], for profiling and capacity
+//! planning.
+//!
+//! Counts are kept per thread: a tree built on the current thread is counted in full, even with the
+//! `rayon` feature, and builds on other threads are not.
+//!
+//! [`StandardMerkleTree`]: crate::standard_binary_tree::StandardMerkleTree
+use core::cell::Cell;
+
+std::thread_local! {
+    static STATS: Cell<HashStats> = const { Cell::new(HashStats { leaf_hashes: 0, pair_hashes: 0 }) };
+}
+
+/// Numbers of hash operations computed on the current thread since the last [`reset`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HashStats {
+    /// Leaf hashes, one per leaf value.
+    pub leaf_hashes: usize,
+    /// Pair hashes, one per internal node.
+    pub pair_hashes: usize,
+}
+
+impl HashStats {
+    /// Returns the number of leaf and pair hashes, comparable to
+    /// [`rebuild_hash_cost`](crate::standard_binary_tree::rebuild_hash_cost).
+    pub fn total(&self) -> usize {
+        self.leaf_hashes + self.pair_hashes
+    }
+}
+
+/// Returns the hash counts of the current thread.
+pub fn snapshot() -> HashStats {
+    STATS.with(Cell::get)
+}
+
+/// Zeroes the hash counts of the current thread.
+pub fn reset() {
+    STATS.with(|stats| stats.set(HashStats::default()));
+}
+
+/// Adds `count` leaf hashes to the current thread's counts.
+pub(crate) fn record_leaf_hashes(count: usize) {
+    STATS.with(|stats| {
+        let mut current = stats.get();
+        current.leaf_hashes += count;
+        stats.set(current);
+    });
+}
+
+/// Adds `count` pair hashes to the current thread's counts.
+pub(crate) fn record_pair_hashes(count: usize) {
+    STATS.with(|stats| {
+        let mut current = stats.get();
+        current.pair_hashes += count;
+        stats.set(current);
+    });
+}
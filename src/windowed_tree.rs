@@ -0,0 +1,176 @@
+//! This module contains the [WindowedTree], one page of a dataset too large for a single tree,
+//! committed to as a chain of [StandardMerkleTree]s.
+
+use alloc::vec::Vec;
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::B256;
+
+use crate::standard_binary_tree::{
+    standard_leaf_hash, verify_positional, HashOrder, MerkleTreeError, OddNodePolicy,
+    StandardMerkleTree,
+};
+
+/// A page of a paginated dataset, whose tree commits to the root of the previous page.
+///
+/// The previous root is the first leaf of the page, a `bytes32` before the values of the page, so a
+/// page's root commits to every page before it and the roots of the pages form a verifiable chain.
+/// The first page of a chain links to [`B256::ZERO`].
+///
+/// The tree is padded with [`OddNodePolicy::HashWithSelf`] and hashed with
+/// [`HashOrder::Positional`], so the link leaf is the left node at every level and a value of the
+/// page can't be passed off as its link.
+#[derive(Debug)]
+pub struct WindowedTree {
+    /// The root of the previous page.
+    prev_root: B256,
+    /// The tree over `prev_root` followed by the values of the page.
+    tree: StandardMerkleTree,
+}
+
+impl WindowedTree {
+    /// Builds the page of `values` following the page whose root is `prev_root`.
+    ///
+    /// Returns [`MerkleTreeError::NotSupportedType`] if a value isn't of a supported leaf type.
+    pub fn new_page(prev_root: B256, values: &[DynSolValue]) -> Result<Self, MerkleTreeError> {
+        let mut leaves = Vec::with_capacity(values.len() + 1);
+        leaves.push(link_leaf(prev_root));
+        leaves.extend_from_slice(values);
+        Ok(Self {
+            prev_root,
+            tree: StandardMerkleTree::builder()
+                .odd_node_policy(OddNodePolicy::HashWithSelf)
+                .hash_order(HashOrder::Positional)
+                .build(&leaves)?,
+        })
+    }
+
+    /// Returns the root of the previous page.
+    pub fn prev_root(&self) -> B256 {
+        self.prev_root
+    }
+
+    /// Returns the root of the page, which the next page links to.
+    pub fn root(&self) -> B256 {
+        self.tree.root().expect("a page always has its link leaf")
+    }
+
+    /// Returns the tree of the page, to generate proofs for its values.
+    pub fn tree(&self) -> &StandardMerkleTree {
+        &self.tree
+    }
+
+    /// Generates a proof that the page links to [`Self::prev_root`], checked by [`verify_link`].
+    pub fn link_proof(&self) -> Vec<B256> {
+        self.tree
+            .get_proof_by_index(0)
+            .expect("a page always has its link leaf")
+    }
+
+    /// Verifies that `pages` form a chain whose roots are `roots`, in order.
+    ///
+    /// Every page must have the matching root and link to the root before it. The first page may
+    /// link to any root, so that a chain can be verified from any page on.
+    pub fn verify_chain(pages: &[Self], roots: &[B256]) -> bool {
+        pages.len() == roots.len()
+            && pages
+                .iter()
+                .zip(roots)
+                .enumerate()
+                .all(|(index, (page, root))| {
+                    page.root() == *root
+                        && (index == 0 || page.prev_root == roots[index - 1])
+                        && verify_link(*root, page.prev_root, &page.link_proof())
+                })
+    }
+}
+
+/// Verifies a proof from [`WindowedTree::link_proof`] that the page with the given `root` links to
+/// `prev_root`.
+///
+/// The proof is folded as that of the first leaf, so it only verifies for the link leaf itself.
+pub fn verify_link(root: B256, prev_root: B256, proof: &[B256]) -> bool {
+    standard_leaf_hash(&link_leaf(prev_root)).map_or(false, |leaf_hash| {
+        verify_positional(root, leaf_hash, 0, proof)
+    })
+}
+
+/// Returns the leaf linking a page to the previous one.
+fn link_leaf(prev_root: B256) -> DynSolValue {
+    DynSolValue::FixedBytes(prev_root, 32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{verify_link, WindowedTree};
+    use crate::alloc::string::ToString;
+    use alloc::vec::Vec;
+    use alloy_dyn_abi::DynSolValue;
+    use alloy_primitives::B256;
+
+    /// Tests that three chained pages verify as a chain, and that a reordered or broken chain
+    /// doesn't.
+    #[test]
+    fn test_verify_chain() {
+        let page_values = |page: usize| {
+            (0..4)
+                .map(|i| DynSolValue::String((page * 4 + i).to_string()))
+                .collect::<Vec<DynSolValue>>()
+        };
+        let mut pages = Vec::new();
+        let mut prev_root = B256::ZERO;
+        for page in 0..3 {
+            let values = page_values(page);
+            let tree = WindowedTree::new_page(prev_root, &values).unwrap();
+            for value in values.iter() {
+                let proof = tree.tree().get_proof(value).unwrap();
+                assert!(tree.tree().verify_proof(value, proof));
+            }
+            assert!(verify_link(tree.root(), prev_root, &tree.link_proof()));
+            prev_root = tree.root();
+            pages.push(tree);
+        }
+        let roots = pages.iter().map(WindowedTree::root).collect::<Vec<B256>>();
+
+        assert!(WindowedTree::verify_chain(&pages, &roots));
+        assert!(WindowedTree::verify_chain(&pages[1..], &roots[1..]));
+        assert!(!WindowedTree::verify_chain(&pages, &roots[..2]));
+
+        let mut swapped = roots.clone();
+        swapped.swap(1, 2);
+        assert!(!WindowedTree::verify_chain(&pages, &swapped));
+
+        // A page linking to the wrong root breaks the chain.
+        let forged = WindowedTree::new_page(B256::ZERO, &page_values(1)).unwrap();
+        let forged_roots = [roots[0], forged.root()];
+        assert!(!WindowedTree::verify_chain(
+            &[pages.remove(0), forged],
+            &forged_roots
+        ));
+        assert!(!verify_link(roots[1], B256::ZERO, &pages[0].link_proof()));
+    }
+
+    /// Tests that a value of a page that looks like a link leaf doesn't verify as the link.
+    #[test]
+    fn test_verify_link_is_positional() {
+        let prev_root = B256::repeat_byte(0x11);
+        let fake_root = B256::repeat_byte(0x22);
+        for count in 1..6 {
+            let mut values = (0..count)
+                .map(|i| DynSolValue::String(i.to_string()))
+                .collect::<Vec<DynSolValue>>();
+            values.push(DynSolValue::FixedBytes(fake_root, 32));
+            let page = WindowedTree::new_page(prev_root, &values).unwrap();
+
+            let fake_proof = page.tree().get_proof(&values[count]).unwrap();
+            assert!(page.tree().verify_proof(&values[count], fake_proof.clone()));
+            assert!(!verify_link(page.root(), fake_root, &fake_proof));
+            assert!(verify_link(page.root(), prev_root, &page.link_proof()));
+        }
+
+        // A value equal to the link leaf leaves the link itself unaffected.
+        let values = [DynSolValue::FixedBytes(prev_root, 32)];
+        let page = WindowedTree::new_page(prev_root, &values).unwrap();
+        assert!(verify_link(page.root(), prev_root, &page.link_proof()));
+        assert!(!verify_link(page.root(), fake_root, &page.link_proof()));
+    }
+}